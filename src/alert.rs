@@ -0,0 +1,224 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::LocalWallet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    market::MarketFeed,
+    store,
+    twap::now,
+    types::Sink,
+    when::{self, Condition},
+};
+
+/// Seconds between checks of the saved alerts
+const CHECK_INTERVAL: u64 = 10;
+
+/// Seconds a command or webhook sink may take before it's abandoned
+const SINK_TIMEOUT: u64 = 10;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub id: u64,
+    pub condition: Condition,
+    pub sinks: Vec<Sink>,
+    /// Whether the condition held at the last check, an alert only fires when this turns true
+    pub active: bool,
+    pub triggered: u32,
+    pub last_triggered: Option<u64>,
+    pub created_at: u64,
+}
+
+impl Alert {
+    pub fn new(condition: Condition, sinks: Vec<Sink>) -> Result<Self, anyhow::Error> {
+        store::create(
+            "alert",
+            Self {
+                id: 0,
+                condition,
+                sinks,
+                active: false,
+                triggered: 0,
+                last_triggered: None,
+                created_at: now(),
+            },
+            |job, id| job.id = id,
+        )
+    }
+
+    pub fn load(id: u64) -> Result<Option<Self>, anyhow::Error> {
+        store::load("alert", id)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        store::save("alert", self.id, self)
+    }
+
+    /// Deletes a saved alert, returns false if it doesn't exist
+    pub fn remove(id: u64) -> Result<bool, anyhow::Error> {
+        store::remove("alert", id)
+    }
+
+    pub fn list() -> Result<Vec<Self>, anyhow::Error> {
+        store::list("alert")
+    }
+
+    pub fn print(&self) {
+        println!("{}", "---".repeat(20));
+        println!("Alert Id: {}", self.id);
+        println!("Condition: {}", self.condition);
+        println!(
+            "Sinks: {}",
+            self.sinks.iter().map(sink_name).collect::<Vec<_>>().join(", ")
+        );
+        println!("Active: {}", if self.active { "yes" } else { "no" });
+        println!("Triggered: {} times", self.triggered);
+    }
+
+    /// Delivers the alert to every sink, a failing sink doesn't stop the others
+    pub async fn notify(&self, current: &str) {
+        let message = format!("Alert {}: {} ({})", self.id, self.condition, current);
+
+        for sink in self.sinks.iter() {
+            if let Err(err) = deliver(sink, self, &message).await {
+                println!("Failed to deliver alert {} to {}: {:#?}", self.id, sink_name(sink), err);
+            }
+        }
+    }
+}
+
+fn sink_name(sink: &Sink) -> String {
+    match sink {
+        Sink::Stdout => "stdout".to_string(),
+        Sink::Desktop => "desktop".to_string(),
+        Sink::Command(command) => format!("command:{}", command),
+        Sink::Webhook(url) => format!("webhook:{}", url),
+    }
+}
+
+async fn deliver(sink: &Sink, alert: &Alert, message: &str) -> Result<(), anyhow::Error> {
+    let mut command = match sink {
+        Sink::Stdout => {
+            println!("{}", message);
+            return Ok(());
+        }
+        Sink::Webhook(url) => {
+            let body = serde_json::json!({
+                "id": alert.id,
+                "condition": alert.condition.to_string(),
+                "message": message,
+                "triggeredAt": alert.last_triggered,
+            });
+
+            reqwest::Client::new()
+                .post(url)
+                .timeout(Duration::from_secs(SINK_TIMEOUT))
+                .json(&body)
+                .send()
+                .await?
+                .error_for_status()?;
+
+            return Ok(());
+        }
+        Sink::Desktop if cfg!(target_os = "macos") => {
+            // the message is passed as an argument, no escaping can break the script
+            let mut command = tokio::process::Command::new("osascript");
+            command
+                .arg("-e")
+                .arg("on run argv")
+                .arg("-e")
+                .arg("display notification (item 1 of argv) with title \"Hyperliquid\"")
+                .arg("-e")
+                .arg("end run")
+                .arg(message);
+            command
+        }
+        Sink::Desktop => {
+            let mut command = tokio::process::Command::new("notify-send");
+            command.arg("Hyperliquid").arg(message);
+            command
+        }
+        Sink::Command(script) => {
+            let mut command = tokio::process::Command::new("sh");
+            command
+                .arg("-c")
+                .arg(script)
+                .env("HL_ALERT_ID", alert.id.to_string())
+                .env("HL_ALERT_CONDITION", alert.condition.to_string())
+                .env("HL_ALERT_MESSAGE", message);
+            command
+        }
+    };
+
+    let status = tokio::time::timeout(Duration::from_secs(SINK_TIMEOUT), command.status()).await??;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("Exited with {}", status));
+    }
+
+    Ok(())
+}
+
+/// Checks every saved alert and fires the ones whose condition started holding. An alert fires
+/// again only after its condition stopped holding in between. An alert that fails to be checked
+/// or saved is reported and skipped, the others are still checked.
+pub async fn check(feed: &MarketFeed, wallet: &LocalWallet) -> Result<(), anyhow::Error> {
+    let mut alerts = Alert::list()?;
+
+    for alert in alerts.iter_mut() {
+        // each alert reads its own values, an operand that fails e.g a delisted coin only holds back its alert
+        let values = match when::values(&[&alert.condition], feed, wallet).await {
+            Ok(values) => values,
+            Err(err) => {
+                println!("Failed to check alert {}: {:#?}", alert.id, err);
+                continue;
+            }
+        };
+
+        let holds = alert.condition.evaluate(&values);
+        if holds == alert.active {
+            continue;
+        }
+
+        alert.active = holds;
+
+        if holds {
+            alert.triggered += 1;
+            alert.last_triggered = Some(now());
+
+            let current = values
+                .iter()
+                .map(|(operand, value)| format!("{}: {}", operand, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            alert.notify(&current).await;
+        }
+
+        // a removed alert isn't saved back
+        let saved = match Alert::load(alert.id) {
+            Ok(Some(_)) => alert.save(),
+            Ok(None) => Ok(()),
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = saved {
+            println!("Failed to update alert {}: {:#?}", alert.id, err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the saved alerts every 10 seconds, until cancelled
+pub async fn watch(feed: &MarketFeed, wallet: Arc<LocalWallet>) {
+    loop {
+        if let Err(err) = check(feed, &wallet).await {
+            println!("Failed to check alerts: {:#?}", err);
+        }
+
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::LocalWallet;
+use hyperliquid::{
+    types::exchange::request::{Limit, OrderRequest, OrderType, Tif},
+    utils::{parse_price, parse_size},
+    Exchange,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    journal::Journal,
+    market::MarketFeed,
+    pair::{execute_legs, Leg},
+    types::Recovery,
+};
+
+/// What a basket's tp and sl are compared against
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BasketTrigger {
+    /// Combined unrealized PnL of the legs in USD
+    #[default]
+    Pnl,
+    /// Sum of each leg's weight times its market price
+    Index,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BasketJob {
+    /// Signed weight per asset, negative weights are shorts
+    pub legs: Vec<(String, f64)>,
+    /// Takes the legs as weighted when true, flips every leg otherwise
+    pub is_buy: bool,
+    /// Total order size in USD, split between the legs by the size of their weights
+    pub size: f64,
+    pub tp: Option<f64>,
+    pub sl: Option<f64>,
+    #[serde(default)]
+    pub trigger: BasketTrigger,
+    /// What to do when a leg doesn't fully fill on entry
+    #[serde(default)]
+    pub recovery: Recovery,
+    /// How far from a leg's entry price its missing size may be retried, e.g 0.01 for 1%
+    pub max_slippage: f64,
+}
+
+impl BasketJob {
+    pub fn describe(&self) -> String {
+        format!(
+            "basket {} {} ${}",
+            if self.is_buy { "buy" } else { "sell" },
+            self.legs
+                .iter()
+                .map(|(symbol, weight)| format!("{}:{:+}", symbol, weight))
+                .collect::<Vec<_>>()
+                .join(","),
+            self.size
+        )
+    }
+
+    /// Whether the basket holds an asset long
+    fn is_long(&self, weight: f64) -> bool {
+        (weight > 0.0) == self.is_buy
+    }
+
+    async fn prices(&self, feed: &MarketFeed) -> Result<Vec<f64>, anyhow::Error> {
+        let mut prices = Vec::with_capacity(self.legs.len());
+
+        for (symbol, _) in self.legs.iter() {
+            prices.push(feed.mark_px(symbol).await?);
+        }
+
+        Ok(prices)
+    }
+
+    fn index(&self, prices: &[f64]) -> f64 {
+        self.legs
+            .iter()
+            .zip(prices)
+            .map(|((_, weight), price)| weight * price)
+            .sum()
+    }
+}
+
+/// IOC order with 3% slippage from the market price
+fn order(
+    (sz_decimals, asset): (u32, u32),
+    is_buy: bool,
+    sz: f64,
+    market_price: f64,
+    reduce_only: bool,
+) -> OrderRequest {
+    let slippage = 3.0 / 100.0;
+
+    OrderRequest {
+        cloid: None,
+        asset,
+        is_buy,
+        limit_px: parse_price(if is_buy {
+            market_price * (1.0 + slippage)
+        } else {
+            market_price * (1.0 - slippage)
+        }),
+        sz: parse_size(sz, sz_decimals),
+        reduce_only,
+        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+    }
+}
+
+/// Enters every leg of a basket together, then monitors the combined PnL or the index until the
+/// tp or sl is hit and closes every leg
+pub async fn run(
+    job: BasketJob,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) {
+    let mut leg_assets = Vec::with_capacity(job.legs.len());
+    for (symbol, _) in job.legs.iter() {
+        match assets.get(symbol) {
+            Some(asset) => leg_assets.push(*asset),
+            None => {
+                println!("Failed to find asset: {}", symbol);
+                return;
+            }
+        }
+    }
+
+    let prices = loop {
+        match job.prices(feed).await {
+            Ok(prices) => break prices,
+            Err(err) => {
+                println!("Failed to fetch prices: {:#?}. Checking again in 5 seconds\n---", err);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    };
+
+    let total_weight = job.legs.iter().map(|(_, weight)| weight.abs()).sum::<f64>();
+
+    let mut legs = job
+        .legs
+        .iter()
+        .zip(leg_assets.iter())
+        .zip(prices.iter())
+        .enumerate()
+        .map(|(i, (((symbol, weight), asset), market_price))| {
+            let usd = job.size * weight.abs() / total_weight;
+            let sz = usd / market_price;
+            let is_buy = job.is_long(*weight);
+
+            println!("{}", "---".repeat(20));
+            println!("Order {} of {}", i + 1, job.legs.len());
+            println!("Side: {}", if is_buy { "Buy" } else { "Sell" });
+            println!("Size in {}: {}", symbol, parse_size(sz, asset.0));
+            println!("Size in USD: {:.2}", usd);
+            println!("Market price: {}\n", market_price);
+
+            Leg::new(symbol, order(*asset, is_buy, sz, *market_price, false), asset.0, *market_price)
+        })
+        .collect::<Vec<_>>();
+
+    println!("Index: {}\n", job.index(&prices));
+
+    let filled = execute_legs(
+        feed,
+        exchange,
+        wallet.clone(),
+        &mut legs,
+        (job.recovery == Recovery::Retry).then_some(job.max_slippage),
+        true,
+        journal,
+    )
+    .await;
+
+    if !filled {
+        return;
+    }
+
+    if job.tp.is_none() && job.sl.is_none() {
+        return;
+    }
+
+    // signed size and entry price of every leg
+    let positions = legs
+        .iter()
+        .zip(job.legs.iter())
+        .map(|(leg, (_, weight))| {
+            let sz = if job.is_long(*weight) { leg.filled_sz } else { -leg.filled_sz };
+
+            (sz, leg.filled_usd / leg.filled_sz)
+        })
+        .collect::<Vec<_>>();
+
+    println!("Monitoring the basket for tp or sl\n---");
+
+    let prices = loop {
+        let prices = match job.prices(feed).await {
+            Ok(prices) => prices,
+            Err(err) => {
+                println!("Failed to fetch prices: {:#?}. Checking again in 5 seconds\n---", err);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        let pnl = positions
+            .iter()
+            .zip(prices.iter())
+            .map(|((sz, entry_px), price)| sz * (price - entry_px))
+            .sum::<f64>();
+        let index = job.index(&prices);
+
+        // the pnl rises with the basket, the index rises with a bought basket
+        let (value, rising) = match job.trigger {
+            BasketTrigger::Pnl => (pnl, true),
+            BasketTrigger::Index => (index, job.is_buy),
+        };
+
+        if let Some(tp) = job.tp {
+            if (rising && value >= tp) || (!rising && value <= tp) {
+                println!("Take profit reached: {:.4} {} {}", value, if rising { ">=" } else { "<=" }, tp);
+                break prices;
+            }
+        }
+
+        if let Some(sl) = job.sl {
+            if (rising && value <= sl) || (!rising && value >= sl) {
+                println!("Stop loss reached: {:.4} {} {}", value, if rising { "<=" } else { ">=" }, sl);
+                break prices;
+            }
+        }
+
+        println!(
+            "Basket PnL: {:.2}, Index: {:.4}, Tp: {}, Sl: {}. Checking again in 5 seconds\n---",
+            pnl,
+            index,
+            job.tp.map_or("-".to_string(), |tp| tp.to_string()),
+            job.sl.map_or("-".to_string(), |sl| sl.to_string()),
+        );
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    };
+
+    // place exit orders
+    let mut exits = legs
+        .iter()
+        .zip(job.legs.iter())
+        .zip(leg_assets.iter())
+        .zip(prices.iter())
+        .map(|(((leg, (symbol, weight)), asset), market_price)| {
+            let is_buy = !job.is_long(*weight);
+
+            println!("{}", "---".repeat(20));
+            println!("Side: {}", if is_buy { "Buy" } else { "Sell" });
+            println!("Size in {}: {}", symbol, parse_size(leg.filled_sz, asset.0));
+
+            Leg::new(
+                symbol,
+                order(*asset, is_buy, leg.filled_sz, *market_price, true),
+                asset.0,
+                *market_price,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // an exit is never unwound, what is left of it is retried
+    execute_legs(
+        feed,
+        exchange,
+        wallet.clone(),
+        &mut exits,
+        Some(3.0 / 100.0),
+        false,
+        journal,
+    )
+    .await;
+}
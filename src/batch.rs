@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use clap::ArgMatches;
+use ethers::signers::LocalWallet;
+use futures::FutureExt;
+use hyperliquid::types::exchange::{request::CancelRequest, response::Response};
+
+use crate::{
+    command::command,
+    journal::{Journal, JournalEntry},
+    startup::{redact, run, Session},
+    types::Config,
+};
+
+/// Commands that always place orders, one of them placing none failed
+const ORDER_COMMANDS: [&str; 5] = ["buy", "sell", "tp", "sl", "scale"];
+
+/// Commands that can print the orders they'd place without placing them
+const PREVIEW_COMMANDS: [&str; 6] = ["buy", "sell", "tp", "sl", "scale", "rebalance"];
+
+/// A command of the batch with its variables filled in
+struct Line {
+    number: usize,
+    /// The command with the values of its secret arguments redacted, as it's journaled
+    command_line: String,
+    matches: ArgMatches,
+}
+
+fn is_variable_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Fills `$name` and `${name}` in. A `$` not followed by a name, e.g `$500`, is kept as is
+fn expand(text: &str, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        let name = match chars.peek() {
+            Some('{') => {
+                chars.next();
+                let name = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                if name.is_empty() || !name.chars().all(is_variable_char) {
+                    return Err(format!("Invalid variable ${{{}}}", name));
+                }
+                name
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| is_variable_char(*c)) {
+                    name.push(c);
+                }
+                name
+            }
+            _ => {
+                expanded.push(c);
+                continue;
+            }
+        };
+
+        match variables.get(&name) {
+            Some(value) => expanded.push_str(value),
+            None => return Err(format!("Undefined variable ${}", name)),
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Reads `name = value` definitions and commands, one per line. Blank lines and lines starting
+/// with `#` are skipped and commands may start with `hl`. Every command is checked before any runs.
+/// Variables given up front win over definitions of the same name in the source.
+fn parse(source: &str, mut variables: HashMap<String, String>) -> Result<Vec<Line>, String> {
+    let given = variables.keys().cloned().collect::<Vec<_>>();
+    let mut lines = vec![];
+
+    for (index, text) in source.lines().enumerate() {
+        let number = index + 1;
+        let text = text.trim();
+
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let text = expand(text, &variables).map_err(|err| format!("Line {}: {}", number, err))?;
+
+        if let Some((name, value)) = text.split_once('=') {
+            let name = name.trim();
+
+            if !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(is_variable_char)
+            {
+                if !given.iter().any(|given| given == name) {
+                    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                    variables.insert(name.to_string(), value.to_string());
+                }
+                continue;
+            }
+        }
+
+        let text = text.strip_prefix("hl ").unwrap_or(&text).trim().to_string();
+
+        let words = shlex::split(&text).ok_or(format!("Line {}: unbalanced quotes", number))?;
+
+        let words = std::iter::once("hl".to_string()).chain(words).collect::<Vec<_>>();
+
+        let matches = command()
+            .try_get_matches_from(&words)
+            .map_err(|err| format!("Line {}: {}", number, err.to_string().trim()))?;
+
+        if let Some(name @ ("run" | "shell")) = matches.subcommand_name() {
+            return Err(format!("Line {}: {} can't be used in a batch", number, name));
+        }
+
+        lines.push(Line {
+            number,
+            command_line: redact(&words, &matches),
+            matches,
+        });
+    }
+
+    Ok(lines)
+}
+
+/// Cancels the orders of the batch that are still open. Filled orders can't be undone
+async fn rollback(session: &Session, config: &Config, placed: &[JournalEntry]) {
+    let open = placed
+        .iter()
+        .filter(|entry| entry.status.as_deref() != Some("filled"))
+        .filter_map(|entry| {
+            entry.oid.map(|oid| CancelRequest {
+                asset: entry.asset,
+                oid,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if open.is_empty() {
+        println!("No open orders to cancel");
+        return;
+    }
+
+    let wallet = match config.private_key.parse::<LocalWallet>() {
+        Ok(wallet) => Arc::new(wallet),
+        Err(_) => {
+            println!("Error: Invalid private key");
+            return;
+        }
+    };
+
+    let oids = open
+        .iter()
+        .map(|cancel| cancel.oid.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match session.exchange.cancel_order(wallet, open, None).await {
+        Ok(Response::Ok(_)) => println!("Rolled back by cancelling orders {}", oids),
+        Ok(Response::Err(err)) => println!("Failed to cancel orders {}: {}", oids, err),
+        Err(err) => println!("Failed to cancel orders {}: {:#?}", oids, err),
+    }
+}
+
+/// Prints the orders each command would place with its sizes and prices resolved against the
+/// market, without placing any. Commands that can't be previewed are listed as they'd run
+async fn preview(config: &mut Config, session: &Session, lines: Vec<Line>) {
+    let total = lines.len();
+    println!("{}", "---".repeat(20));
+    println!("Dry run, {} commands would run in order", total);
+
+    for (index, line) in lines.into_iter().enumerate() {
+        println!("{}", "---".repeat(20));
+        println!("[{}/{}] Line {}: {}", index + 1, total, line.number, line.command_line);
+
+        let name = line.matches.subcommand_name().unwrap_or_default().to_string();
+        if !PREVIEW_COMMANDS.contains(&name.as_str()) {
+            println!("Not previewed, {} runs as is", name);
+            continue;
+        }
+
+        // a command relying on what an earlier line opens can't resolve until that line ran
+        let previewed = AssertUnwindSafe(run(config, session, line.matches, &line.command_line, true))
+            .catch_unwind()
+            .await
+            .unwrap_or(false);
+
+        if !previewed {
+            println!("Line {} couldn't be previewed, it may rely on an earlier line of the batch", line.number);
+        }
+    }
+}
+
+/// Runs the commands of a batch file, or of stdin, in order and stops at the first one that fails
+pub async fn execute(config: &mut Config, session: &Session, matches: &ArgMatches) {
+    let file = matches.get_one::<String>("file").expect("File has a default");
+    let dry_run = matches.get_flag("dry-run");
+    let cancel_on_failure = matches.get_flag("cancel-on-failure");
+
+    let source = if file == "-" {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source).map(|_| source)
+    } else {
+        std::fs::read_to_string(file)
+    };
+
+    let source = match source {
+        Ok(source) => source,
+        Err(err) => {
+            println!("Failed to read {}: {}", file, err);
+            return;
+        }
+    };
+
+    let variables = match matches
+        .get_many::<String>("var")
+        .unwrap_or_default()
+        .map(|var| {
+            var.split_once('=')
+                .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+                .ok_or(format!("Invalid variable {}, expected name=value", var))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()
+    {
+        Ok(variables) => variables,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+
+    let lines = match parse(&source, variables) {
+        Ok(lines) => lines,
+        Err(err) => {
+            println!("Invalid batch, nothing was run\n{}", err);
+            return;
+        }
+    };
+
+    if lines.is_empty() {
+        println!("No commands to run");
+        return;
+    }
+
+    if dry_run {
+        preview(config, session, lines).await;
+        return;
+    }
+
+    let journal = match Journal::open() {
+        Ok(journal) => journal,
+        Err(err) => {
+            println!("Failed to open trade journal: {:#?}", err);
+            return;
+        }
+    };
+
+    let total = lines.len();
+    let mut placed: Vec<JournalEntry> = vec![];
+
+    for (index, line) in lines.into_iter().enumerate() {
+        println!("{}", "---".repeat(20));
+        println!("[{}/{}] Line {}: {}", index + 1, total, line.number, line.command_line);
+
+        let command_line = line.command_line;
+        let name = line.matches.subcommand_name().unwrap_or_default().to_string();
+
+        let before = match journal.last_command_id() {
+            Ok(id) => id,
+            Err(err) => {
+                println!("Failed to read trade journal: {:#?}", err);
+                return;
+            }
+        };
+
+        let succeeded = AssertUnwindSafe(run(config, session, line.matches, &command_line, false))
+            .catch_unwind()
+            .await
+            .unwrap_or(false);
+
+        let orders = journal
+            .command_orders(before, &command_line)
+            .unwrap_or_else(|err| {
+                println!("Failed to read trade journal: {:#?}", err);
+                vec![]
+            });
+
+        let failure = if !succeeded {
+            Some("the command failed".to_string())
+        } else if let Some(order) = orders
+            .iter()
+            .find(|order| order.status.as_deref() == Some("error"))
+        {
+            Some(format!(
+                "order {} was rejected: {}",
+                order.id,
+                order.detail.clone().unwrap_or_default()
+            ))
+        } else if orders.is_empty() && ORDER_COMMANDS.contains(&name.as_str()) {
+            Some("no order was placed".to_string())
+        } else {
+            None
+        };
+
+        placed.extend(orders);
+
+        if let Some(failure) = failure {
+            println!("{}", "---".repeat(20));
+            println!(
+                "Line {} failed, {}. Stopped after {} of {} commands",
+                line.number,
+                failure,
+                index + 1,
+                total
+            );
+
+            if cancel_on_failure {
+                rollback(session, config, &placed).await;
+            } else {
+                let open = placed
+                    .iter()
+                    .filter(|entry| entry.oid.is_some() && entry.status.as_deref() != Some("filled"))
+                    .count();
+                println!("{} orders of the batch are left open", open);
+            }
+
+            return;
+        }
+    }
+
+    println!("{}", "---".repeat(20));
+    println!(
+        "Batch done, {} commands ran and placed {} orders",
+        total,
+        placed.len()
+    );
+}
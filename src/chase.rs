@@ -0,0 +1,238 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid::{
+    types::exchange::{
+        request::{CancelRequest, Limit, OrderRequest, OrderType, Tif},
+        response::{Response, Status, StatusType},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+
+use crate::{
+    helpers::{order_fills, place_order},
+    journal::Journal,
+    twap::{place_slice, Slice},
+};
+
+/// Seconds between top of book checks
+const CHECK_INTERVAL: u64 = 2;
+
+/// Rests a post-only order at the best bid/ask and re-prices it as the top of book moves
+pub struct Chase {
+    pub max_reprices: u32,
+    /// Stops chasing once the top of book moved this fraction away from the start price
+    pub max_distance: f64,
+    /// Takes whatever is left at market once chasing stops, instead of leaving it resting
+    pub fallback_ioc: bool,
+}
+
+/// What a chase ended with
+pub struct Chased {
+    /// Filled size in the base asset
+    pub filled_sz: f64,
+    /// Filled size in USD
+    pub filled_usd: f64,
+    /// Order id and price of the order left resting, if any
+    pub resting: Option<(u64, f64)>,
+}
+
+impl Chased {
+    /// Average fill price, or the resting price if nothing was filled
+    pub fn entry_px(&self) -> Option<f64> {
+        if self.filled_sz > 0.0 {
+            Some(self.filled_usd / self.filled_sz)
+        } else {
+            self.resting.map(|(_, px)| px)
+        }
+    }
+}
+
+/// Best bid for a buy, best ask for a sell
+async fn top_of_book(info: &Info, symbol: &str, is_buy: bool) -> Result<f64, anyhow::Error> {
+    let book = info.l2_book(symbol.to_uppercase()).await?;
+
+    let level = book
+        .levels
+        .get(if is_buy { 0 } else { 1 })
+        .and_then(|levels| levels.first())
+        .ok_or_else(|| anyhow::anyhow!("Empty order book for {}", symbol))?;
+
+    Ok(level.px.parse::<f64>()?)
+}
+
+impl Chase {
+    /// Whether the reprices or the distance from the start price are used up, checked before
+    /// every reprice
+    fn exhausted(&self, reprices: u32, start_px: f64, best_px: f64) -> bool {
+        let distance = (best_px - start_px).abs() / start_px;
+        let exhausted = reprices >= self.max_reprices || distance > self.max_distance;
+
+        if exhausted {
+            println!(
+                "Stopped chasing after {} reprices, {:.2}% away from {}",
+                reprices,
+                distance * 100.0,
+                start_px
+            );
+        }
+
+        exhausted
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    chase: &Chase,
+    info: &Info,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    symbol: &str,
+    (sz_decimals, asset): (u32, u32),
+    is_buy: bool,
+    sz: f64,
+    journal: &Journal,
+) -> Result<Chased, anyhow::Error> {
+    let start_px = top_of_book(info, symbol, is_buy).await?;
+
+    let mut chased = Chased {
+        filled_sz: 0.0,
+        filled_usd: 0.0,
+        resting: None,
+    };
+    // every order placed after the first is a reprice
+    let mut reprices = 0;
+    let mut placed = false;
+
+    println!(
+        "Chasing the best {} from {}, up to {} reprices or {}% away\n---",
+        if is_buy { "bid" } else { "ask" },
+        start_px,
+        chase.max_reprices,
+        chase.max_distance * 100.0
+    );
+
+    loop {
+        let best_px = match top_of_book(info, symbol, is_buy).await {
+            Ok(best_px) => best_px,
+            Err(err) => {
+                println!("Failed to fetch order book: {:#?}", err);
+                tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+                continue;
+            }
+        };
+
+        if let Some((oid, px)) = chased.resting {
+            let open_orders = info.open_orders(wallet.address()).await?;
+
+            if open_orders.iter().any(|order| order.oid == oid) {
+                if px == best_px {
+                    tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+                    continue;
+                }
+
+                if chase.exhausted(reprices, start_px, best_px) {
+                    break;
+                }
+
+                exchange
+                    .cancel_order(wallet.clone(), vec![CancelRequest { asset, oid }], None)
+                    .await?;
+            }
+
+            // whatever was filled before it left the book, by a fill or the cancel above
+            let (filled_sz, filled_usd) = order_fills(info, wallet.address(), oid).await?;
+            chased.filled_sz += filled_sz;
+            chased.filled_usd += filled_usd;
+            chased.resting = None;
+        }
+
+        let remaining = parse_size(sz - chased.filled_sz, sz_decimals);
+        if remaining.parse::<f64>().unwrap_or(0.0) <= 0.0 {
+            break;
+        }
+
+        if placed {
+            if chase.exhausted(reprices, start_px, best_px) {
+                break;
+            }
+            reprices += 1;
+        }
+        placed = true;
+
+        let order = OrderRequest {
+            cloid: None,
+            asset,
+            is_buy,
+            limit_px: parse_price(best_px),
+            sz: remaining.clone(),
+            reduce_only: false,
+            order_type: OrderType::Limit(Limit { tif: Tif::Alo }),
+        };
+
+        match place_order(exchange, wallet.clone(), vec![order], journal).await? {
+            Response::Ok(order) => {
+                if let Some(StatusType::Statuses(statuses)) = order.data {
+                    for status in statuses {
+                        match status {
+                            Status::Resting(order) => {
+                                println!("Order {} resting at {} for {} {}", order.oid, best_px, remaining, symbol);
+                                chased.resting = Some((order.oid, best_px));
+                            }
+                            Status::Filled(order) => {
+                                let total_sz = order.total_sz.parse::<f64>().unwrap_or(0.0);
+                                let avg_px = order.avg_px.parse::<f64>().unwrap_or(0.0);
+
+                                chased.filled_sz += total_sz;
+                                chased.filled_usd += total_sz * avg_px;
+                            }
+                            // post-only orders are rejected when the book moved through the price
+                            Status::Error(msg) => {
+                                println!("Order at {} rejected: {:#?}", best_px, msg);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Response::Err(err) => return Err(anyhow::anyhow!(err)),
+        }
+
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+    }
+
+    if chase.fallback_ioc {
+        if let Some((oid, _)) = chased.resting.take() {
+            exchange
+                .cancel_order(wallet.clone(), vec![CancelRequest { asset, oid }], None)
+                .await?;
+
+            let (filled_sz, filled_usd) = order_fills(info, wallet.address(), oid).await?;
+            chased.filled_sz += filled_sz;
+            chased.filled_usd += filled_usd;
+        }
+
+        let remaining = sz - chased.filled_sz;
+        if parse_size(remaining, sz_decimals).parse::<f64>().unwrap_or(0.0) > 0.0 {
+            println!("Falling back to market for {} {}\n", parse_size(remaining, sz_decimals), symbol);
+
+            let slice = Slice {
+                asset,
+                sz_decimals,
+                is_buy,
+                sz: remaining,
+                market_price: top_of_book(info, symbol, is_buy).await?,
+                limit_px: None,
+                reduce_only: false,
+            };
+
+            let (filled_sz, filled_usd) = place_slice(exchange, wallet, slice, journal).await?;
+            chased.filled_sz += filled_sz;
+            chased.filled_usd += filled_usd;
+        }
+    }
+
+    Ok(chased)
+}
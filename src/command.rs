@@ -1,5 +1,5 @@
 //using version 2.33 not the latest one
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 
 pub fn command() -> Command {
     Command::new(env!("CARGO_PKG_NAME"))
@@ -74,6 +74,7 @@ pub fn command() -> Command {
         .subcommand(
             Command::new("tp")
                 .about("Takes profit on open order as a market order")
+                .args_conflicts_with_subcommands(true)
                 .arg(
                     Arg::new("size")
                         .required(true)
@@ -96,6 +97,52 @@ pub fn command() -> Command {
                             "Take profit price or %/$ gain in asset before tp or %$ gain in pnl before tp"
                         )
                 )
+                .subcommand(
+                    Command::new("ladder")
+                        .about("Scales out of an open position with reduce-only orders spread across a price range")
+                        .arg(
+                            Arg::new("asset")
+                                .required(true)
+                                .index(1)
+                                .help("Asset symbol e.g ETH, SOL, BTC")
+                        )
+                        .arg(
+                            Arg::new("size")
+                                .required(true)
+                                .index(2)
+                                .help("% of the position to take profit on")
+                        )
+                        .arg(
+                            Arg::new("from")
+                                .long("from")
+                                .required(true)
+                                .help("Price of the first rung or %/$ gain from the entry price e.g 2000, 5% or +100")
+                        )
+                        .arg(
+                            Arg::new("to")
+                                .long("to")
+                                .required(true)
+                                .help("Price of the last rung or %/$ gain from the entry price e.g 2200, 15% or +300")
+                        )
+                        .arg(
+                            Arg::new("rungs")
+                                .long("rungs")
+                                .required(true)
+                                .help("Number of orders")
+                        )
+                        .arg(
+                            Arg::new("trigger")
+                                .long("trigger")
+                                .action(ArgAction::SetTrue)
+                                .help("Places market trigger orders instead of resting limit orders")
+                        )
+                        .arg(
+                            Arg::new("watch")
+                                .long("watch")
+                                .action(ArgAction::SetTrue)
+                                .help("Keeps running and re-balances the open rungs whenever the position size changes")
+                        )
+                )
         )
         .subcommand(
             Command::new("sl")
@@ -153,6 +200,30 @@ pub fn command() -> Command {
                         .help("Stop loss value")
                         .long("sl")
                 )
+                .arg(
+                    Arg::new("chase")
+                        .help("Rests a post-only order at the best bid and re-prices it as the top of book moves")
+                        .long("chase")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("max_reprices")
+                        .help("Stops chasing after this many reprices")
+                        .long("max-reprices")
+                        .default_value("10")
+                )
+                .arg(
+                    Arg::new("max_distance")
+                        .help("Stops chasing once the top of book moved this % away from the start price e.g 0.5%")
+                        .long("max-distance")
+                        .default_value("0.5%")
+                )
+                .arg(
+                    Arg::new("fallback_ioc")
+                        .help("Takes whatever is left at market once chasing stops, instead of leaving it resting")
+                        .long("fallback-ioc")
+                        .action(ArgAction::SetTrue)
+                )
         )
         .subcommand(
             Command::new("sell")
@@ -184,6 +255,30 @@ pub fn command() -> Command {
                         .help("Stop loss value")
                         .long("sl")
                 )
+                .arg(
+                    Arg::new("chase")
+                        .help("Rests a post-only order at the best ask and re-prices it as the top of book moves")
+                        .long("chase")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("max_reprices")
+                        .help("Stops chasing after this many reprices")
+                        .long("max-reprices")
+                        .default_value("10")
+                )
+                .arg(
+                    Arg::new("max_distance")
+                        .help("Stops chasing once the top of book moved this % away from the start price e.g 0.5%")
+                        .long("max-distance")
+                        .default_value("0.5%")
+                )
+                .arg(
+                    Arg::new("fallback_ioc")
+                        .help("Takes whatever is left at market once chasing stops, instead of leaving it resting")
+                        .long("fallback-ioc")
+                        .action(ArgAction::SetTrue)
+                )
         )
         .subcommand(
             Command::new("twap")
@@ -208,9 +303,47 @@ pub fn command() -> Command {
                                 .required(true)
                                 .index(3)
                                 .help(
-                                    "Time between intervals in minutes, number of intervals e.g 5,10"
+                                    "Time between intervals in minutes or with a s/m/h unit, number of intervals e.g 5,10 or 30s,20"
                                 )
                         )
+                        .arg(
+                            Arg::new("size_jitter")
+                                .long("size-jitter")
+                                .required(false)
+                                .help("Randomizes each slice size by up to +/- this % e.g 20%")
+                        )
+                        .arg(
+                            Arg::new("interval_jitter")
+                                .long("interval-jitter")
+                                .required(false)
+                                .help("Randomizes each interval by up to +/- this % e.g 20%")
+                        )
+                        .arg(
+                            Arg::new("limit")
+                                .long("limit")
+                                .required(false)
+                                .help("Hard limit price, slices are not placed while the market trades beyond it e.g 1900")
+                        )
+                        .arg(
+                            Arg::new("on_limit")
+                                .long("on-limit")
+                                .required(false)
+                                .value_parser(["skip", "defer"])
+                                .default_value("defer")
+                                .help("What to do with a slice beyond the limit price: skip it or defer it to the next interval")
+                        )
+                        .arg(
+                            Arg::new("max_participation")
+                                .long("max-participation")
+                                .required(false)
+                                .help("Caps each slice to this % of the volume traded over the last interval e.g 10%")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the twap to the running daemon instead of running it in the foreground")
+                        )
                 )
                 .subcommand(
                     Command::new("sell")
@@ -232,184 +365,1303 @@ pub fn command() -> Command {
                                 .required(true)
                                 .index(3)
                                 .help(
-                                    "Time between intervals in minutes, number of intervals e.g 5,10"
+                                    "Time between intervals in minutes or with a s/m/h unit, number of intervals e.g 5,10 or 30s,20"
                                 )
                         )
+                        .arg(
+                            Arg::new("size_jitter")
+                                .long("size-jitter")
+                                .required(false)
+                                .help("Randomizes each slice size by up to +/- this % e.g 20%")
+                        )
+                        .arg(
+                            Arg::new("interval_jitter")
+                                .long("interval-jitter")
+                                .required(false)
+                                .help("Randomizes each interval by up to +/- this % e.g 20%")
+                        )
+                        .arg(
+                            Arg::new("limit")
+                                .long("limit")
+                                .required(false)
+                                .help("Hard limit price, slices are not placed while the market trades beyond it e.g 1900")
+                        )
+                        .arg(
+                            Arg::new("on_limit")
+                                .long("on-limit")
+                                .required(false)
+                                .value_parser(["skip", "defer"])
+                                .default_value("defer")
+                                .help("What to do with a slice beyond the limit price: skip it or defer it to the next interval")
+                        )
+                        .arg(
+                            Arg::new("max_participation")
+                                .long("max-participation")
+                                .required(false)
+                                .help("Caps each slice to this % of the volume traded over the last interval e.g 10%")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the twap to the running daemon instead of running it in the foreground")
+                        )
                 )
-        )
-        .subcommand(
-            Command::new("view")
-                .about("Handles the view commands")
                 .subcommand(
-                    Command::new("upnl").about("view unrealized pnl")
+                    Command::new("list")
+                        .about("list all twaps and their progress")
                 )
                 .subcommand(
-                    Command::new("wallet")
-                        .about("view wallet balance")
+                    Command::new("status")
+                        .about("show the progress of a twap")
                         .arg(
-                            Arg::new("balance")
+                            Arg::new("id")
                                 .required(true)
                                 .index(1)
-                                .help("argument to complete the view wallet balance command")
-                            
+                                .help("Twap id")
                         )
                 )
                 .subcommand(
-                    Command::new("unfilled")
-                        .about("view unfilled orders")
+                    Command::new("pause")
+                        .about("pause a running twap")
                         .arg(
-                            Arg::new("orders")
+                            Arg::new("id")
                                 .required(true)
                                 .index(1)
-                                .help("argument to complete the view unfilled orders command")
-                            
+                                .help("Twap id")
                         )
                 )
                 .subcommand(
-                    Command::new("open")
-                        .about("view open positions")
+                    Command::new("resume")
+                        .about("resume a paused or interrupted twap from its last completed slice")
                         .arg(
-                            Arg::new("positions")
+                            Arg::new("id")
                                 .required(true)
                                 .index(1)
-                                .help("argument to complete the view open positions command")
-                            
+                                .help("Twap id")
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .action(ArgAction::SetTrue)
+                                .help("Resume a twap still marked as running whose process has exited")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the twap to the running daemon instead of running it in the foreground")
+                        )
+                )
+                .subcommand(
+                    Command::new("cancel")
+                        .about("cancel a twap, remaining slices will not be placed")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Twap id")
                         )
                 )
         )
         .subcommand(
-            Command::new("pair")
-                .about("Takes 50% of order size and longs Asset X and takes another 50% of order size and shorts Asset Y.")
+            Command::new("pov")
+                .about("Executes the order as a percentage of the volume traded in the asset, placing the part owed at market every few seconds")
                 .subcommand(
                     Command::new("buy")
-                        .about("pair to buy")
+                        .about("pov buy")
                         .arg(
                             Arg::new("size")
                                 .required(true)
                                 .index(1)
-                                .help("Order Size")
+                                .help("Total order size")
                         )
                         .arg(
-                            Arg::new("pair")
+                            Arg::new("asset")
                                 .required(true)
                                 .index(2)
-                                .help("Asset X/Asset Y e.g BTC/SOL")
+                                .help("asset to be traded")
                         )
                         .arg(
-                            Arg::new("price")
-                                .required(false)
-                                .long("price")
-                                .help("Limit price if Commandlicable")
+                            Arg::new("rate")
+                                .long("rate")
+                                .required(true)
+                                .help("Percentage of the traded volume to execute e.g 5%")
                         )
                         .arg(
-                            Arg::new("sl")
+                            Arg::new("limit")
+                                .long("limit")
                                 .required(false)
-                                .long("sl")
-                                .help("Stop loss")
+                                .help("Limit price, volume traded beyond it is not participated in e.g 1900")
                         )
                         .arg(
-                            Arg::new("tp")
+                            Arg::new("deadline")
+                                .long("deadline")
                                 .required(false)
-                                .long("tp")
-                                .help("Take profit")
+                                .help("Stops after this long even if the order isn't fully executed, in minutes or with a s/m/h unit e.g 30 or 2h")
                         )
                 )
                 .subcommand(
                     Command::new("sell")
-                        .about("pair to sell")
+                        .about("pov sell")
                         .arg(
                             Arg::new("size")
                                 .required(true)
                                 .index(1)
-                                .help("Order Size")
+                                .help("Total order size")
                         )
                         .arg(
-                            Arg::new("pair")
+                            Arg::new("asset")
                                 .required(true)
                                 .index(2)
-                                .help("Asset X/Asset Y e.g BTC/SOL")
+                                .help("asset to be traded")
                         )
                         .arg(
-                            Arg::new("price")
-                                .long("price")
-                                .required(false)
-                                .help("Limit price if Commandlicable")
+                            Arg::new("rate")
+                                .long("rate")
+                                .required(true)
+                                .help("Percentage of the traded volume to execute e.g 5%")
                         )
                         .arg(
-                            Arg::new("sl")
+                            Arg::new("limit")
+                                .long("limit")
                                 .required(false)
-                                .long("sl")
-                                .help("stop loss")
+                                .help("Limit price, volume traded beyond it is not participated in e.g 1900")
                         )
                         .arg(
-                            Arg::new("tp")
+                            Arg::new("deadline")
+                                .long("deadline")
                                 .required(false)
-                                .long("tp")
-                                .help("Take profit")
+                                .help("Stops after this long even if the order isn't fully executed, in minutes or with a s/m/h unit e.g 30 or 2h")
                         )
                 )
         )
         .subcommand(
-            Command::new("scale")
-                .about("Divides the total order size by the number of intervals. After the time between intervals, each piece of the divided order will be bought at market")
+            Command::new("iceberg")
+                .about("Keeps a single visible GTC clip at the limit price and places the next one as soon as it fills, until the total is done")
                 .subcommand(
                     Command::new("buy")
-                        .about("scale buy")
+                        .about("iceberg buy")
                         .arg(
-                            Arg::new("size_per_interval")
+                            Arg::new("size")
                                 .required(true)
                                 .index(1)
-                                .help(
-                                    "total order size/number of intervals"
-                                )
+                                .help("Total order size")
                         )
                         .arg(
                             Arg::new("asset")
                                 .required(true)
                                 .index(2)
-                                .help("asset e.g ETH, SOL, BTC")
+                                .help("asset to be traded")
                         )
                         .arg(
-                            Arg::new("lower")
+                            Arg::new("price")
+                                .long("price")
                                 .required(true)
-                                .index(3)
-                                .help("lower price bracket")
+                                .help("Limit price e.g 1900")
                         )
                         .arg(
-                            Arg::new("upper")
+                            Arg::new("show")
+                                .long("show")
                                 .required(true)
-                                .index(4)
-                                .help("upper price bracket")
+                                .help("Size of the single visible clip e.g 100")
                         )
                 )
                 .subcommand(
                     Command::new("sell")
-                        .about("Divides the total order size by the number of intervals. After the time between intervals, each piece of the divided order will be bought at market")
+                        .about("iceberg sell")
                         .arg(
-                            Arg::new("size_per_interval")
+                            Arg::new("size")
                                 .required(true)
                                 .index(1)
-                                .help(
-                                    "total order size/number of intervals"
-                                )
+                                .help("Total order size")
                         )
                         .arg(
                             Arg::new("asset")
                                 .required(true)
                                 .index(2)
-                                .help("asset e.g ETH, SOL, BTC")
+                                .help("asset to be traded")
                         )
                         .arg(
-                            Arg::new("lower")
+                            Arg::new("price")
+                                .long("price")
                                 .required(true)
-                                .index(3)
-                                .help("Lower price bracket")
+                                .help("Limit price e.g 1900")
                         )
                         .arg(
-                            Arg::new("upper")
+                            Arg::new("show")
+                                .long("show")
                                 .required(true)
-                                .index(4)
-                                .help("Upper price bracket")
+                                .help("Size of the single visible clip e.g 100")
+                        )
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("list all icebergs and their progress")
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("show the progress of an iceberg")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Iceberg id")
+                        )
+                )
+                .subcommand(
+                    Command::new("cancel")
+                        .about("cancel an iceberg and its visible clip")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Iceberg id")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("grid")
+                .about("Places a buy/sell grid across a price range and flips every filled level into an order on the next one")
+                .args_conflicts_with_subcommands(true)
+                .arg(
+                    Arg::new("asset")
+                        .required(true)
+                        .index(1)
+                        .help("asset to be traded")
+                )
+                .arg(
+                    Arg::new("lower")
+                        .long("lower")
+                        .required(true)
+                        .help("Lowest grid level price e.g 1800")
+                )
+                .arg(
+                    Arg::new("upper")
+                        .long("upper")
+                        .required(true)
+                        .help("Highest grid level price e.g 2200")
+                )
+                .arg(
+                    Arg::new("levels")
+                        .long("levels")
+                        .required(true)
+                        .help("Number of grid levels, at least 2")
+                )
+                .arg(
+                    Arg::new("size")
+                        .long("size")
+                        .required(true)
+                        .help("Order size of each level in USD e.g 100")
+                )
+                .arg(
+                    Arg::new("stop_lower")
+                        .long("stop-lower")
+                        .required(false)
+                        .help("Cancels the grid and closes its position when the price falls to this level, below --lower")
+                )
+                .arg(
+                    Arg::new("stop_upper")
+                        .long("stop-upper")
+                        .required(false)
+                        .help("Cancels the grid and closes its position when the price rises to this level, above --upper")
+                )
+                .arg(
+                    Arg::new("paper")
+                        .long("paper")
+                        .action(ArgAction::SetTrue)
+                        .help("Runs against a simulated exchange filled from the live mark price, no orders are sent")
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("list all grids and their realized profit")
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("show the orders and realized profit of a grid")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Grid id")
+                        )
+                )
+                .subcommand(
+                    Command::new("cancel")
+                        .about("cancel a grid and its open orders, its position is left open")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Grid id")
+                        )
+                )
+                .subcommand(
+                    Command::new("resume")
+                        .about("pick an interrupted grid back up, a paper grid is placed again around the market price")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Grid id")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the grid to the running daemon instead of running it in the foreground")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("dca")
+                .about("Buys a fixed USD amount of an asset at market on a schedule")
+                .subcommand(
+                    Command::new("add")
+                        .about("schedule a recurring buy")
+                        .arg(
+                            Arg::new("asset")
+                                .required(true)
+                                .index(1)
+                                .help("asset to be bought")
+                        )
+                        .arg(
+                            Arg::new("usd")
+                                .required(true)
+                                .index(2)
+                                .help("Amount bought every run in USD")
+                        )
+                        .arg(
+                            Arg::new("every")
+                                .long("every")
+                                .required(true)
+                                .help("Time between runs, in minutes or with a s/m/h/d unit e.g 1d")
+                        )
+                        .arg(
+                            Arg::new("at")
+                                .long("at")
+                                .required(false)
+                                .help("Time of day in UTC of the first run e.g 14:00, the first run is right away otherwise")
+                        )
+                        .arg(
+                            Arg::new("max_price")
+                                .long("max-price")
+                                .required(false)
+                                .help("Skips a run while the market price is above it e.g 2500")
+                        )
+                        .arg(
+                            Arg::new("missed")
+                                .long("missed")
+                                .default_value("once")
+                                .help("What to do about runs missed while nothing executed the schedule: skip, once or all")
+                        )
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("list all scheduled buys with their last runs")
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("remove a scheduled buy")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Dca id")
+                        )
+                )
+                .subcommand(
+                    Command::new("run")
+                        .about("execute every scheduled buy that is due, then exit")
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Keeps executing the schedules in the running daemon instead, see `hl daemon start`")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("rebalance")
+                .about("Places the orders that bring the open positions to target notional weights")
+                .arg(
+                    Arg::new("targets")
+                        .long("targets")
+                        .required(true)
+                        .help("Target weight per asset, negative for shorts e.g btc=40%,eth=30%,sol=-30%")
+                )
+                .arg(
+                    Arg::new("notional")
+                        .long("notional")
+                        .required(true)
+                        .help("Portfolio notional in USD the weights apply to e.g 10000")
+                )
+                .arg(
+                    Arg::new("band")
+                        .long("band")
+                        .default_value("0%")
+                        .help("Skips assets whose weight drifted from its target by no more than this e.g 2%")
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .action(ArgAction::SetTrue)
+                        .help("Places resting GTC orders at the mark price instead of IOC orders")
+                )
+                .arg(
+                    Arg::new("preview")
+                        .long("preview")
+                        .action(ArgAction::SetTrue)
+                        .help("Only print the preview of the orders without placing them")
+                )
+        )
+        .subcommand(
+            Command::new("when")
+                .about("Watches a condition on mark prices, ratios, funding rates or account value and places an order once it holds")
+                .arg(
+                    Arg::new("condition")
+                        .required(true)
+                        .index(1)
+                        .help("Comparisons combined with and/or e.g \"btc > 70000 and funding(eth) < 0\"")
+                )
+                .arg(
+                    Arg::new("side")
+                        .required(true)
+                        .index(2)
+                        .value_parser(["buy", "sell"])
+                        .help("Order side: buy or sell")
+                )
+                .arg(
+                    Arg::new("asset")
+                        .long("asset")
+                        .required(true)
+                        .help("Asset to order")
+                )
+                .arg(
+                    Arg::new("size")
+                        .long("size")
+                        .required(true)
+                        .help("Order size in USD e.g $500, or a percentage of the account value e.g 10%")
+                )
+                .arg(
+                    Arg::new("price")
+                        .long("price")
+                        .required(false)
+                        .help("Limit price, the order is placed at market with 3% slippage otherwise")
+                )
+                .arg(
+                    Arg::new("expire")
+                        .long("expire")
+                        .required(false)
+                        .help("Stops watching after this long e.g 30m, 4h, 1d")
+                )
+                .arg(
+                    Arg::new("daemon")
+                        .long("daemon")
+                        .action(ArgAction::SetTrue)
+                        .help("Submit the condition to the running daemon instead of watching it in the foreground")
+                )
+        )
+        .subcommand(
+            Command::new("alert")
+                .about("Notifies through stdout, the desktop, a command or a webhook when prices, positions or the account reach a condition")
+                .subcommand(
+                    Command::new("add")
+                        .about("add an alert")
+                        .arg(
+                            Arg::new("condition")
+                                .required(true)
+                                .num_args(1..)
+                                .allow_negative_numbers(true)
+                                .index(1)
+                                .help("Condition as in `hl when` e.g \"eth < 3000\", \"position eth pnl < -200\" or \"liq-distance < 5%\"")
+                        )
+                        .arg(
+                            Arg::new("notify")
+                                .long("notify")
+                                .action(ArgAction::Append)
+                                .default_value("stdout")
+                                .help("Where the alert is delivered, repeatable: stdout, desktop, command:<command> or webhook:<url>")
+                        )
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("list all alerts")
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("remove an alert")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Alert id")
+                        )
+                )
+                .subcommand(
+                    Command::new("test")
+                        .about("deliver a test notification of an alert to its sinks")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Alert id")
+                        )
+                )
+                .subcommand(
+                    Command::new("watch")
+                        .about("check the alerts every 10 seconds and notify when a condition starts holding")
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the watcher to the running daemon instead of running it in the foreground")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("run")
+                .about("Runs a batch of commands from a file or stdin in order, stopping at the first one that fails")
+                .arg(
+                    Arg::new("file")
+                        .index(1)
+                        .default_value("-")
+                        .help("Batch file with one command per line and name = value variables, - or left out reads stdin")
+                )
+                .arg(
+                    Arg::new("var")
+                        .long("var")
+                        .action(ArgAction::Append)
+                        .help("Sets a variable used as $name or ${name} e.g --var size=100, it wins over a definition of the same name in the file")
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Checks the whole batch and previews the orders of every command, sizes and prices resolved against the market, without placing them")
+                )
+                .arg(
+                    Arg::new("cancel-on-failure")
+                        .long("cancel-on-failure")
+                        .action(ArgAction::SetTrue)
+                        .help("Cancels the orders the batch left open when a command fails")
+                )
+        )
+        .subcommand(
+            Command::new("shell")
+                .about("Opens an interactive prompt taking the same commands, keeping the clients and market data warm between them")
+        )
+        .subcommand(
+            Command::new("strategy")
+                .about("Runs Rhai strategy scripts reacting to market data and their own fills")
+                .subcommand(
+                    Command::new("run")
+                        .about("run a strategy on the live mark price until ctrl-c")
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .index(1)
+                                .help("Strategy script e.g my.rhai")
+                        )
+                        .arg(
+                            Arg::new("asset")
+                                .long("asset")
+                                .required(false)
+                                .help("Asset the strategy trades, the default asset in the config when not given")
+                        )
+                        .arg(
+                            Arg::new("paper")
+                                .long("paper")
+                                .action(ArgAction::SetTrue)
+                                .conflicts_with("live")
+                                .required_unless_present("live")
+                                .help("Runs against a simulated exchange filled from the live mark price, no orders are sent")
+                        )
+                        .arg(
+                            Arg::new("live")
+                                .long("live")
+                                .action(ArgAction::SetTrue)
+                                .help("Places real orders on the exchange")
+                        )
+                        .arg(
+                            Arg::new("interval")
+                                .long("interval")
+                                .default_value("5s")
+                                .help("Time between ticks e.g 5s or 1m")
+                        )
+                )
+                .subcommand(
+                    Command::new("test")
+                        .about("replay recorded mark prices through a strategy against a simulated exchange")
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .index(1)
+                                .help("Strategy script e.g my.rhai")
+                        )
+                        .arg(
+                            Arg::new("data")
+                                .required(true)
+                                .index(2)
+                                .help("Recorded time,mark rows e.g from strategy record")
+                        )
+                        .arg(
+                            Arg::new("asset")
+                                .long("asset")
+                                .required(false)
+                                .help("Asset the strategy trades, the default asset in the config when not given")
+                        )
+                )
+                .subcommand(
+                    Command::new("record")
+                        .about("append the mark price of an asset to a file every interval until ctrl-c")
+                        .arg(
+                            Arg::new("asset")
+                                .required(true)
+                                .index(1)
+                                .help("Asset to record")
+                        )
+                        .arg(
+                            Arg::new("data")
+                                .required(true)
+                                .index(2)
+                                .help("File the time,mark rows are appended to")
+                        )
+                        .arg(
+                            Arg::new("interval")
+                                .long("interval")
+                                .default_value("5s")
+                                .help("Time between recorded prices e.g 5s or 1m")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serves orders, positions, balances and daemon jobs as a local JSON API protected by a bearer token")
+                .arg(
+                    Arg::new("bind")
+                        .long("bind")
+                        .default_value("127.0.0.1:8080")
+                        .help("Address to listen on")
+                )
+                .arg(
+                    Arg::new("token")
+                        .long("token")
+                        .required(false)
+                        .help("Bearer token every request must carry, read from HL_API_TOKEN or generated when not given")
+                )
+        )
+        .subcommand(
+            Command::new("webhook")
+                .about("Receives JSON alert signals e.g from TradingView and places the buy, sell, close, tp and sl orders they describe")
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .default_value("8000")
+                        .value_parser(clap::value_parser!(u16))
+                        .help("Port to listen on, on every interface")
+                )
+                .arg(
+                    Arg::new("secret")
+                        .long("secret")
+                        .required(false)
+                        .help("Shared secret every signal must carry, read from HL_WEBHOOK_SECRET or generated when not given")
+                )
+                .arg(
+                    Arg::new("dedup-window")
+                        .long("dedup-window")
+                        .default_value("5m")
+                        .help("How long a signal is remembered, a replay within it is dropped e.g 30s, 5m or 1h")
+                )
+                .arg(
+                    Arg::new("max-per-minute")
+                        .long("max-per-minute")
+                        .default_value("10")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Most signals acted on per minute, the rest are rejected")
+                )
+        )
+        .subcommand(
+            Command::new("basket")
+                .about("Splits the order size between weighted long and short legs entered together")
+                .subcommand(
+                    Command::new("buy")
+                        .about("basket to buy")
+                        .arg(
+                            Arg::new("size")
+                                .required(true)
+                                .index(1)
+                                .help("Order size in USD, split between the legs by the size of their weights")
+                        )
+                        .arg(
+                            Arg::new("legs")
+                                .required(true)
+                                .index(2)
+                                .help("Signed weight per asset, negative weights are shorts e.g btc:+2,eth:+1,sol:-3")
+                        )
+                        .arg(
+                            Arg::new("tp")
+                                .required(false)
+                                .long("tp")
+                                .allow_hyphen_values(true)
+                                .help("Take profit as basket PnL in USD or a percentage of the size e.g 200 or 5%, or an index value with --index")
+                        )
+                        .arg(
+                            Arg::new("sl")
+                                .required(false)
+                                .long("sl")
+                                .allow_hyphen_values(true)
+                                .help("Stop loss as basket loss in USD or a percentage of the size e.g 100 or 2%, or an index value with --index")
+                        )
+                        .arg(
+                            Arg::new("index")
+                                .long("index")
+                                .action(ArgAction::SetTrue)
+                                .help("Compares --tp and --sl to the basket index, the sum of each weight times its market price")
+                        )
+                        .arg(
+                            Arg::new("on_leg_failure")
+                                .long("on-leg-failure")
+                                .default_value("retry")
+                                .help("When a leg doesn't fully fill on entry: retry it within --max-slippage and unwind if it still didn't, or unwind the filled legs right away")
+                        )
+                        .arg(
+                            Arg::new("max_slippage")
+                                .long("max-slippage")
+                                .default_value("1%")
+                                .help("How far from its entry price a leg's missing size may be retried")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the basket to the running daemon instead of running it in the foreground")
+                        )
+                )
+                .subcommand(
+                    Command::new("sell")
+                        .about("basket to sell")
+                        .arg(
+                            Arg::new("size")
+                                .required(true)
+                                .index(1)
+                                .help("Order size in USD, split between the legs by the size of their weights")
+                        )
+                        .arg(
+                            Arg::new("legs")
+                                .required(true)
+                                .index(2)
+                                .help("Signed weight per asset, negative weights are shorts e.g btc:+2,eth:+1,sol:-3")
+                        )
+                        .arg(
+                            Arg::new("tp")
+                                .required(false)
+                                .long("tp")
+                                .allow_hyphen_values(true)
+                                .help("Take profit as basket PnL in USD or a percentage of the size e.g 200 or 5%, or an index value with --index")
+                        )
+                        .arg(
+                            Arg::new("sl")
+                                .required(false)
+                                .long("sl")
+                                .allow_hyphen_values(true)
+                                .help("Stop loss as basket loss in USD or a percentage of the size e.g 100 or 2%, or an index value with --index")
+                        )
+                        .arg(
+                            Arg::new("index")
+                                .long("index")
+                                .action(ArgAction::SetTrue)
+                                .help("Compares --tp and --sl to the basket index, the sum of each weight times its market price")
+                        )
+                        .arg(
+                            Arg::new("on_leg_failure")
+                                .long("on-leg-failure")
+                                .default_value("retry")
+                                .help("When a leg doesn't fully fill on entry: retry it within --max-slippage and unwind if it still didn't, or unwind the filled legs right away")
+                        )
+                        .arg(
+                            Arg::new("max_slippage")
+                                .long("max-slippage")
+                                .default_value("1%")
+                                .help("How far from its entry price a leg's missing size may be retried")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the basket to the running daemon instead of running it in the foreground")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("view")
+                .about("Handles the view commands")
+                .subcommand(
+                    Command::new("upnl").about("view unrealized pnl")
+                )
+                .subcommand(
+                    Command::new("wallet")
+                        .about("view wallet balance")
+                        .arg(
+                            Arg::new("balance")
+                                .required(true)
+                                .index(1)
+                                .help("argument to complete the view wallet balance command")
+                            
+                        )
+                )
+                .subcommand(
+                    Command::new("unfilled")
+                        .about("view unfilled orders")
+                        .arg(
+                            Arg::new("orders")
+                                .required(true)
+                                .index(1)
+                                .help("argument to complete the view unfilled orders command")
+                            
+                        )
+                )
+                .subcommand(
+                    Command::new("open")
+                        .about("view open positions")
+                        .arg(
+                            Arg::new("positions")
+                                .required(true)
+                                .index(1)
+                                .help("argument to complete the view open positions command")
+                            
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("pair")
+                .about("Splits the order size between a long in Asset X and a short in Asset Y.")
+                .subcommand(
+                    Command::new("buy")
+                        .about("pair to buy")
+                        .arg(
+                            Arg::new("size")
+                                .required(true)
+                                .index(1)
+                                .help("Order Size")
+                        )
+                        .arg(
+                            Arg::new("pair")
+                                .required(true)
+                                .index(2)
+                                .help("Asset X/Asset Y e.g BTC/SOL")
+                        )
+                        .arg(
+                            Arg::new("price")
+                                .required(false)
+                                .allow_negative_numbers(true)
+                                .long("price")
+                                .help("Limit price if Commandlicable")
+                        )
+                        .arg(
+                            Arg::new("sl")
+                                .required(false)
+                                .allow_negative_numbers(true)
+                                .long("sl")
+                                .help("Stop loss")
+                        )
+                        .arg(
+                            Arg::new("tp")
+                                .required(false)
+                                .allow_negative_numbers(true)
+                                .long("tp")
+                                .help("Take profit")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the pair to the running daemon instead of running it in the foreground")
+                        )
+                        .arg(
+                            Arg::new("hedge")
+                                .long("hedge")
+                                .default_value("equal")
+                                .help("How the size is split between the legs: equal, beta or vol, estimated from a week of hourly candles")
+                        )
+                        .arg(
+                            Arg::new("spread")
+                                .long("spread")
+                                .default_value("ratio")
+                                .help("Watches the base/quote ratio or its log: ratio or log, --price, --tp and --sl are in the same unit")
+                        )
+                        .arg(
+                            Arg::new("precision")
+                                .long("precision")
+                                .default_value("6")
+                                .help("Decimals the spread is printed with")
+                        )
+                        .arg(
+                            Arg::new("entry_z")
+                                .long("entry-z")
+                                .required(false)
+                                .conflicts_with("price")
+                                .help("Enters once the spread is this many standard deviations from its mean e.g 2")
+                        )
+                        .arg(
+                            Arg::new("exit_z")
+                                .long("exit-z")
+                                .required(false)
+                                .help("Exits once the spread reverts to within this many standard deviations of its mean e.g 0.5")
+                        )
+                        .arg(
+                            Arg::new("lookback")
+                                .long("lookback")
+                                .default_value("168")
+                                .help("Number of hourly candles the z-score is computed over")
+                        )
+                        .arg(
+                            Arg::new("on_leg_failure")
+                                .long("on-leg-failure")
+                                .default_value("retry")
+                                .help("When a leg doesn't fully fill on entry: retry it within --max-slippage and unwind if it still didn't, or unwind the filled legs right away")
+                        )
+                        .arg(
+                            Arg::new("max_slippage")
+                                .long("max-slippage")
+                                .default_value("1%")
+                                .help("How far from its entry price a leg's missing size may be retried")
+                        )
+                )
+                .subcommand(
+                    Command::new("sell")
+                        .about("pair to sell")
+                        .arg(
+                            Arg::new("size")
+                                .required(true)
+                                .index(1)
+                                .help("Order Size")
+                        )
+                        .arg(
+                            Arg::new("pair")
+                                .required(true)
+                                .index(2)
+                                .help("Asset X/Asset Y e.g BTC/SOL")
+                        )
+                        .arg(
+                            Arg::new("price")
+                                .long("price")
+                                .required(false)
+                                .help("Limit price if Commandlicable")
+                        )
+                        .arg(
+                            Arg::new("sl")
+                                .required(false)
+                                .allow_negative_numbers(true)
+                                .long("sl")
+                                .help("stop loss")
+                        )
+                        .arg(
+                            Arg::new("tp")
+                                .required(false)
+                                .allow_negative_numbers(true)
+                                .long("tp")
+                                .help("Take profit")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the pair to the running daemon instead of running it in the foreground")
+                        )
+                        .arg(
+                            Arg::new("hedge")
+                                .long("hedge")
+                                .default_value("equal")
+                                .help("How the size is split between the legs: equal, beta or vol, estimated from a week of hourly candles")
+                        )
+                        .arg(
+                            Arg::new("spread")
+                                .long("spread")
+                                .default_value("ratio")
+                                .help("Watches the base/quote ratio or its log: ratio or log, --price, --tp and --sl are in the same unit")
+                        )
+                        .arg(
+                            Arg::new("precision")
+                                .long("precision")
+                                .default_value("6")
+                                .help("Decimals the spread is printed with")
+                        )
+                        .arg(
+                            Arg::new("entry_z")
+                                .long("entry-z")
+                                .required(false)
+                                .conflicts_with("price")
+                                .help("Enters once the spread is this many standard deviations from its mean e.g 2")
+                        )
+                        .arg(
+                            Arg::new("exit_z")
+                                .long("exit-z")
+                                .required(false)
+                                .help("Exits once the spread reverts to within this many standard deviations of its mean e.g 0.5")
+                        )
+                        .arg(
+                            Arg::new("lookback")
+                                .long("lookback")
+                                .default_value("168")
+                                .help("Number of hourly candles the z-score is computed over")
+                        )
+                        .arg(
+                            Arg::new("on_leg_failure")
+                                .long("on-leg-failure")
+                                .default_value("retry")
+                                .help("When a leg doesn't fully fill on entry: retry it within --max-slippage and unwind if it still didn't, or unwind the filled legs right away")
+                        )
+                        .arg(
+                            Arg::new("max_slippage")
+                                .long("max-slippage")
+                                .default_value("1%")
+                                .help("How far from its entry price a leg's missing size may be retried")
+                        )
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("list all saved pairs with their live PnL")
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("show the legs, entry and live spread and PnL of a pair")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Pair id")
+                        )
+                )
+                .subcommand(
+                    Command::new("close")
+                        .about("close both legs of a pair together at market")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Pair id")
+                        )
+                )
+                .subcommand(
+                    Command::new("set-tp")
+                        .about("change the take profit of an open pair, a running monitor picks it up")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Pair id")
+                        )
+                        .arg(
+                            Arg::new("value")
+                                .required(true)
+                                .index(2)
+                                .allow_negative_numbers(true)
+                                .help("Take profit in the pair's spread unit")
+                        )
+                )
+                .subcommand(
+                    Command::new("set-sl")
+                        .about("change the stop loss of an open pair, a running monitor picks it up")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Pair id")
+                        )
+                        .arg(
+                            Arg::new("value")
+                                .required(true)
+                                .index(2)
+                                .allow_negative_numbers(true)
+                                .help("Stop loss in the pair's spread unit")
+                        )
+                )
+                .subcommand(
+                    Command::new("resume")
+                        .about("monitor an open pair for its tp, sl or exit z-score again")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Pair id")
+                        )
+                        .arg(
+                            Arg::new("daemon")
+                                .long("daemon")
+                                .action(ArgAction::SetTrue)
+                                .help("Submit the pair to the running daemon instead of monitoring it in the foreground")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("scale")
+                .about("Places limit orders at intervals between a lower and upper price")
+                .subcommand(
+                    Command::new("buy")
+                        .about("scale buy")
+                        .arg(
+                            Arg::new("size_per_interval")
+                                .required(true)
+                                .index(1)
+                                .help(
+                                    "total order size/number of intervals"
+                                )
+                        )
+                        .arg(
+                            Arg::new("asset")
+                                .required(true)
+                                .index(2)
+                                .help("asset e.g ETH, SOL, BTC")
+                        )
+                        .arg(
+                            Arg::new("lower")
+                                .required(true)
+                                .index(3)
+                                .help("lower price bracket")
+                        )
+                        .arg(
+                            Arg::new("upper")
+                                .required(true)
+                                .index(4)
+                                .help("upper price bracket")
+                        )
+                        .arg(
+                            Arg::new("distribution")
+                                .long("distribution")
+                                .value_parser(["linear", "geometric", "exponential"])
+                                .default_value("linear")
+                                .help("Spacing of the orders: equal steps, equal percentage steps or steps doubling away from the market")
+                        )
+                        .arg(
+                            Arg::new("weight")
+                                .long("weight")
+                                .default_value("flat")
+                                .help("Size of each order from the one nearest the market: flat, increasing, decreasing or custom weights e.g 1,2,4")
+                        )
+                        .arg(
+                            Arg::new("preview")
+                                .long("preview")
+                                .action(ArgAction::SetTrue)
+                                .help("Only print the preview of the orders without placing them")
+                        )
+                )
+                .subcommand(
+                    Command::new("sell")
+                        .about("scale sell")
+                        .arg(
+                            Arg::new("size_per_interval")
+                                .required(true)
+                                .index(1)
+                                .help(
+                                    "total order size/number of intervals"
+                                )
+                        )
+                        .arg(
+                            Arg::new("asset")
+                                .required(true)
+                                .index(2)
+                                .help("asset e.g ETH, SOL, BTC")
+                        )
+                        .arg(
+                            Arg::new("lower")
+                                .required(true)
+                                .index(3)
+                                .help("Lower price bracket")
+                        )
+                        .arg(
+                            Arg::new("upper")
+                                .required(true)
+                                .index(4)
+                                .help("Upper price bracket")
+                        )
+                        .arg(
+                            Arg::new("distribution")
+                                .long("distribution")
+                                .value_parser(["linear", "geometric", "exponential"])
+                                .default_value("linear")
+                                .help("Spacing of the orders: equal steps, equal percentage steps or steps doubling away from the market")
+                        )
+                        .arg(
+                            Arg::new("weight")
+                                .long("weight")
+                                .default_value("flat")
+                                .help("Size of each order from the one nearest the market: flat, increasing, decreasing or custom weights e.g 1,2,4")
+                        )
+                        .arg(
+                            Arg::new("preview")
+                                .long("preview")
+                                .action(ArgAction::SetTrue)
+                                .help("Only print the preview of the orders without placing them")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("journal")
+                .about("Browses the local trade journal of commands, orders and their statuses")
+                .subcommand(
+                    Command::new("list")
+                        .about("list the most recent journaled trades")
+                        .arg(
+                            Arg::new("limit")
+                                .long("limit")
+                                .required(false)
+                                .help("Number of trades to list, defaults to 20")
+                        )
+                )
+                .subcommand(
+                    Command::new("show")
+                        .about("show a journaled trade")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Journal id of the trade")
+                        )
+                )
+                .subcommand(
+                    Command::new("annotate")
+                        .about("attach a note or tags to a journaled trade")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Journal id of the trade")
+                        )
+                        .arg(
+                            Arg::new("note")
+                                .long("note")
+                                .required(false)
+                                .help("Free text note e.g \"breakout entry\"")
+                        )
+                        .arg(
+                            Arg::new("tag")
+                                .long("tag")
+                                .required(false)
+                                .action(ArgAction::Append)
+                                .help("Tag to attach, can be repeated e.g --tag swing --tag btc")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("daemon")
+                .about("Runs long-running strategies (twap, pair, basket, dca, when, alert) in a background process")
+                .subcommand(
+                    Command::new("start")
+                        .about("start the daemon, jobs submitted with --daemon run in this process")
+                )
+                .subcommand(
+                    Command::new("jobs")
+                        .about("list the jobs hosted by the daemon")
+                )
+                .subcommand(
+                    Command::new("cancel")
+                        .about("cancel a job hosted by the daemon")
+                        .arg(
+                            Arg::new("id")
+                                .required(true)
+                                .index(1)
+                                .help("Daemon job id")
                         )
                 )
         )
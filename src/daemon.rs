@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use ethers::signers::LocalWallet;
+use home::home_dir;
+use hyperliquid::Exchange;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::Mutex,
+    task::JoinHandle,
+};
+
+use crate::{
+    alert,
+    basket::{self, BasketJob},
+    dca,
+    grid::{self, GridJob, SimulatedExchange, Venue},
+    journal::Journal,
+    market::MarketFeed,
+    pair::{self, PairJob, PairPosition},
+    twap::{self, TwapJob, TwapStatus},
+    when::{self, WhenJob},
+};
+
+/// A long-running strategy hosted by the daemon
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Job {
+    /// Drives a saved twap job from its last completed slice
+    Twap { id: u64 },
+    Pair(PairJob),
+    /// Monitors a saved open pair for its tp, sl or exit z-score
+    PairMonitor { id: u64 },
+    /// Drives a saved grid from its open orders
+    Grid { id: u64 },
+    Basket(BasketJob),
+    /// Executes the saved dca jobs on their schedules
+    Dca,
+    When(WhenJob),
+    /// Checks the saved alerts and delivers their notifications
+    Alerts,
+}
+
+impl Job {
+    pub fn describe(&self) -> String {
+        match self {
+            Job::Twap { id } => format!("twap {}", id),
+            Job::Pair(job) => job.describe(),
+            Job::PairMonitor { id } => format!("pair monitor {}", id),
+            Job::Grid { id } => format!("grid {}", id),
+            Job::Basket(job) => job.describe(),
+            Job::Dca => "dca scheduler".to_string(),
+            Job::When(job) => job.describe(),
+            Job::Alerts => "alert watcher".to_string(),
+        }
+    }
+
+    fn strategy(&self) -> &'static str {
+        match self {
+            Job::Twap { .. } => "twap",
+            Job::Pair(_) | Job::PairMonitor { .. } => "pair",
+            Job::Grid { .. } => "grid",
+            Job::Basket(_) => "basket",
+            Job::Dca => "dca",
+            Job::When(_) => "when",
+            Job::Alerts => "alert",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DaemonRequest {
+    Submit { command_line: String, job: Job },
+    Jobs,
+    Cancel { id: u64 },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DaemonResponse {
+    Submitted { id: u64 },
+    Jobs { jobs: Vec<JobInfo> },
+    Cancelled { id: u64 },
+    Error { message: String },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: u64,
+    pub description: String,
+    pub running: bool,
+    pub submitted_at: u64,
+}
+
+struct RunningJob {
+    job: Job,
+    handle: JoinHandle<()>,
+    submitted_at: u64,
+}
+
+/// Shares a single market-data feed, exchange client and signer between all hosted jobs
+struct Daemon {
+    feed: MarketFeed,
+    exchange: Arc<Exchange>,
+    wallet: Arc<LocalWallet>,
+    assets: Arc<HashMap<String, (u32, u32)>>,
+    profile: String,
+    jobs: Mutex<HashMap<u64, RunningJob>>,
+    next_id: AtomicU64,
+}
+
+pub fn socket_path() -> Result<PathBuf, anyhow::Error> {
+    let home_dir = match home_dir() {
+        Some(path) => path,
+        None => return Err(anyhow::anyhow!("Impossible to get your home dir!")),
+    };
+
+    // create .hyperliquid if it doesn't exist
+    let config_path = home_dir.join(".hyperliquid");
+    std::fs::create_dir_all(&config_path)?;
+
+    Ok(config_path.join("daemon.sock"))
+}
+
+/// Sends a single request to the running daemon and waits for its response
+pub async fn request(request: &DaemonRequest) -> Result<DaemonResponse, anyhow::Error> {
+    let stream = UnixStream::connect(socket_path()?).await.map_err(|err| {
+        anyhow::anyhow!(
+            "Failed to connect to the daemon, is `hl daemon start` running? {}",
+            err
+        )
+    })?;
+
+    let (reader, mut writer) = stream.into_split();
+
+    writer
+        .write_all(format!("{}\n", serde_json::to_string(request)?).as_bytes())
+        .await?;
+
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Submits a job to the daemon, returning the daemon job id
+pub async fn submit(command_line: &str, job: Job) -> Result<u64, anyhow::Error> {
+    let request = DaemonRequest::Submit {
+        command_line: command_line.to_string(),
+        job,
+    };
+
+    match self::request(&request).await? {
+        DaemonResponse::Submitted { id } => Ok(id),
+        DaemonResponse::Error { message } => Err(anyhow::anyhow!(message)),
+        _ => Err(anyhow::anyhow!("Unexpected daemon response")),
+    }
+}
+
+/// Listens on the daemon socket until ctrl-c, hosting every submitted job in this process
+pub async fn serve(
+    feed: MarketFeed,
+    exchange: Arc<Exchange>,
+    wallet: Arc<LocalWallet>,
+    assets: HashMap<String, (u32, u32)>,
+    profile: String,
+) -> Result<(), anyhow::Error> {
+    let path = socket_path()?;
+
+    if path.exists() {
+        // a live daemon answers, a socket left behind by one that crashed doesn't
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(anyhow::anyhow!(
+                "A daemon is already running on {}",
+                path.display()
+            ));
+        }
+
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+
+    let daemon = Arc::new(Daemon {
+        feed,
+        exchange,
+        wallet,
+        assets: Arc::new(assets),
+        profile,
+        jobs: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+    });
+
+    println!("Daemon listening on {}\n---", path.display());
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let daemon = daemon.clone();
+
+                tokio::spawn(async move {
+                    if let Err(err) = daemon.handle(stream).await {
+                        println!("Failed to handle daemon request: {:#?}", err);
+                    }
+                });
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!("Shutting down daemon, interrupted twaps can be resumed with `hl twap resume`");
+                break;
+            }
+        }
+    }
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+impl Daemon {
+    async fn handle(&self, stream: UnixStream) -> Result<(), anyhow::Error> {
+        let (reader, mut writer) = stream.into_split();
+
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).await?;
+
+        let response = match serde_json::from_str::<DaemonRequest>(&line) {
+            Ok(DaemonRequest::Submit { command_line, job }) => self.submit(&command_line, job).await,
+            Ok(DaemonRequest::Jobs) => self.jobs().await,
+            Ok(DaemonRequest::Cancel { id }) => self.cancel(id).await,
+            Err(err) => DaemonResponse::Error {
+                message: format!("Invalid request: {}", err),
+            },
+        };
+
+        writer
+            .write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn submit(&self, command_line: &str, job: Job) -> DaemonResponse {
+        let mut journal = match Journal::open() {
+            Ok(journal) => journal,
+            Err(err) => {
+                return DaemonResponse::Error {
+                    message: format!("Failed to open trade journal: {}", err),
+                }
+            }
+        };
+
+        if let Err(err) = journal.record_command(command_line, &self.profile, job.strategy()) {
+            println!("Failed to journal command: {:#?}", err);
+        }
+
+        let feed = self.feed.clone();
+        let exchange = self.exchange.clone();
+        let wallet = self.wallet.clone();
+        let assets = self.assets.clone();
+
+        let handle = match job.clone() {
+            Job::Twap { id } => {
+                let twap = match TwapJob::load(id) {
+                    Ok(Some(twap)) => twap,
+                    Ok(None) => {
+                        return DaemonResponse::Error {
+                            message: format!("No twap with id {}", id),
+                        }
+                    }
+                    Err(err) => {
+                        return DaemonResponse::Error {
+                            message: format!("Failed to read twap {}: {}", id, err),
+                        }
+                    }
+                };
+
+                let asset = match assets.get(&twap.symbol) {
+                    Some(asset) => *asset,
+                    None => {
+                        return DaemonResponse::Error {
+                            message: format!("Failed to find asset: {}", twap.symbol),
+                        }
+                    }
+                };
+
+                tokio::spawn(async move {
+                    twap::run(twap, &feed, &exchange, wallet, asset, &journal).await;
+                })
+            }
+            Job::Pair(pair) => tokio::spawn(async move {
+                pair::run(pair, &feed, &exchange, wallet, &assets, &journal).await;
+            }),
+            Job::PairMonitor { id } => {
+                let position = match PairPosition::load(id) {
+                    Ok(Some(position)) => position,
+                    Ok(None) => {
+                        return DaemonResponse::Error {
+                            message: format!("No pair with id {}", id),
+                        }
+                    }
+                    Err(err) => {
+                        return DaemonResponse::Error {
+                            message: format!("Failed to read pair {}: {}", id, err),
+                        }
+                    }
+                };
+
+                tokio::spawn(async move {
+                    pair::monitor(position, &feed, &exchange, wallet, &assets, &journal).await;
+                })
+            }
+            Job::Grid { id } => {
+                let mut grid = match GridJob::load(id) {
+                    Ok(Some(grid)) => grid,
+                    Ok(None) => {
+                        return DaemonResponse::Error {
+                            message: format!("No grid with id {}", id),
+                        }
+                    }
+                    Err(err) => {
+                        return DaemonResponse::Error {
+                            message: format!("Failed to read grid {}: {}", id, err),
+                        }
+                    }
+                };
+
+                let asset = match assets.get(&grid.symbol) {
+                    Some(asset) => *asset,
+                    None => {
+                        return DaemonResponse::Error {
+                            message: format!("Failed to find asset: {}", grid.symbol),
+                        }
+                    }
+                };
+
+                tokio::spawn(async move {
+                    let venue = if grid.paper {
+                        // the simulated orders only lived in the interrupted run, place the grid again
+                        grid.orders.clear();
+                        grid.pending.clear();
+
+                        Venue::Simulated(SimulatedExchange::default())
+                    } else {
+                        Venue::Live {
+                            info: feed.info(),
+                            exchange: &exchange,
+                            wallet,
+                            journal: &journal,
+                        }
+                    };
+
+                    if let Err(err) = grid::run(grid, &feed, venue, asset).await {
+                        println!("Grid {} stopped with error: {:#?}", id, err);
+                    }
+                })
+            }
+            Job::Basket(basket) => tokio::spawn(async move {
+                basket::run(basket, &feed, &exchange, wallet, &assets, &journal).await;
+            }),
+            Job::Dca => tokio::spawn(async move {
+                dca::schedule(&feed, &exchange, wallet, &assets, &journal).await;
+            }),
+            Job::When(when) => tokio::spawn(async move {
+                when::run(when, &feed, &exchange, wallet, &assets, &journal).await;
+            }),
+            Job::Alerts => tokio::spawn(async move {
+                alert::watch(&feed, wallet).await;
+            }),
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        println!("Job {} started: {}\n---", id, job.describe());
+
+        self.jobs.lock().await.insert(
+            id,
+            RunningJob {
+                job,
+                handle,
+                submitted_at: twap::now(),
+            },
+        );
+
+        DaemonResponse::Submitted { id }
+    }
+
+    async fn jobs(&self) -> DaemonResponse {
+        let jobs = self.jobs.lock().await;
+
+        let mut jobs = jobs
+            .iter()
+            .map(|(id, running)| JobInfo {
+                id: *id,
+                description: running.job.describe(),
+                running: !running.handle.is_finished(),
+                submitted_at: running.submitted_at,
+            })
+            .collect::<Vec<_>>();
+
+        jobs.sort_by_key(|job| job.id);
+
+        DaemonResponse::Jobs { jobs }
+    }
+
+    async fn cancel(&self, id: u64) -> DaemonResponse {
+        let jobs = self.jobs.lock().await;
+
+        let running = match jobs.get(&id) {
+            Some(running) => running,
+            None => {
+                return DaemonResponse::Error {
+                    message: format!("No daemon job with id {}", id),
+                }
+            }
+        };
+
+        // keep the saved twap in sync so it isn't mistaken for an interrupted one
+        if let Job::Twap { id: twap_id } = running.job {
+            if let Ok(Some(mut twap)) = TwapJob::load(twap_id) {
+                if twap.status == TwapStatus::Running {
+                    twap.status = TwapStatus::Cancelled;
+                    if let Err(err) = twap.save() {
+                        println!("Failed to update twap {}: {:#?}", twap_id, err);
+                    }
+                }
+            }
+        }
+
+        running.handle.abort();
+
+        println!("Job {} cancelled: {}\n---", id, running.job.describe());
+
+        DaemonResponse::Cancelled { id }
+    }
+}
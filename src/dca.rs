@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::LocalWallet;
+use hyperliquid::{utils::parse_price, Exchange};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    journal::{DcaRun, Journal},
+    market::MarketFeed,
+    store,
+    twap::{now, place_slice, Slice},
+    types::MissedRuns,
+};
+
+/// Seconds between schedule checks when the daemon hosts the scheduler
+const CHECK_INTERVAL: u64 = 30;
+
+/// A run started later than this after its scheduled time counts as missed
+const GRACE: u64 = 10 * 60;
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DcaJob {
+    pub id: u64,
+    pub symbol: String,
+    /// Amount bought every run in USD
+    pub usd: f64,
+    /// Seconds between runs
+    pub every: u64,
+    /// Time of day in UTC of the first run, as seconds since midnight
+    pub at: Option<u64>,
+    /// Runs are skipped while the market price is above it
+    pub max_price: Option<f64>,
+    pub missed: MissedRuns,
+    /// Unix time of the next scheduled run
+    pub next_run: u64,
+    pub runs: u32,
+    pub last_run: Option<u64>,
+    pub created_at: u64,
+}
+
+impl DcaJob {
+    pub fn new(
+        symbol: &str,
+        usd: f64,
+        every: Duration,
+        at: Option<u64>,
+        max_price: Option<f64>,
+        missed: MissedRuns,
+    ) -> Result<Self, anyhow::Error> {
+        if every.as_secs() < 60 {
+            return Err(anyhow::anyhow!("Runs must be at least a minute apart"));
+        }
+
+        let created_at = now();
+
+        // the first run is at the next occurrence of the time of day, right away otherwise
+        let next_run = match at {
+            Some(at) => {
+                let today = created_at - created_at % DAY + at;
+                if today > created_at {
+                    today
+                } else {
+                    today + DAY
+                }
+            }
+            None => created_at,
+        };
+
+        store::create(
+            "dca",
+            Self {
+                id: 0,
+                symbol: symbol.to_uppercase(),
+                usd,
+                every: every.as_secs(),
+                at,
+                max_price,
+                missed,
+                next_run,
+                runs: 0,
+                last_run: None,
+                created_at,
+            },
+            |job, id| job.id = id,
+        )
+    }
+
+    pub fn load(id: u64) -> Result<Option<Self>, anyhow::Error> {
+        store::load("dca", id)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        store::save("dca", self.id, self)
+    }
+
+    /// Deletes a saved job, returns false if it doesn't exist
+    pub fn remove(id: u64) -> Result<bool, anyhow::Error> {
+        store::remove("dca", id)
+    }
+
+    pub fn list() -> Result<Vec<Self>, anyhow::Error> {
+        store::list("dca")
+    }
+
+    pub fn print(&self) {
+        println!("{}", "---".repeat(20));
+        println!("Dca Id: {}", self.id);
+        println!("Asset: {}", self.symbol);
+        println!("Size in USD: {}", self.usd);
+        println!("Every: {}", span(self.every));
+        if let Some(at) = self.at {
+            println!("At: {:02}:{:02} UTC", at / 3600, at % 3600 / 60);
+        }
+        if let Some(max_price) = self.max_price {
+            println!("Max Price: {}", max_price);
+        }
+        println!(
+            "Missed Runs: {}",
+            match self.missed {
+                MissedRuns::Skip => "skip",
+                MissedRuns::Once => "once",
+                MissedRuns::All => "all",
+            }
+        );
+        println!("Completed Runs: {}", self.runs);
+        println!("Next Run: in {}", span(self.next_run.saturating_sub(now())));
+    }
+}
+
+/// Formats seconds with the largest whole unit e.g 1d, 4h, 30m
+fn span(secs: u64) -> String {
+    match secs {
+        secs if secs >= DAY && secs % DAY == 0 => format!("{}d", secs / DAY),
+        secs if secs >= 3600 => format!("{}h{}m", secs / 3600, secs % 3600 / 60),
+        secs => format!("{}m", secs.div_ceil(60)),
+    }
+}
+
+/// Executes every saved job whose next run is due, returning the number of jobs executed. Runs
+/// started more than 10 minutes late are handled by each job's missed-run policy.
+pub async fn run_due(
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) -> Result<u32, anyhow::Error> {
+    let mut executed = 0;
+
+    for mut job in DcaJob::list()? {
+        let now = now();
+        if now < job.next_run {
+            continue;
+        }
+
+        let scheduled_at = job.next_run;
+        // scheduled runs that have passed, including the one being executed
+        let due = 1 + (now - scheduled_at) / job.every;
+        let late = now - scheduled_at > GRACE.min(job.every / 2);
+
+        let runs = match (late, job.missed) {
+            (false, _) => 1,
+            (true, MissedRuns::Skip) => 0,
+            (true, MissedRuns::Once) => 1,
+            (true, MissedRuns::All) => due,
+        };
+
+        // advance the schedule before buying so a crash can't repeat the run
+        job.next_run = scheduled_at + due * job.every;
+        job.last_run = Some(now);
+        job.save()?;
+
+        let usd = job.usd * runs as f64;
+        let mut run = DcaRun {
+            created_at: String::new(),
+            job_id: job.id,
+            scheduled_at,
+            outcome: "skipped".to_string(),
+            usd,
+            filled_sz: 0.0,
+            filled_usd: 0.0,
+            detail: None,
+        };
+
+        println!("{}", "---".repeat(20));
+        println!(
+            "Dca {}: buying ${} of {}{}",
+            job.id,
+            usd,
+            job.symbol,
+            if late { " (late)" } else { "" }
+        );
+
+        if runs == 0 {
+            run.detail = Some(format!("Skipped {} missed runs", due));
+        } else {
+            match execute(&job, usd, feed, exchange, wallet.clone(), assets, journal).await {
+                Ok(Some((filled_sz, filled_usd))) => {
+                    run.outcome = "filled".to_string();
+                    run.filled_sz = filled_sz;
+                    run.filled_usd = filled_usd;
+
+                    job.runs += 1;
+                    job.save()?;
+                }
+                Ok(None) => {
+                    run.detail = Some(format!(
+                        "Market price above the max price {}",
+                        job.max_price.unwrap_or_default()
+                    ));
+                }
+                Err(err) => {
+                    run.outcome = "failed".to_string();
+                    run.detail = Some(err.to_string());
+                }
+            }
+        }
+
+        match &run.detail {
+            Some(detail) => println!("Run {}: {}\n", run.outcome, detail),
+            None => println!(
+                "Run {}: bought {} {} (${:.2})\n",
+                run.outcome, run.filled_sz, job.symbol, run.filled_usd
+            ),
+        }
+
+        if let Err(err) = journal.record_dca_run(&run) {
+            println!("Failed to journal dca run: {:#?}", err);
+        }
+
+        executed += 1;
+    }
+
+    Ok(executed)
+}
+
+/// Buys `usd` of the job's asset at market, returns None if the price is above the max price
+async fn execute(
+    job: &DcaJob,
+    usd: f64,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) -> Result<Option<(f64, f64)>, anyhow::Error> {
+    let (sz_decimals, asset) = *assets
+        .get(&job.symbol)
+        .ok_or_else(|| anyhow::anyhow!("Failed to find asset: {}", job.symbol))?;
+
+    let market_price = feed.mark_px(&job.symbol).await?;
+
+    if job.max_price.is_some_and(|max_price| market_price > max_price) {
+        return Ok(None);
+    }
+
+    println!("Market price: {}", parse_price(market_price));
+
+    let slice = Slice {
+        asset,
+        sz_decimals,
+        is_buy: true,
+        sz: usd / market_price,
+        market_price,
+        limit_px: job.max_price,
+        reduce_only: false,
+    };
+
+    let (filled_sz, filled_usd) = place_slice(exchange, wallet, slice, journal).await?;
+
+    if filled_sz <= 0.0 {
+        return Err(anyhow::anyhow!("Order didn't fill"));
+    }
+
+    Ok(Some((filled_sz, filled_usd)))
+}
+
+/// Checks the saved jobs every 30 seconds and executes the ones due, until cancelled
+pub async fn schedule(
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) {
+    loop {
+        if let Err(err) = run_due(feed, exchange, wallet.clone(), assets, journal).await {
+            println!("Failed to run dca jobs: {:#?}", err);
+        }
+
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+    }
+}
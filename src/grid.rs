@@ -0,0 +1,760 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid::{
+    types::exchange::{
+        request::{CancelRequest, Limit, OrderRequest, OrderType, Tif},
+        response::{Response, Status},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::{order_fills, place_batch},
+    journal::Journal,
+    market::MarketFeed,
+    scale,
+    store,
+    twap::{now, place_slice, Slice},
+    types::{Distribution, Weight},
+};
+
+/// Seconds between fill checks
+const CHECK_INTERVAL: u64 = 5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GridStatus {
+    Running,
+    /// The market left the stop-loss bounds, the grid was cancelled and its position closed
+    Stopped,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GridOrder {
+    pub level: usize,
+    pub is_buy: bool,
+    /// Size in the base asset
+    pub sz: f64,
+    pub oid: u64,
+    /// Price of the fill this order closes, not set for an order opening a grid position
+    pub entry_px: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GridJob {
+    pub id: u64,
+    pub symbol: String,
+    pub prices: Vec<f64>,
+    /// Size of each level in USD
+    pub size: f64,
+    pub stop_lower: Option<f64>,
+    pub stop_upper: Option<f64>,
+    /// Runs against a simulated exchange filled from the live mark price
+    pub paper: bool,
+    pub orders: Vec<GridOrder>,
+    /// Flips of filled levels that failed to place, retried on the next check
+    #[serde(default)]
+    pub pending: Vec<GridOrder>,
+    /// Net position opened by the grid in the base asset, negative when short
+    pub position: f64,
+    pub realized_profit: f64,
+    pub round_trips: u32,
+    pub status: GridStatus,
+    pub created_at: u64,
+}
+
+impl GridJob {
+    /// Spaces `levels` prices between `lower` and `upper` with the linear scale ladder
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        symbol: &str,
+        lower: f64,
+        upper: f64,
+        levels: u32,
+        size: f64,
+        stop_lower: Option<f64>,
+        stop_upper: Option<f64>,
+        paper: bool,
+    ) -> Result<Self, anyhow::Error> {
+        if levels < 2 {
+            return Err(anyhow::anyhow!("A grid needs at least 2 levels"));
+        }
+
+        if stop_lower.is_some_and(|stop| stop >= lower) || stop_upper.is_some_and(|stop| stop <= upper) {
+            return Err(anyhow::anyhow!("Stop-loss bounds must be outside the grid range"));
+        }
+
+        let mut prices = scale::ladder(
+            true,
+            size * levels as f64,
+            levels,
+            lower,
+            upper,
+            Distribution::Linear,
+            &Weight::Flat,
+        )
+        .map_err(|err| anyhow::anyhow!(err))?
+        .iter()
+        .map(|rung| rung.price)
+        .collect::<Vec<_>>();
+        prices.reverse();
+
+        store::create(
+            "grid",
+            Self {
+                id: 0,
+                symbol: symbol.to_uppercase(),
+                prices,
+                size,
+                stop_lower,
+                stop_upper,
+                paper,
+                orders: vec![],
+                pending: vec![],
+                position: 0.0,
+                realized_profit: 0.0,
+                round_trips: 0,
+                status: GridStatus::Running,
+                created_at: now(),
+            },
+            |job, id| job.id = id,
+        )
+    }
+
+    pub fn load(id: u64) -> Result<Option<Self>, anyhow::Error> {
+        store::load("grid", id)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        store::save("grid", self.id, self)
+    }
+
+    pub fn list() -> Result<Vec<Self>, anyhow::Error> {
+        store::list("grid")
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        match self.status {
+            GridStatus::Running => "Running",
+            GridStatus::Stopped => "Stopped",
+            GridStatus::Cancelled => "Cancelled",
+        }
+    }
+
+    pub fn print(&self) {
+        println!("{}", "---".repeat(20));
+        println!("Grid Id: {}{}", self.id, if self.paper { " (paper)" } else { "" });
+        println!("Asset: {}", self.symbol);
+        println!("Status: {}", self.status_name());
+        println!(
+            "Range: {} - {} over {} levels",
+            parse_price(self.prices[0]),
+            parse_price(self.prices[self.prices.len() - 1]),
+            self.prices.len()
+        );
+        println!("Size per Level in USD: {}", self.size);
+        if self.stop_lower.is_some() || self.stop_upper.is_some() {
+            println!(
+                "Stop-loss Bounds: {} - {}",
+                self.stop_lower.map_or("none".to_string(), |stop| stop.to_string()),
+                self.stop_upper.map_or("none".to_string(), |stop| stop.to_string())
+            );
+        }
+        println!(
+            "Open Orders: {} buys, {} sells",
+            self.orders.iter().filter(|order| order.is_buy).count(),
+            self.orders.iter().filter(|order| !order.is_buy).count()
+        );
+        if !self.pending.is_empty() {
+            println!("Orders Waiting to be Placed: {}", self.pending.len());
+        }
+        println!("Position in {}: {}", self.symbol, self.position);
+        println!("Round Trips: {}", self.round_trips);
+        println!("Realized Profit in USD: {:.2}", self.realized_profit);
+    }
+
+    /// Re-reads the saved status before saving so a cancel issued meanwhile is kept
+    fn checkpoint(&mut self) {
+        if let Ok(Some(latest)) = GridJob::load(self.id) {
+            if latest.status == GridStatus::Cancelled {
+                self.status = latest.status;
+            }
+        }
+
+        if let Err(err) = self.save() {
+            println!("Failed to save grid {}: {:#?}", self.id, err);
+        }
+    }
+}
+
+/// An order resting on the simulated exchange
+struct SimulatedOrder {
+    is_buy: bool,
+    px: f64,
+    sz: f64,
+    filled: bool,
+}
+
+/// Fills resting orders at their limit price once the mark price trades through them
+#[derive(Default)]
+pub struct SimulatedExchange {
+    orders: HashMap<u64, SimulatedOrder>,
+    next_oid: u64,
+}
+
+impl SimulatedExchange {
+    fn place(&mut self, is_buy: bool, px: f64, sz: f64) -> u64 {
+        self.next_oid += 1;
+        self.orders.insert(
+            self.next_oid,
+            SimulatedOrder {
+                is_buy,
+                px,
+                sz,
+                filled: false,
+            },
+        );
+
+        self.next_oid
+    }
+
+    fn match_orders(&mut self, mark_px: f64) {
+        for order in self.orders.values_mut() {
+            if (order.is_buy && mark_px <= order.px) || (!order.is_buy && mark_px >= order.px) {
+                order.filled = true;
+            }
+        }
+    }
+}
+
+/// Where a grid or a strategy places its orders, the exchange or a simulation of it
+pub enum Venue<'a> {
+    Live {
+        info: &'a Info,
+        exchange: &'a Exchange,
+        wallet: Arc<LocalWallet>,
+        journal: &'a Journal,
+    },
+    Simulated(SimulatedExchange),
+}
+
+impl Venue<'_> {
+    /// Places GTC orders of (is_buy, price, size), returning the order ids of those resting
+    pub async fn place(
+        &mut self,
+        (sz_decimals, asset): (u32, u32),
+        orders: &[(bool, f64, f64)],
+    ) -> Result<Vec<Option<u64>>, anyhow::Error> {
+        match self {
+            Venue::Live {
+                exchange,
+                wallet,
+                journal,
+                ..
+            } => {
+                let requests = orders
+                    .iter()
+                    .map(|(is_buy, px, sz)| OrderRequest {
+                        cloid: None,
+                        asset,
+                        is_buy: *is_buy,
+                        limit_px: parse_price(*px),
+                        sz: parse_size(*sz, sz_decimals),
+                        reduce_only: false,
+                        order_type: OrderType::Limit(Limit { tif: Tif::Gtc }),
+                    })
+                    .collect();
+
+                let statuses = place_batch(exchange, wallet.clone(), requests, journal).await?;
+
+                Ok(statuses
+                    .iter()
+                    .map(|status| match status {
+                        Status::Resting(order) => Some(order.oid),
+                        Status::Filled(order) => Some(order.oid),
+                        _ => None,
+                    })
+                    .collect())
+            }
+            Venue::Simulated(simulated) => Ok(orders
+                .iter()
+                .map(|(is_buy, px, sz)| Some(simulated.place(*is_buy, *px, *sz)))
+                .collect()),
+        }
+    }
+
+    /// Order ids still resting on the book
+    pub async fn open_orders(&mut self, mark_px: f64) -> Result<Vec<u64>, anyhow::Error> {
+        match self {
+            Venue::Live { info, wallet, .. } => Ok(info
+                .open_orders(wallet.address())
+                .await?
+                .iter()
+                .map(|order| order.oid)
+                .collect()),
+            Venue::Simulated(simulated) => {
+                simulated.match_orders(mark_px);
+
+                Ok(simulated
+                    .orders
+                    .iter()
+                    .filter(|(_, order)| !order.filled)
+                    .map(|(oid, _)| *oid)
+                    .collect())
+            }
+        }
+    }
+
+    /// Filled size of an order in the base asset and in USD
+    pub async fn fills(&self, oid: u64) -> Result<(f64, f64), anyhow::Error> {
+        match self {
+            Venue::Live { info, wallet, .. } => order_fills(info, wallet.address(), oid).await,
+            Venue::Simulated(simulated) => Ok(simulated
+                .orders
+                .get(&oid)
+                .filter(|order| order.filled)
+                .map_or((0.0, 0.0), |order| (order.sz, order.sz * order.px))),
+        }
+    }
+
+    pub async fn cancel(&mut self, asset: u32, oids: Vec<u64>) -> Result<(), anyhow::Error> {
+        match self {
+            Venue::Live {
+                exchange, wallet, ..
+            } => {
+                let cancels = oids
+                    .into_iter()
+                    .map(|oid| CancelRequest { asset, oid })
+                    .collect::<Vec<_>>();
+
+                if cancels.is_empty() {
+                    return Ok(());
+                }
+
+                match exchange.cancel_order(wallet.clone(), cancels, None).await? {
+                    Response::Ok(_) => Ok(()),
+                    Response::Err(err) => Err(anyhow::anyhow!(err)),
+                }
+            }
+            Venue::Simulated(simulated) => {
+                for oid in oids {
+                    simulated.orders.remove(&oid);
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Closes the grid's net position at market, returning the filled size and USD
+    async fn close(
+        &mut self,
+        asset: (u32, u32),
+        position: f64,
+        mark_px: f64,
+    ) -> Result<(f64, f64), anyhow::Error> {
+        self.market(asset, position < 0.0, position.abs(), mark_px, true).await
+    }
+
+    /// Buys or sells at market, returning the filled size and USD. A reduce-only order never
+    /// opens a position the other way. A simulated order fills whole at the mark price
+    pub async fn market(
+        &mut self,
+        (sz_decimals, asset): (u32, u32),
+        is_buy: bool,
+        sz: f64,
+        mark_px: f64,
+        reduce_only: bool,
+    ) -> Result<(f64, f64), anyhow::Error> {
+        match self {
+            Venue::Live {
+                exchange,
+                wallet,
+                journal,
+                ..
+            } => {
+                let slice = Slice {
+                    asset,
+                    sz_decimals,
+                    is_buy,
+                    sz,
+                    market_price: mark_px,
+                    limit_px: None,
+                    reduce_only,
+                };
+
+                place_slice(exchange, wallet.clone(), slice, journal).await
+            }
+            Venue::Simulated(simulated) => {
+                let oid = simulated.place(is_buy, mark_px, sz);
+                simulated.match_orders(mark_px);
+
+                Ok(simulated
+                    .orders
+                    .get(&oid)
+                    .map_or((0.0, 0.0), |order| (order.sz, order.sz * order.px)))
+            }
+        }
+    }
+}
+
+/// Places an order on every level but the one nearest the market price, buys below it and sells
+/// above it
+async fn place_grid(
+    job: &mut GridJob,
+    venue: &mut Venue<'_>,
+    asset: (u32, u32),
+    mark_px: f64,
+) -> Result<(), anyhow::Error> {
+    // an order on the level at the market price would cross the book right away
+    let nearest = job
+        .prices
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (*a - mark_px).abs().total_cmp(&(*b - mark_px).abs()))
+        .map(|(level, _)| level);
+
+    let orders = job
+        .prices
+        .iter()
+        .enumerate()
+        .filter(|(level, _)| Some(*level) != nearest)
+        .map(|(level, px)| (level, *px < mark_px, *px))
+        .collect::<Vec<_>>();
+
+    let requests = orders
+        .iter()
+        .map(|(_, is_buy, px)| (*is_buy, *px, job.size / px))
+        .collect::<Vec<_>>();
+
+    let oids = venue.place(asset, &requests).await?;
+
+    for ((level, is_buy, px), oid) in orders.into_iter().zip(oids) {
+        if let Some(oid) = oid {
+            job.orders.push(GridOrder {
+                level,
+                is_buy,
+                sz: job.size / px,
+                oid,
+                entry_px: None,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Books the fills of the grid's orders and places their flips. An order whose fills can't be
+/// fetched is checked again on the next step and flips that fail to place stay pending, so an
+/// error never loses a level. Returns whether the grid changed.
+async fn step(
+    job: &mut GridJob,
+    venue: &mut Venue<'_>,
+    asset: (u32, u32),
+    mark_px: f64,
+) -> Result<bool, anyhow::Error> {
+    let open = venue.open_orders(mark_px).await?;
+
+    let (filled, resting): (Vec<_>, Vec<_>) = job
+        .orders
+        .drain(..)
+        .partition(|order| !open.contains(&order.oid));
+    job.orders = resting;
+
+    if filled.is_empty() && job.pending.is_empty() {
+        return Ok(false);
+    }
+
+    for order in filled {
+        let (filled_sz, filled_usd) = match venue.fills(order.oid).await {
+            Ok(fills) => fills,
+            Err(err) => {
+                println!("Failed to fetch the fills of grid order {}: {:#?}", order.oid, err);
+                job.orders.push(order);
+                continue;
+            }
+        };
+
+        if filled_sz <= 0.0 {
+            println!("Grid order {} at level {} was cancelled outside the grid", order.oid, order.level + 1);
+            continue;
+        }
+
+        let fill_px = filled_usd / filled_sz;
+
+        println!(
+            "{} {} {} at {} filled (level {})",
+            if order.is_buy { "Bought" } else { "Sold" },
+            filled_sz,
+            job.symbol,
+            parse_price(fill_px),
+            order.level + 1
+        );
+
+        job.position += if order.is_buy { filled_sz } else { -filled_sz };
+
+        if let Some(entry_px) = order.entry_px {
+            let profit = if order.is_buy {
+                (entry_px - fill_px) * filled_sz
+            } else {
+                (fill_px - entry_px) * filled_sz
+            };
+
+            job.realized_profit += profit;
+            job.round_trips += 1;
+
+            println!("Round trip closed for ${:.2}, realized grid profit ${:.2}", profit, job.realized_profit);
+        }
+
+        let level = if order.is_buy {
+            order.level + 1
+        } else {
+            match order.level.checked_sub(1) {
+                Some(level) => level,
+                None => continue,
+            }
+        };
+
+        if level >= job.prices.len() {
+            continue;
+        }
+
+        job.pending.push(GridOrder {
+            level,
+            is_buy: !order.is_buy,
+            sz: filled_sz,
+            oid: 0,
+            // an order that closed a round trip is flipped into one opening the next
+            entry_px: match order.entry_px {
+                Some(_) => None,
+                None => Some(fill_px),
+            },
+        });
+    }
+
+    let pending = std::mem::take(&mut job.pending);
+
+    let requests = pending
+        .iter()
+        .map(|flip| (flip.is_buy, job.prices[flip.level], flip.sz))
+        .collect::<Vec<_>>();
+
+    match venue.place(asset, &requests).await {
+        Ok(oids) => {
+            for (mut flip, oid) in pending.into_iter().zip(oids) {
+                if let Some(oid) = oid {
+                    flip.oid = oid;
+                    job.orders.push(flip);
+                }
+            }
+        }
+        Err(err) => {
+            println!("Failed to place {} grid orders, retrying on the next check: {:#?}", pending.len(), err);
+            job.pending = pending;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Places the grid around the market price, then flips every filled level into an order on the
+/// next level: a filled buy at level k places a sell at level k + 1 and a filled sell at level k
+/// places a buy at level k - 1. Runs until the grid is cancelled or the market leaves the
+/// stop-loss bounds.
+pub async fn run(
+    mut job: GridJob,
+    feed: &MarketFeed,
+    mut venue: Venue<'_>,
+    asset: (u32, u32),
+) -> Result<(), anyhow::Error> {
+    let (sz_decimals, asset_id) = asset;
+
+    if job.orders.is_empty() && job.pending.is_empty() {
+        let mark_px = feed.mark_px(&job.symbol).await?;
+
+        place_grid(&mut job, &mut venue, asset, mark_px).await?;
+
+        job.checkpoint();
+        job.print();
+    }
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+
+        match GridJob::load(job.id) {
+            Ok(Some(latest)) => job.status = latest.status,
+            Ok(None) => {
+                println!("Grid {} no longer exists, stopping", job.id);
+                return Ok(());
+            }
+            Err(err) => println!("Failed to read grid {}: {:#?}", job.id, err),
+        }
+
+        if job.status == GridStatus::Cancelled {
+            // the cancel command may already have cancelled them
+            let oids = job.orders.iter().map(|order| order.oid).collect();
+            if let Err(err) = venue.cancel(asset_id, oids).await {
+                println!("Failed to cancel grid orders: {:#?}", err);
+            }
+            job.orders.clear();
+            job.pending.clear();
+            job.save()?;
+
+            println!("Grid {} cancelled, its orders were cancelled", job.id);
+            return Ok(());
+        }
+
+        let mark_px = match feed.mark_px(&job.symbol).await {
+            Ok(mark_px) => mark_px,
+            Err(err) => {
+                println!("Failed to fetch market price: {:#?}", err);
+                continue;
+            }
+        };
+
+        let out_of_bounds = job.stop_lower.is_some_and(|stop| mark_px <= stop)
+            || job.stop_upper.is_some_and(|stop| mark_px >= stop);
+
+        if out_of_bounds {
+            println!("Market price {} left the stop-loss bounds, stopping grid {}", mark_px, job.id);
+
+            let oids = job.orders.iter().map(|order| order.oid).collect();
+            if let Err(err) = venue.cancel(asset_id, oids).await {
+                println!("Failed to cancel grid orders, retrying on the next check: {:#?}", err);
+                continue;
+            }
+            job.orders.clear();
+            job.pending.clear();
+            job.status = GridStatus::Stopped;
+
+            if parse_size(job.position.abs(), sz_decimals).parse::<f64>().unwrap_or(0.0) > 0.0 {
+                match venue.close(asset, job.position, mark_px).await {
+                    Ok((filled_sz, filled_usd)) => {
+                        println!(
+                            "Closed {} {} of the grid position at market (${:.2})",
+                            filled_sz, job.symbol, filled_usd
+                        );
+                        job.position -= filled_sz * job.position.signum();
+                    }
+                    Err(err) => {
+                        job.checkpoint();
+                        println!("Grid {} stopped, its position of {} {} is left open", job.id, job.position, job.symbol);
+                        return Err(err);
+                    }
+                }
+            }
+
+            job.save()?;
+            job.print();
+
+            return Ok(());
+        }
+
+        match step(&mut job, &mut venue, asset, mark_px).await {
+            Ok(true) => job.checkpoint(),
+            Ok(false) => {}
+            Err(err) => println!("Failed to check grid orders: {:#?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSET: (u32, u32) = (4, 1);
+
+    fn job() -> GridJob {
+        GridJob {
+            id: 1,
+            symbol: "ETH".to_string(),
+            prices: vec![1800.0, 1900.0, 2000.0, 2100.0, 2200.0],
+            size: 100.0,
+            stop_lower: None,
+            stop_upper: None,
+            paper: true,
+            orders: vec![],
+            pending: vec![],
+            position: 0.0,
+            realized_profit: 0.0,
+            round_trips: 0,
+            status: GridStatus::Running,
+            created_at: 0,
+        }
+    }
+
+    fn order(job: &GridJob, level: usize) -> &GridOrder {
+        job.orders
+            .iter()
+            .find(|order| order.level == level)
+            .expect("No order at level")
+    }
+
+    #[tokio::test]
+    async fn skips_the_level_nearest_the_market() {
+        let mut job = job();
+        let mut venue = Venue::Simulated(SimulatedExchange::default());
+
+        place_grid(&mut job, &mut venue, ASSET, 2012.5).await.unwrap();
+
+        let mut levels = job.orders.iter().map(|order| order.level).collect::<Vec<_>>();
+        levels.sort();
+        assert_eq!(levels, vec![0, 1, 3, 4]);
+        assert!(order(&job, 1).is_buy);
+        assert!(!order(&job, 3).is_buy);
+    }
+
+    #[tokio::test]
+    async fn flips_fills_and_books_round_trips() {
+        let mut job = job();
+        let mut venue = Venue::Simulated(SimulatedExchange::default());
+
+        place_grid(&mut job, &mut venue, ASSET, 2000.0).await.unwrap();
+        assert!(!step(&mut job, &mut venue, ASSET, 1950.0).await.unwrap());
+
+        // the buy at 1900 fills and flips into a sell one level up
+        assert!(step(&mut job, &mut venue, ASSET, 1890.0).await.unwrap());
+        let sz = 100.0 / 1900.0;
+        assert_eq!(job.position, sz);
+        let flip = order(&job, 2);
+        assert!(!flip.is_buy);
+        assert_eq!(flip.entry_px, Some(1900.0));
+        assert_eq!(job.round_trips, 0);
+
+        // the sell at 2000 closes the round trip and flips back into an opening buy
+        assert!(step(&mut job, &mut venue, ASSET, 2000.0).await.unwrap());
+        assert_eq!(job.round_trips, 1);
+        assert!((job.realized_profit - 100.0 * sz).abs() < 1e-9);
+        assert!(job.position.abs() < 1e-12);
+        let flip = order(&job, 1);
+        assert!(flip.is_buy);
+        assert_eq!(flip.entry_px, None);
+        assert!(job.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn short_round_trip_profits_from_a_drop() {
+        let mut job = job();
+        let mut venue = Venue::Simulated(SimulatedExchange::default());
+
+        place_grid(&mut job, &mut venue, ASSET, 2000.0).await.unwrap();
+
+        // the sell at 2100 fills and flips into a buy one level down
+        step(&mut job, &mut venue, ASSET, 2110.0).await.unwrap();
+        let sz = 100.0 / 2100.0;
+        assert_eq!(job.position, -sz);
+        assert_eq!(order(&job, 2).entry_px, Some(2100.0));
+
+        step(&mut job, &mut venue, ASSET, 2000.0).await.unwrap();
+        assert_eq!(job.round_trips, 1);
+        assert!((job.realized_profit - 100.0 * sz).abs() < 1e-9);
+    }
+}
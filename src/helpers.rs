@@ -1,4 +1,27 @@
-use hyperliquid::types::info::response::{AssetContext, Ctx};
+use std::sync::Arc;
+
+use ethers::{signers::LocalWallet, types::Address};
+use hyper::{body::HttpBody, Body, StatusCode};
+use hyperliquid::{
+    types::{
+        exchange::{
+            request::{CancelRequest, Limit, OrderRequest, OrderType, Tif},
+            response::{Response, Status, StatusType},
+        },
+        info::response::{AssetContext, Ctx, UserState},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+use serde_json::{json, Value};
+
+use crate::journal::Journal;
+
+/// Orders sent per signed request when placing a batch
+const BATCH_SIZE: usize = 20;
+
+/// Largest request body the API and the webhook receiver read, 64 KiB
+const MAX_BODY: usize = 64 * 1024;
 
 pub fn asset_ctx<'a>(
     asset_ctxs: &'a Vec<AssetContext>,
@@ -9,11 +32,15 @@ pub fn asset_ctx<'a>(
         _ => return Ok(None),
     };
 
-    let position = universe
+    // an unknown or delisted asset has no ctx
+    let position = match universe
         .universe
         .iter()
         .position(|a| a.name.to_uppercase() == asset.to_uppercase())
-        .expect("Asset not found");
+    {
+        Some(position) => position,
+        None => return Ok(None),
+    };
 
     let ctxs = match asset_ctxs.get(1) {
         Some(AssetContext::Ctx(ctxs)) => ctxs,
@@ -24,3 +51,232 @@ pub fn asset_ctx<'a>(
 
     Ok(ctx)
 }
+
+/// Places orders on the exchange, writing each request and its returned status to the journal
+pub async fn place_order(
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    orders: Vec<OrderRequest>,
+    journal: &Journal,
+) -> hyperliquid::Result<Response> {
+    let ids = journal.record_orders(&orders).unwrap_or_else(|err| {
+        println!("Failed to journal orders: {:#?}", err);
+        vec![]
+    });
+
+    let response = exchange.place_order(wallet, orders, None).await;
+
+    let recorded = match &response {
+        Ok(Response::Ok(order)) => match &order.data {
+            Some(StatusType::Statuses(statuses)) => ids
+                .iter()
+                .zip(statuses.iter())
+                .try_for_each(|(id, status)| journal.record_status(*id, status)),
+            _ => Ok(()),
+        },
+        Ok(Response::Err(err)) => journal.record_error(&ids, err),
+        Err(err) => journal.record_error(&ids, &err.to_string()),
+    };
+
+    if let Err(err) = recorded {
+        println!("Failed to journal order status: {:#?}", err);
+    }
+
+    response
+}
+
+/// Filled size in the base asset and in USD of an order, from the user's fills
+pub async fn order_fills(
+    info: &Info,
+    user: Address,
+    oid: u64,
+) -> Result<(f64, f64), anyhow::Error> {
+    let fills = info.user_fills(user).await?;
+
+    Ok(fills
+        .iter()
+        .filter(|fill| fill.oid == oid)
+        .fold((0.0, 0.0), |(sz, usd), fill| {
+            let fill_sz = fill.sz.parse::<f64>().unwrap_or(0.0);
+            let fill_px = fill.px.parse::<f64>().unwrap_or(0.0);
+
+            (sz + fill_sz, usd + fill_sz * fill_px)
+        }))
+}
+
+/// Places orders in chunks of signed requests, returning each order's status in the same order as
+/// `orders`. If a chunk fails or any of its orders is rejected, every order already resting is
+/// cancelled so the batch isn't left half-placed, and the error is returned. Fills can't be undone.
+pub async fn place_batch(
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    orders: Vec<OrderRequest>,
+    journal: &Journal,
+) -> Result<Vec<Status>, anyhow::Error> {
+    let assets = orders.iter().map(|order| order.asset).collect::<Vec<_>>();
+    let mut statuses = Vec::with_capacity(orders.len());
+    let mut orders = orders.into_iter().peekable();
+
+    while orders.peek().is_some() {
+        let chunk = orders.by_ref().take(BATCH_SIZE).collect::<Vec<_>>();
+        let first = statuses.len();
+        let len = chunk.len();
+
+        let failure = match place_order(exchange, wallet.clone(), chunk, journal).await {
+            Ok(Response::Ok(order)) => match order.data {
+                Some(StatusType::Statuses(chunk_statuses)) if chunk_statuses.len() == len => {
+                    statuses.extend(chunk_statuses);
+
+                    statuses[first..]
+                        .iter()
+                        .enumerate()
+                        .find_map(|(i, status)| match status {
+                            Status::Error(msg) => {
+                                Some(format!("Order {} failed with error: {}", first + i + 1, msg))
+                            }
+                            _ => None,
+                        })
+                }
+                _ => Some(format!(
+                    "Orders {} to {} got an unexpected response",
+                    first + 1,
+                    first + len
+                )),
+            },
+            Ok(Response::Err(err)) => Some(err),
+            Err(err) => Some(err.to_string()),
+        };
+
+        if let Some(failure) = failure {
+            let resting = statuses
+                .iter()
+                .zip(assets.iter())
+                .filter_map(|(status, asset)| match status {
+                    Status::Resting(order) => Some(CancelRequest {
+                        asset: *asset,
+                        oid: order.oid,
+                    }),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            if resting.is_empty() {
+                return Err(anyhow::anyhow!(failure));
+            }
+
+            let oids = resting
+                .iter()
+                .map(|cancel| cancel.oid.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            return match exchange.cancel_order(wallet, resting, None).await {
+                Ok(Response::Ok(_)) => Err(anyhow::anyhow!(
+                    "{}. Rolled back by cancelling orders {}",
+                    failure,
+                    oids
+                )),
+                Ok(Response::Err(err)) => Err(anyhow::anyhow!(
+                    "{}. Failed to cancel orders {}: {}",
+                    failure,
+                    oids,
+                    err
+                )),
+                Err(err) => Err(anyhow::anyhow!(
+                    "{}. Failed to cancel orders {}: {}",
+                    failure,
+                    oids,
+                    err
+                )),
+            };
+        }
+    }
+
+    Ok(statuses)
+}
+
+/// Limit price of a market order, an IOC 3% through the mark price
+pub fn market_px(market_price: f64, is_buy: bool) -> f64 {
+    let slippage = 3.0 / 100.0;
+
+    if is_buy {
+        market_price * (1.0 + slippage)
+    } else {
+        market_price * (1.0 - slippage)
+    }
+}
+
+/// Signed size of the position in an asset, positive for long, negative for short and 0 when flat
+pub fn position_szi(state: &UserState, symbol: &str) -> Result<f64, anyhow::Error> {
+    let szi = state
+        .asset_positions
+        .iter()
+        .find(|ap| ap.position.coin.to_uppercase() == symbol.to_uppercase())
+        .map(|ap| ap.position.szi.parse::<f64>())
+        .transpose()?
+        .unwrap_or(0.0);
+
+    Ok(szi)
+}
+
+/// Reduce-only market order closing `share` (0 to 1) of a position of signed size `szi`
+pub fn close_order(asset: u32, sz_decimals: u32, szi: f64, share: f64, market_price: f64) -> OrderRequest {
+    let is_buy = szi < 0.0;
+
+    OrderRequest {
+        cloid: None,
+        asset,
+        is_buy,
+        limit_px: parse_price(market_px(market_price, is_buy)),
+        sz: parse_size(szi.abs() * share, sz_decimals),
+        reduce_only: true,
+        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+    }
+}
+
+/// An order's status as answered by the API and the webhook receiver
+pub fn status(status: &Status) -> Value {
+    match status {
+        Status::Filled(order) => json!({
+            "status": "filled",
+            "oid": order.oid,
+            "totalSz": order.total_sz,
+            "avgPx": order.avg_px,
+        }),
+        Status::Resting(order) => json!({ "status": "resting", "oid": order.oid }),
+        Status::Error(msg) => json!({ "status": "error", "error": msg }),
+        _ => json!({ "status": "ok" }),
+    }
+}
+
+/// Compares a given token to the expected one in a time that doesn't depend on where they differ
+pub fn token_eq(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Reads a request body of at most 64 KiB, a larger one is refused without being buffered
+pub async fn read_body(mut body: Body) -> Result<Vec<u8>, (StatusCode, String)> {
+    let too_large = || (StatusCode::PAYLOAD_TOO_LARGE, format!("Body larger than {} bytes", MAX_BODY));
+
+    // a declared Content-Length over the limit is refused before any read
+    if body.size_hint().lower() > MAX_BODY as u64 {
+        return Err(too_large());
+    }
+
+    let mut bytes = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_BODY {
+            return Err(too_large());
+        }
+
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(bytes)
+}
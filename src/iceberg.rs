@@ -0,0 +1,319 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid::{
+    types::exchange::{
+        request::{CancelByCloidRequest, Limit, OrderRequest, OrderType, Tif},
+        response::{Response, Status, StatusType},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    helpers::{order_fills, place_order},
+    journal::Journal,
+    store,
+    twap::now,
+};
+
+/// Seconds between checks on the visible clip
+const CHECK_INTERVAL: u64 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IcebergStatus {
+    Running,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IcebergJob {
+    pub id: u64,
+    pub symbol: String,
+    pub is_buy: bool,
+    pub price: f64,
+    /// Total size in the base asset
+    pub total_sz: f64,
+    /// Size of each visible clip in the base asset
+    pub clip_sz: f64,
+    pub filled_sz: f64,
+    pub filled_usd: f64,
+    pub clips_placed: u32,
+    /// Client order id of the clip currently on the book
+    pub clip_cloid: Option<Uuid>,
+    pub clip_oid: Option<u64>,
+    pub status: IcebergStatus,
+    pub created_at: u64,
+}
+
+impl IcebergJob {
+    pub fn new(
+        symbol: &str,
+        is_buy: bool,
+        price: f64,
+        total_sz: f64,
+        clip_sz: f64,
+    ) -> Result<Self, anyhow::Error> {
+        store::create(
+            "iceberg",
+            Self {
+                id: 0,
+                symbol: symbol.to_uppercase(),
+                is_buy,
+                price,
+                total_sz,
+                clip_sz,
+                filled_sz: 0.0,
+                filled_usd: 0.0,
+                clips_placed: 0,
+                clip_cloid: None,
+                clip_oid: None,
+                status: IcebergStatus::Running,
+                created_at: now(),
+            },
+            |job, id| job.id = id,
+        )
+    }
+
+    pub fn load(id: u64) -> Result<Option<Self>, anyhow::Error> {
+        store::load("iceberg", id)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        store::save("iceberg", self.id, self)
+    }
+
+    pub fn list() -> Result<Vec<Self>, anyhow::Error> {
+        store::list("iceberg")
+    }
+
+    pub fn side(&self) -> &'static str {
+        if self.is_buy {
+            "Buy"
+        } else {
+            "Sell"
+        }
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        match self.status {
+            IcebergStatus::Running => "Running",
+            IcebergStatus::Cancelled => "Cancelled",
+            IcebergStatus::Completed => "Completed",
+        }
+    }
+
+    pub fn print(&self) {
+        println!("{}", "---".repeat(20));
+        println!("Iceberg Id: {}", self.id);
+        println!("Side: {}", self.side());
+        println!("Asset: {}", self.symbol);
+        println!("Status: {}", self.status_name());
+        println!("Price: {}", self.price);
+        println!("Total Size in {}: {}", self.symbol, self.total_sz);
+        println!("Clip Size in {}: {}", self.symbol, self.clip_sz);
+        println!("Clips Placed: {}", self.clips_placed);
+        println!("Filled Size in {}: {}", self.symbol, self.filled_sz);
+        println!("Filled Size in USD: {:.2}", self.filled_usd);
+        if let Some(cloid) = self.clip_cloid {
+            println!("Visible Clip: {}", cloid);
+        }
+    }
+
+    /// Re-reads the saved status before saving so a cancel issued meanwhile is kept
+    fn checkpoint(&mut self) {
+        if let Ok(Some(latest)) = IcebergJob::load(self.id) {
+            if latest.status == IcebergStatus::Cancelled {
+                self.status = latest.status;
+            }
+        }
+
+        if let Err(err) = self.save() {
+            println!("Failed to save iceberg {}: {:#?}", self.id, err);
+        }
+    }
+}
+
+/// Cancels the visible clip of an iceberg by its cloid
+pub async fn cancel_clip(
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    asset: u32,
+    cloid: Uuid,
+) -> Result<(), anyhow::Error> {
+    match exchange
+        .cancel_order_by_cloid(wallet, vec![CancelByCloidRequest { asset, cloid }], None)
+        .await?
+    {
+        Response::Ok(_) => Ok(()),
+        Response::Err(err) => Err(anyhow::anyhow!(err)),
+    }
+}
+
+/// Keeps a single GTC clip on the book at the iceberg price, placing the next one as soon as
+/// the visible clip fills, until the total is filled or the iceberg is cancelled
+pub async fn run(
+    mut job: IcebergJob,
+    info: &Info,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    (sz_decimals, asset): (u32, u32),
+    journal: &Journal,
+) {
+    loop {
+        match IcebergJob::load(job.id) {
+            Ok(Some(latest)) => job.status = latest.status,
+            Ok(None) => {
+                println!("Iceberg {} no longer exists, stopping", job.id);
+                return;
+            }
+            Err(err) => println!("Failed to read iceberg {}: {:#?}", job.id, err),
+        }
+
+        if job.status != IcebergStatus::Running {
+            println!("Iceberg {} {}", job.id, job.status_name().to_lowercase());
+            return;
+        }
+
+        if let Some(oid) = job.clip_oid {
+            match info.open_orders(wallet.address()).await {
+                Ok(open_orders) if open_orders.iter().any(|order| order.oid == oid) => {
+                    tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+                    continue;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    println!("Failed to fetch open orders: {:#?}", err);
+                    tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+                    continue;
+                }
+            }
+
+            // the clip left the book, by a fill or a cancel from outside the iceberg
+            let (filled_sz, filled_usd) = match order_fills(info, wallet.address(), oid).await {
+                Ok(filled) => filled,
+                Err(err) => {
+                    println!("Failed to fetch fills: {:#?}", err);
+                    tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+                    continue;
+                }
+            };
+
+            job.filled_sz += filled_sz;
+            job.filled_usd += filled_usd;
+            job.clip_cloid = None;
+            job.clip_oid = None;
+
+            println!(
+                "Clip {} filled {} {}. Filled {} of {} {}\n---",
+                job.clips_placed, filled_sz, job.symbol, job.filled_sz, job.total_sz, job.symbol
+            );
+
+            if filled_sz <= 0.0 {
+                println!("Clip {} was cancelled outside the iceberg, stopping", job.clips_placed);
+                job.status = IcebergStatus::Cancelled;
+                job.checkpoint();
+                return;
+            }
+
+            job.checkpoint();
+
+            // cancelled while the clip was on the book, no further clip is placed
+            if job.status != IcebergStatus::Running {
+                println!("Iceberg {} {}", job.id, job.status_name().to_lowercase());
+                return;
+            }
+        }
+
+        let remaining = parse_size(job.total_sz - job.filled_sz, sz_decimals)
+            .parse::<f64>()
+            .unwrap_or(0.0);
+        if remaining <= 0.0 {
+            break;
+        }
+
+        let cloid = Uuid::new_v4();
+        let order = OrderRequest {
+            cloid: Some(cloid),
+            asset,
+            is_buy: job.is_buy,
+            limit_px: parse_price(job.price),
+            sz: parse_size(job.clip_sz.min(remaining), sz_decimals),
+            reduce_only: false,
+            order_type: OrderType::Limit(Limit { tif: Tif::Gtc }),
+        };
+
+        println!("{}", "---".repeat(20));
+        println!("Clip {}", job.clips_placed + 1);
+        println!("Side: {}", job.side());
+        println!("Size in {}: {}", job.symbol, order.sz);
+        println!("Price: {}\n", order.limit_px);
+
+        match place_order(exchange, wallet.clone(), vec![order], journal).await {
+            Ok(Response::Ok(order)) => {
+                if let Some(StatusType::Statuses(statuses)) = order.data {
+                    for status in statuses {
+                        match status {
+                            Status::Resting(order) => {
+                                println!("Clip {} resting as {}\n", order.oid, cloid);
+                                job.clip_cloid = Some(cloid);
+                                job.clip_oid = Some(order.oid);
+                            }
+                            Status::Filled(order) => {
+                                println!("Clip {} was successfully filled.\n", order.oid);
+
+                                let total_sz = order.total_sz.parse::<f64>().unwrap_or(0.0);
+                                let avg_px = order.avg_px.parse::<f64>().unwrap_or(0.0);
+
+                                job.filled_sz += total_sz;
+                                job.filled_usd += total_sz * avg_px;
+                            }
+                            Status::Error(msg) => {
+                                println!("Clip failed with error: {:#?}\n", msg);
+                                job.status = IcebergStatus::Cancelled;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Ok(Response::Err(err)) => {
+                println!("{:#?}", err);
+                job.status = IcebergStatus::Cancelled;
+            }
+            Err(err) => {
+                println!("{:#?}", err);
+                job.status = IcebergStatus::Cancelled;
+            }
+        }
+
+        job.clips_placed += 1;
+        job.checkpoint();
+
+        // a cancel issued while the clip was being placed couldn't see it
+        if job.status == IcebergStatus::Cancelled {
+            if let Some(cloid) = job.clip_cloid {
+                if let Err(err) = cancel_clip(exchange, wallet.clone(), asset, cloid).await {
+                    println!("Failed to cancel clip {}: {:#?}", cloid, err);
+                }
+            }
+
+            println!("Iceberg {} cancelled", job.id);
+            return;
+        }
+    }
+
+    job.status = IcebergStatus::Completed;
+    job.checkpoint();
+
+    println!(
+        "Iceberg {} completed ✔️ Filled {} {} (${:.2})\n---",
+        job.id, job.filled_sz, job.symbol, job.filled_usd
+    );
+}
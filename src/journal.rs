@@ -0,0 +1,341 @@
+use std::sync::{Mutex, MutexGuard};
+
+use home::home_dir;
+use hyperliquid::types::exchange::{request::OrderRequest, response::Status};
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub struct Journal {
+    conn: Mutex<Connection>,
+    command_id: Option<i64>,
+}
+
+pub struct DcaRun {
+    pub created_at: String,
+    pub job_id: u64,
+    /// Unix time the run was scheduled for
+    pub scheduled_at: u64,
+    /// filled, skipped or failed
+    pub outcome: String,
+    /// Amount meant to be bought in USD
+    pub usd: f64,
+    pub filled_sz: f64,
+    pub filled_usd: f64,
+    pub detail: Option<String>,
+}
+
+pub struct JournalEntry {
+    pub id: i64,
+    pub created_at: String,
+    pub command_line: String,
+    pub profile: String,
+    pub strategy: String,
+    pub asset: u32,
+    pub is_buy: bool,
+    pub limit_px: String,
+    pub sz: String,
+    pub reduce_only: bool,
+    pub request: String,
+    pub status: Option<String>,
+    pub oid: Option<u64>,
+    pub detail: Option<String>,
+    pub note: Option<String>,
+    pub tags: Option<String>,
+}
+
+impl Journal {
+    pub fn open() -> Result<Self, anyhow::Error> {
+        let home_dir = match home_dir() {
+            Some(path) => path,
+            None => return Err(anyhow::anyhow!("Impossible to get your home dir!")),
+        };
+
+        // create .hyperliquid if it doesn't exist
+        let journal_path = home_dir.join(".hyperliquid");
+        std::fs::create_dir_all(&journal_path)?;
+
+        Self::init(Connection::open(journal_path.join("journal.db"))?)
+    }
+
+    /// A journal kept in memory only, for running commands when the journal file can't be opened
+    pub fn in_memory() -> Result<Self, anyhow::Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, anyhow::Error> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS commands (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                command_line TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                strategy TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS orders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command_id INTEGER REFERENCES commands(id),
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                asset INTEGER NOT NULL,
+                is_buy INTEGER NOT NULL,
+                limit_px TEXT NOT NULL,
+                sz TEXT NOT NULL,
+                reduce_only INTEGER NOT NULL,
+                request TEXT NOT NULL,
+                status TEXT,
+                oid INTEGER,
+                detail TEXT,
+                note TEXT,
+                tags TEXT
+            );
+            CREATE TABLE IF NOT EXISTS dca_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command_id INTEGER REFERENCES commands(id),
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                job_id INTEGER NOT NULL,
+                scheduled_at INTEGER NOT NULL,
+                outcome TEXT NOT NULL,
+                usd REAL NOT NULL,
+                filled_sz REAL NOT NULL,
+                filled_usd REAL NOT NULL,
+                detail TEXT
+            );",
+        )?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            command_id: None,
+        })
+    }
+
+    /// Records the invocation that every following order will be tagged with
+    pub fn record_command(
+        &mut self,
+        command_line: &str,
+        profile: &str,
+        strategy: &str,
+    ) -> Result<(), anyhow::Error> {
+        let conn = self.conn()?;
+
+        conn.execute(
+            "INSERT INTO commands (command_line, profile, strategy) VALUES (?1, ?2, ?3)",
+            params![command_line, profile, strategy],
+        )?;
+
+        let command_id = conn.last_insert_rowid();
+        drop(conn);
+
+        self.command_id = Some(command_id);
+
+        Ok(())
+    }
+
+    /// Records the orders about to be sent, returning their journal ids in the same order
+    pub fn record_orders(&self, orders: &[OrderRequest]) -> Result<Vec<i64>, anyhow::Error> {
+        let conn = self.conn()?;
+        let mut ids = Vec::with_capacity(orders.len());
+
+        for order in orders {
+            conn.execute(
+                "INSERT INTO orders (command_id, asset, is_buy, limit_px, sz, reduce_only, request)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    self.command_id,
+                    order.asset,
+                    order.is_buy,
+                    order.limit_px,
+                    order.sz,
+                    order.reduce_only,
+                    serde_json::to_string(order)?,
+                ],
+            )?;
+
+            ids.push(conn.last_insert_rowid());
+        }
+
+        Ok(ids)
+    }
+
+    pub fn record_status(&self, id: i64, status: &Status) -> Result<(), anyhow::Error> {
+        let (status, oid, detail) = match status {
+            Status::Filled(order) => ("filled", Some(order.oid), None),
+            Status::Resting(order) => ("resting", Some(order.oid), None),
+            Status::Error(msg) => ("error", None, Some(msg.clone())),
+            _ => ("unknown", None, None),
+        };
+
+        self.conn()?.execute(
+            "UPDATE orders SET status = ?1, oid = ?2, detail = ?3 WHERE id = ?4",
+            params![status, oid, detail, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Marks orders whose request never got a per-order status back, e.g. a rejected action
+    pub fn record_error(&self, ids: &[i64], err: &str) -> Result<(), anyhow::Error> {
+        let conn = self.conn()?;
+
+        for id in ids {
+            conn.execute(
+                "UPDATE orders SET status = 'error', detail = ?1 WHERE id = ?2",
+                params![err, id],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn list(&self, limit: u32) -> Result<Vec<JournalEntry>, anyhow::Error> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "{} ORDER BY o.id DESC LIMIT ?1",
+            Self::SELECT_ENTRY
+        ))?;
+
+        let entries = stmt
+            .query_map(params![limit], Self::entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    pub fn show(&self, id: i64) -> Result<Option<JournalEntry>, anyhow::Error> {
+        let entry = self
+            .conn()?
+            .query_row(
+                &format!("{} WHERE o.id = ?1", Self::SELECT_ENTRY),
+                params![id],
+                Self::entry,
+            )
+            .optional()?;
+
+        Ok(entry)
+    }
+
+    /// Id of the last journaled command, 0 before the first one
+    pub fn last_command_id(&self) -> Result<i64, anyhow::Error> {
+        let id = self.conn()?.query_row(
+            "SELECT COALESCE(MAX(id), 0) FROM commands",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(id)
+    }
+
+    /// Orders of the commands journaled after `after` with the given command line, oldest first
+    pub fn command_orders(
+        &self,
+        after: i64,
+        command_line: &str,
+    ) -> Result<Vec<JournalEntry>, anyhow::Error> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE o.command_id > ?1 AND c.command_line = ?2 ORDER BY o.id",
+            Self::SELECT_ENTRY
+        ))?;
+
+        let entries = stmt
+            .query_map(params![after, command_line], Self::entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Attaches a note and/or tags to a journaled trade, returns false if the trade doesn't exist
+    pub fn annotate(
+        &self,
+        id: i64,
+        note: Option<&str>,
+        tags: &[String],
+    ) -> Result<bool, anyhow::Error> {
+        let tags = if tags.is_empty() {
+            None
+        } else {
+            Some(tags.join(","))
+        };
+
+        let updated = self.conn()?.execute(
+            "UPDATE orders SET note = COALESCE(?1, note), tags = COALESCE(?2, tags) WHERE id = ?3",
+            params![note, tags, id],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    pub fn record_dca_run(&self, run: &DcaRun) -> Result<(), anyhow::Error> {
+        self.conn()?.execute(
+            "INSERT INTO dca_runs (command_id, job_id, scheduled_at, outcome, usd, filled_sz, filled_usd, detail)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                self.command_id,
+                run.job_id,
+                run.scheduled_at,
+                run.outcome,
+                run.usd,
+                run.filled_sz,
+                run.filled_usd,
+                run.detail,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Most recent runs of a dca job, newest first
+    pub fn dca_runs(&self, job_id: u64, limit: u32) -> Result<Vec<DcaRun>, anyhow::Error> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT created_at, job_id, scheduled_at, outcome, usd, filled_sz, filled_usd, detail
+             FROM dca_runs WHERE job_id = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+
+        let runs = stmt
+            .query_map(params![job_id, limit], |row| {
+                Ok(DcaRun {
+                    created_at: row.get(0)?,
+                    job_id: row.get(1)?,
+                    scheduled_at: row.get(2)?,
+                    outcome: row.get(3)?,
+                    usd: row.get(4)?,
+                    filled_sz: row.get(5)?,
+                    filled_usd: row.get(6)?,
+                    detail: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(runs)
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>, anyhow::Error> {
+        self.conn
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Journal connection lock poisoned"))
+    }
+
+    const SELECT_ENTRY: &'static str = "SELECT o.id, o.created_at, c.command_line, c.profile, c.strategy,
+            o.asset, o.is_buy, o.limit_px, o.sz, o.reduce_only, o.request, o.status, o.oid,
+            o.detail, o.note, o.tags
+        FROM orders o LEFT JOIN commands c ON c.id = o.command_id";
+
+    fn entry(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+        Ok(JournalEntry {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            command_line: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            profile: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            strategy: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            asset: row.get(5)?,
+            is_buy: row.get(6)?,
+            limit_px: row.get(7)?,
+            sz: row.get(8)?,
+            reduce_only: row.get(9)?,
+            request: row.get(10)?,
+            status: row.get(11)?,
+            oid: row.get(12)?,
+            detail: row.get(13)?,
+            note: row.get(14)?,
+            tags: row.get(15)?,
+        })
+    }
+}
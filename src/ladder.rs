@@ -0,0 +1,277 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid::{
+    types::exchange::{
+        request::{CancelRequest, Limit, OrderRequest, OrderType, Tif, TpSl, Trigger},
+        response::{Response, Status},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+
+use crate::{
+    helpers::{order_fills, place_batch},
+    journal::Journal,
+};
+
+/// Seconds between position checks when watching a ladder
+const CHECK_INTERVAL: u64 = 10;
+
+/// Reduce-only take-profit orders spread across a price range, sized to a share of a position
+pub struct TpLadder {
+    pub symbol: String,
+    pub asset: u32,
+    pub sz_decimals: u32,
+    /// Fraction of the position to take profit on e.g 0.5 for 50%
+    pub share: f64,
+    pub prices: Vec<f64>,
+    /// Places market trigger orders instead of resting limit orders
+    pub trigger: bool,
+}
+
+/// Open position in an asset: (size in the base asset, entry price, is long)
+pub async fn position(
+    info: &Info,
+    wallet: &LocalWallet,
+    symbol: &str,
+) -> Result<Option<(f64, f64, bool)>, anyhow::Error> {
+    let state = info.user_state(wallet.address()).await?;
+
+    let position = state.asset_positions.iter().find(|ap| {
+        ap.position.coin.to_uppercase() == symbol.to_uppercase() && ap.position.entry_px.is_some()
+    });
+
+    let position = match position {
+        Some(position) => &position.position,
+        None => return Ok(None),
+    };
+
+    // Positive for long, negative for short
+    let szi = position.szi.parse::<f64>()?;
+    let entry_px = position
+        .entry_px
+        .as_ref()
+        .expect("Entry price is checked above")
+        .parse::<f64>()?;
+
+    if szi == 0.0 {
+        return Ok(None);
+    }
+
+    Ok(Some((szi.abs(), entry_px, szi > 0.0)))
+}
+
+/// Evenly spaced prices from `from` to `to`, a single rung is placed in the middle
+pub fn prices(from: f64, to: f64, rungs: u32) -> Vec<f64> {
+    if rungs == 1 {
+        return vec![(from + to) / 2.0];
+    }
+
+    (0..rungs)
+        .map(|i| from + (to - from) * i as f64 / (rungs - 1) as f64)
+        .collect()
+}
+
+impl TpLadder {
+    /// Splits `total` between `count` rungs, rounding each so the rungs sum to the rounded total
+    fn sizes(&self, total: f64, count: usize) -> Vec<String> {
+        let round = |sz: f64| parse_size(sz, self.sz_decimals).parse::<f64>().unwrap_or(0.0);
+
+        (0..count)
+            .map(|i| {
+                let sz = round(total * (i + 1) as f64 / count as f64)
+                    - round(total * i as f64 / count as f64);
+
+                parse_size(sz, self.sz_decimals)
+            })
+            .collect()
+    }
+
+    fn orders(&self, prices: &[f64], total: f64, is_long: bool) -> Vec<OrderRequest> {
+        prices
+            .iter()
+            .zip(self.sizes(total, prices.len()))
+            .map(|(price, sz)| OrderRequest {
+                cloid: None,
+                asset: self.asset,
+                is_buy: !is_long,
+                limit_px: parse_price(*price),
+                sz,
+                reduce_only: true,
+                order_type: if self.trigger {
+                    OrderType::Trigger(Trigger {
+                        trigger_px: parse_price(*price).parse().unwrap(),
+                        is_market: true,
+                        tpsl: TpSl::Tp,
+                    })
+                } else {
+                    OrderType::Limit(Limit { tif: Tif::Gtc })
+                },
+            })
+            .filter(|order| order.sz.parse::<f64>().unwrap_or(0.0) > 0.0)
+            .collect()
+    }
+
+    /// Size filled by the given rungs
+    async fn filled(
+        &self,
+        info: &Info,
+        wallet: &LocalWallet,
+        rungs: &[(u64, f64)],
+    ) -> Result<f64, anyhow::Error> {
+        let mut filled = 0.0;
+
+        for (oid, _) in rungs {
+            filled += order_fills(info, wallet.address(), *oid).await?.0;
+        }
+
+        Ok(filled)
+    }
+
+    /// Places a rung at each price, returning the order ids of the rungs with their prices
+    async fn place(
+        &self,
+        exchange: &Exchange,
+        wallet: Arc<LocalWallet>,
+        prices: &[f64],
+        total: f64,
+        is_long: bool,
+        journal: &Journal,
+    ) -> Result<Vec<(u64, f64)>, anyhow::Error> {
+        let orders = self.orders(prices, total, is_long);
+
+        println!("{}", "---".repeat(20));
+        println!("Side: Close {}", if is_long { "Long" } else { "Short" });
+        for (i, order) in orders.iter().enumerate() {
+            println!(
+                "Rung {} of {}: {} {} at {}",
+                i + 1,
+                orders.len(),
+                order.sz,
+                self.symbol,
+                order.limit_px
+            );
+        }
+        println!();
+
+        let rungs = orders
+            .iter()
+            .map(|order| order.limit_px.parse::<f64>().unwrap_or(0.0))
+            .collect::<Vec<_>>();
+
+        let statuses = place_batch(exchange, wallet, orders, journal).await?;
+
+        Ok(statuses
+            .iter()
+            .zip(rungs)
+            .filter_map(|(status, price)| match status {
+                Status::Resting(order) => {
+                    println!("Take profit order {} at {} was successfully placed.", order.oid, price);
+                    Some((order.oid, price))
+                }
+                Status::Filled(order) => {
+                    println!("Take profit order {} at {} was successfully filled.", order.oid, price);
+                    Some((order.oid, price))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Places the ladder and, when `watch` is set, re-sizes the rungs still open whenever the
+    /// position changes other than by the ladder's own fills
+    pub async fn run(
+        &self,
+        info: &Info,
+        exchange: &Exchange,
+        wallet: Arc<LocalWallet>,
+        watch: bool,
+        journal: &Journal,
+    ) -> Result<(), anyhow::Error> {
+        let (position_sz, _, is_long) = position(info, &wallet, &self.symbol)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No open position for {}", self.symbol))?;
+
+        let mut rungs = self
+            .place(exchange, wallet.clone(), &self.prices, position_sz * self.share, is_long, journal)
+            .await?;
+
+        if !watch {
+            return Ok(());
+        }
+
+        println!("\nWatching the {} position to keep the ladder balanced\n---", self.symbol);
+
+        // the position as if none of the ladder had filled
+        let mut base_sz = position_sz;
+        // filled by rungs that were cancelled when re-balancing
+        let mut retired_filled = 0.0;
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+
+            let position_sz = match position(info, &wallet, &self.symbol).await? {
+                Some((position_sz, _, _)) => position_sz,
+                None => {
+                    println!("{} position closed", self.symbol);
+                    break;
+                }
+            };
+
+            let open_orders = info.open_orders(wallet.address()).await?;
+            let open = rungs
+                .iter()
+                .filter(|(oid, _)| open_orders.iter().any(|order| order.oid == *oid))
+                .copied()
+                .collect::<Vec<_>>();
+
+            if open.is_empty() {
+                println!("All take profit rungs filled ✔️");
+                break;
+            }
+
+            let filled = retired_filled + self.filled(info, &wallet, &rungs).await?;
+
+            let current_sz = position_sz + filled;
+            let step = 10f64.powi(-(self.sz_decimals as i32));
+            if (current_sz - base_sz).abs() < step {
+                continue;
+            }
+
+            println!(
+                "Position changed from {} to {} {}, re-balancing {} open rungs",
+                parse_size(base_sz, self.sz_decimals),
+                parse_size(current_sz, self.sz_decimals),
+                self.symbol,
+                open.len()
+            );
+
+            let cancels = open
+                .iter()
+                .map(|(oid, _)| CancelRequest {
+                    asset: self.asset,
+                    oid: *oid,
+                })
+                .collect::<Vec<_>>();
+
+            if let Response::Err(err) = exchange.cancel_order(wallet.clone(), cancels, None).await? {
+                return Err(anyhow::anyhow!(err));
+            }
+
+            // once cancelled, the fills of this set of rungs are final
+            retired_filled += self.filled(info, &wallet, &rungs).await?;
+
+            let prices = open.iter().map(|(_, price)| *price).collect::<Vec<_>>();
+            let remaining = (current_sz * self.share - retired_filled).max(0.0);
+
+            base_sz = current_sz;
+            rungs = self
+                .place(exchange, wallet.clone(), &prices, remaining, is_long, journal)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,27 @@
+pub mod alert;
+pub mod basket;
+pub mod batch;
+pub mod chase;
 pub mod command;
 pub mod config;
+pub mod daemon;
+pub mod dca;
+pub mod grid;
 pub mod helpers;
+pub mod iceberg;
+pub mod journal;
+pub mod ladder;
+pub mod market;
+pub mod pair;
+pub mod pov;
+pub mod rebalance;
+pub mod scale;
+pub mod serve;
+pub mod shell;
 pub mod startup;
+pub mod store;
+pub mod strategy;
+pub mod twap;
 pub mod types;
+pub mod webhook;
+pub mod when;
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hyperliquid::{types::info::response::AssetContext, Info};
+use tokio::sync::Mutex;
+
+use crate::helpers::asset_ctx;
+
+type Snapshot = (Instant, Arc<Vec<AssetContext>>);
+
+/// Asset contexts shared between jobs. Concurrent callers within `max_age` reuse the same fetch
+/// so many jobs watching prices cost a single request per refresh.
+#[derive(Clone)]
+pub struct MarketFeed {
+    info: Arc<Info>,
+    max_age: Duration,
+    latest: Arc<Mutex<Option<Snapshot>>>,
+}
+
+impl MarketFeed {
+    pub fn new(info: Arc<Info>, max_age: Duration) -> Self {
+        Self {
+            info,
+            max_age,
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn info(&self) -> &Info {
+        &self.info
+    }
+
+    pub async fn contexts(&self) -> hyperliquid::Result<Arc<Vec<AssetContext>>> {
+        let mut latest = self.latest.lock().await;
+
+        if let Some((fetched_at, asset_ctxs)) = latest.as_ref() {
+            if fetched_at.elapsed() < self.max_age {
+                return Ok(asset_ctxs.clone());
+            }
+        }
+
+        let asset_ctxs = Arc::new(self.info.contexts().await?);
+        *latest = Some((Instant::now(), asset_ctxs.clone()));
+
+        Ok(asset_ctxs)
+    }
+
+    pub async fn mark_px(&self, symbol: &str) -> Result<f64, anyhow::Error> {
+        let asset_ctxs = self.contexts().await?;
+
+        let mark_px = asset_ctx(&asset_ctxs, symbol)?
+            .ok_or_else(|| anyhow::anyhow!("Failed to find asset: {}", symbol))?
+            .mark_px
+            .parse::<f64>()?;
+
+        Ok(mark_px)
+    }
+}
@@ -0,0 +1,1041 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ethers::signers::LocalWallet;
+use hyperliquid::{
+    types::exchange::{
+        request::{Limit, OrderRequest, OrderType, Tif},
+        response::{Response, Status, StatusType},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::place_order,
+    journal::Journal,
+    market::MarketFeed,
+    store,
+    twap::{now, place_slice, Slice},
+    types::{Hedge, Recovery, Spread},
+};
+
+/// Candle interval the hedge ratio is estimated from
+const HEDGE_INTERVAL: &str = "1h";
+
+/// Number of candles the hedge ratio is estimated from, a week of hourly candles
+const HEDGE_CANDLES: u64 = 168;
+
+/// How often the spread's mean and standard deviation are re-computed from candle history
+const Z_SCORE_REFRESH: Duration = Duration::from_secs(5 * 60);
+
+/// Attempts at filling the missing size of a leg before giving up on it
+const LEG_RETRIES: u32 = 3;
+
+/// Mean and standard deviation of the spread over the lookback, with when they were computed
+type SpreadStats = (f64, f64, Instant);
+
+fn default_precision() -> usize {
+    6
+}
+
+fn default_lookback() -> u64 {
+    HEDGE_CANDLES
+}
+
+fn default_max_slippage() -> f64 {
+    0.01
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairJob {
+    pub base: String,
+    pub quote: String,
+    /// Longs base and shorts quote when true, shorts base and longs quote otherwise
+    pub is_buy: bool,
+    /// Total order size in USD, split between both legs by the hedge ratio
+    pub size: f64,
+    /// Spread to wait for before entering, enters at market when not set
+    pub target: Option<f64>,
+    pub tp: Option<f64>,
+    pub sl: Option<f64>,
+    #[serde(default)]
+    pub hedge: Hedge,
+    /// Watches the base/quote ratio or its log, target, tp and sl are in the same unit
+    #[serde(default)]
+    pub spread: Spread,
+    /// Decimals the spread is printed with
+    #[serde(default = "default_precision")]
+    pub precision: usize,
+    /// Enters once the spread is this many standard deviations from its mean, below it for a buy
+    /// and above it for a sell
+    pub entry_z: Option<f64>,
+    /// Exits once the spread reverts to within this many standard deviations of its mean
+    pub exit_z: Option<f64>,
+    /// Number of hourly candles the z-score is computed over
+    #[serde(default = "default_lookback")]
+    pub lookback: u64,
+    /// What to do when a leg doesn't fully fill on entry
+    #[serde(default)]
+    pub recovery: Recovery,
+    /// How far from a leg's entry price its missing size may be retried, e.g 0.01 for 1%
+    #[serde(default = "default_max_slippage")]
+    pub max_slippage: f64,
+}
+
+impl PairJob {
+    pub fn describe(&self) -> String {
+        format!(
+            "pair {} {}/{} ${}",
+            if self.is_buy { "buy" } else { "sell" },
+            self.base.to_uppercase(),
+            self.quote.to_uppercase(),
+            self.size
+        )
+    }
+
+    fn spread_of(&self, base_price: f64, quote_price: f64) -> f64 {
+        match self.spread {
+            Spread::Ratio => base_price / quote_price,
+            Spread::Log => (base_price / quote_price).ln(),
+        }
+    }
+
+    fn spread_name(&self) -> &'static str {
+        match self.spread {
+            Spread::Ratio => "Ratio",
+            Spread::Log => "Log Spread",
+        }
+    }
+
+    /// Formats a spread with the job's precision
+    fn fmt(&self, value: f64) -> String {
+        format!("{:.*}", self.precision, value)
+    }
+
+    async fn current_spread(&self, feed: &MarketFeed) -> Result<(f64, f64, f64), anyhow::Error> {
+        let base_market_price = feed.mark_px(&self.base).await?;
+        let quote_market_price = feed.mark_px(&self.quote).await?;
+
+        Ok((
+            base_market_price,
+            quote_market_price,
+            self.spread_of(base_market_price, quote_market_price),
+        ))
+    }
+
+    /// Z-score of the spread against its hourly closes over the lookback, re-computing the mean
+    /// and standard deviation when they are older than 5 minutes
+    async fn z_score(
+        &self,
+        info: &Info,
+        spread: f64,
+        stats: &mut Option<SpreadStats>,
+    ) -> Result<f64, anyhow::Error> {
+        let (mean, std) = match stats {
+            Some((mean, std, computed_at)) if computed_at.elapsed() < Z_SCORE_REFRESH => (*mean, *std),
+            _ => {
+                let base = closes(info, &self.base, self.lookback).await?;
+                let quote = closes(info, &self.quote, self.lookback).await?;
+
+                let spreads = base
+                    .iter()
+                    .filter_map(|(t, base)| {
+                        quote
+                            .iter()
+                            .find(|(quote_t, _)| quote_t == t)
+                            .map(|(_, quote)| self.spread_of(*base, *quote))
+                    })
+                    .collect::<Vec<_>>();
+
+                if spreads.len() < 2 {
+                    return Err(anyhow::anyhow!("Not enough candles to compute the z-score"));
+                }
+
+                let n = spreads.len() as f64;
+                let mean = spreads.iter().sum::<f64>() / n;
+                let std = (spreads.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+
+                if std <= 0.0 {
+                    return Err(anyhow::anyhow!("The spread didn't move, the z-score is undefined"));
+                }
+
+                *stats = Some((mean, std, Instant::now()));
+
+                (mean, std)
+            }
+        };
+
+        Ok((spread - mean) / std)
+    }
+
+    /// USD of the quote leg per USD of the base leg, estimated from recent candle returns
+    async fn hedge_ratio(&self, info: &Info) -> Result<f64, anyhow::Error> {
+        if self.hedge == Hedge::Equal {
+            return Ok(1.0);
+        }
+
+        let base = returns(info, &self.base).await?;
+        let quote = returns(info, &self.quote).await?;
+
+        // only candles both assets traded in are compared
+        let (base, quote): (Vec<_>, Vec<_>) = base
+            .iter()
+            .filter_map(|(t, base)| {
+                quote
+                    .iter()
+                    .find(|(quote_t, _)| quote_t == t)
+                    .map(|(_, quote)| (*base, *quote))
+            })
+            .unzip();
+
+        if base.len() < 2 {
+            return Err(anyhow::anyhow!("Not enough candles to estimate the hedge ratio"));
+        }
+
+        let n = base.len() as f64;
+        let base_mean = base.iter().sum::<f64>() / n;
+        let quote_mean = quote.iter().sum::<f64>() / n;
+
+        let base_var = base.iter().map(|r| (r - base_mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let quote_var = quote.iter().map(|r| (r - quote_mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let cov = base
+            .iter()
+            .zip(quote.iter())
+            .map(|(b, q)| (b - base_mean) * (q - quote_mean))
+            .sum::<f64>()
+            / (n - 1.0);
+
+        if quote_var <= 0.0 {
+            return Err(anyhow::anyhow!("{} didn't move, the hedge ratio is undefined", self.quote));
+        }
+
+        let ratio = match self.hedge {
+            Hedge::Equal => 1.0,
+            Hedge::Beta => cov / quote_var,
+            Hedge::Vol => (base_var / quote_var).sqrt(),
+        };
+
+        if ratio <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "{} and {} aren't positively correlated, the beta hedge ratio is {:.4}",
+                self.base.to_uppercase(),
+                self.quote.to_uppercase(),
+                ratio
+            ));
+        }
+
+        Ok(ratio)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PairStatus {
+    Open,
+    Closed,
+}
+
+/// Both legs of an entered pair, saved so the pair can be managed after its process exits
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairPosition {
+    pub id: u64,
+    /// The pair as entered, its tp and sl are the ones watched
+    pub job: PairJob,
+    /// Open size of each leg in its base asset
+    pub base_sz: f64,
+    pub quote_sz: f64,
+    pub base_entry_px: f64,
+    pub quote_entry_px: f64,
+    pub entry_spread: f64,
+    pub exit_spread: Option<f64>,
+    pub status: PairStatus,
+    pub created_at: u64,
+    pub closed_at: Option<u64>,
+}
+
+impl PairPosition {
+    fn new(job: PairJob, legs: &[Leg], entry_spread: f64) -> Result<Self, anyhow::Error> {
+        let position = Self {
+            id: 0,
+            job,
+            base_sz: legs[0].filled_sz,
+            quote_sz: legs[1].filled_sz,
+            base_entry_px: legs[0].filled_usd / legs[0].filled_sz,
+            quote_entry_px: legs[1].filled_usd / legs[1].filled_sz,
+            entry_spread,
+            exit_spread: None,
+            status: PairStatus::Open,
+            created_at: now(),
+            closed_at: None,
+        };
+
+        store::create("pair", position, |position, id| position.id = id)
+    }
+
+    pub fn load(id: u64) -> Result<Option<Self>, anyhow::Error> {
+        store::load("pair", id)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        store::save("pair", self.id, self)
+    }
+
+    pub fn list() -> Result<Vec<Self>, anyhow::Error> {
+        store::list("pair")
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        match self.status {
+            PairStatus::Open => "Open",
+            PairStatus::Closed => "Closed",
+        }
+    }
+
+    /// Unrealized PnL of both legs in USD at the given prices
+    pub fn pnl(&self, base_price: f64, quote_price: f64) -> f64 {
+        let base = self.base_sz * (base_price - self.base_entry_px);
+        let quote = self.quote_sz * (quote_price - self.quote_entry_px);
+
+        if self.job.is_buy {
+            base - quote
+        } else {
+            quote - base
+        }
+    }
+
+    /// Prints the pair with its live spread and PnL when the current prices are given
+    pub fn print(&self, prices: Option<(f64, f64)>) {
+        let job = &self.job;
+
+        println!("{}", "---".repeat(20));
+        println!("Pair Id: {}", self.id);
+        println!("Pair: {}", job.describe());
+        println!("Status: {}", self.status_name());
+        println!("Size in {}: {} at {}", job.base.to_uppercase(), self.base_sz, parse_price(self.base_entry_px));
+        println!("Size in {}: {} at {}", job.quote.to_uppercase(), self.quote_sz, parse_price(self.quote_entry_px));
+        println!("Entry {}: {}", job.spread_name(), job.fmt(self.entry_spread));
+        println!("Tp: {}", job.tp.map_or("-".to_string(), |tp| job.fmt(tp)));
+        println!("Sl: {}", job.sl.map_or("-".to_string(), |sl| job.fmt(sl)));
+
+        if let Some(exit_spread) = self.exit_spread {
+            println!("Exit {}: {}", job.spread_name(), job.fmt(exit_spread));
+        }
+
+        if let (PairStatus::Open, Some((base_price, quote_price))) = (self.status, prices) {
+            let spread = job.spread_of(base_price, quote_price);
+
+            println!("Current {}: {}", job.spread_name(), job.fmt(spread));
+            println!("{} Change: {}", job.spread_name(), job.fmt(spread - self.entry_spread));
+            println!("PnL in USD: {:.2}", self.pnl(base_price, quote_price));
+        }
+    }
+}
+
+/// Closes of the last `count` hourly candles of an asset, keyed by candle start time
+async fn closes(info: &Info, symbol: &str, count: u64) -> Result<Vec<(u64, f64)>, anyhow::Error> {
+    let end_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+    let start_time = end_time - count * 60 * 60 * 1000;
+
+    let candles = info
+        .candle_snapshot(symbol.to_uppercase(), HEDGE_INTERVAL.to_string(), start_time, end_time)
+        .await?;
+
+    candles
+        .iter()
+        .map(|candle| Ok((candle.t_start, candle.c.parse::<f64>()?)))
+        .collect()
+}
+
+/// Log returns of the recent candles of an asset, keyed by candle start time
+async fn returns(info: &Info, symbol: &str) -> Result<Vec<(u64, f64)>, anyhow::Error> {
+    let closes = closes(info, symbol, HEDGE_CANDLES).await?;
+
+    Ok(closes
+        .windows(2)
+        .map(|pair| (pair[1].0, (pair[1].1 / pair[0].1).ln()))
+        .collect())
+}
+
+/// Enters both legs of a pair, at market or once the target spread or entry z-score is reached,
+/// then monitors the spread until the tp, sl or exit z-score is hit and closes both legs
+pub async fn run(
+    job: PairJob,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) {
+    let slippage = 3.0 / 100.0;
+
+    let (base_sz_decimals, base_asset) = match assets.get(&job.base.to_uppercase()) {
+        Some(asset) => *asset,
+        None => {
+            println!("Failed to find base asset:  {}", job.base);
+            return;
+        }
+    };
+
+    let (quote_sz_decimals, quote_asset) = match assets.get(&job.quote.to_uppercase()) {
+        Some(asset) => *asset,
+        None => {
+            println!("Failed to find quote asset:  {}", job.quote);
+            return;
+        }
+    };
+
+    let (long_side, short_side) = if job.is_buy {
+        ("Buy", "Sell")
+    } else {
+        ("Sell", "Buy")
+    };
+
+    let mut stats = None;
+
+    // If limit price for eth/btc is .06, wait for the eth/btc ratio to become .06,
+    // then enter both legs at market
+    let (base_market_price, quote_market_price, current_spread) = loop {
+        let (base_market_price, quote_market_price, current_spread) = match job.current_spread(feed).await {
+            Ok(spread) => spread,
+            Err(err) => {
+                println!("Failed to fetch {}: {:#?}. Checking again in 5 seconds\n---", job.spread_name().to_lowercase(), err);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        if let Some(entry_z) = job.entry_z {
+            let z = match job.z_score(feed.info(), current_spread, &mut stats).await {
+                Ok(z) => z,
+                Err(err) => {
+                    println!("Failed to compute z-score: {:#?}. Checking again in 5 seconds\n---", err);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+
+            if job.is_buy && z <= -entry_z {
+                println!("Entry z-score reached: {:.2} <= {}", z, -entry_z);
+                break (base_market_price, quote_market_price, current_spread);
+            }
+
+            if !job.is_buy && z >= entry_z {
+                println!("Entry z-score reached: {:.2} >= {}", z, entry_z);
+                break (base_market_price, quote_market_price, current_spread);
+            }
+
+            println!(
+                "Current {}: {}, Z-Score: {:.2}, Entry Z-Score: {}. Checking again in 5 seconds\n---",
+                job.spread_name(),
+                job.fmt(current_spread),
+                z,
+                if job.is_buy { -entry_z } else { entry_z },
+            );
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+
+        let target = match job.target {
+            Some(target) => target,
+            None => break (base_market_price, quote_market_price, current_spread),
+        };
+
+        if job.is_buy && current_spread >= target {
+            println!("{} reached: {} >= {}", job.spread_name(), job.fmt(current_spread), target);
+            break (base_market_price, quote_market_price, current_spread);
+        }
+
+        if !job.is_buy && current_spread <= target {
+            println!("{} reached: {} <= {}", job.spread_name(), job.fmt(current_spread), target);
+            break (base_market_price, quote_market_price, current_spread);
+        }
+
+        println!(
+            "Current {}: {}, Target: {}, Diff: {}. Checking again in 5 seconds\n---",
+            job.spread_name(),
+            job.fmt(current_spread),
+            job.fmt(target),
+            job.fmt(current_spread - target),
+        );
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    };
+
+    let hedge_ratio = match job.hedge_ratio(feed.info()).await {
+        Ok(hedge_ratio) => hedge_ratio,
+        Err(err) => {
+            println!("Failed to estimate the hedge ratio: {:#?}", err);
+            return;
+        }
+    };
+
+    // the quote leg carries hedge_ratio USD for every USD of the base leg
+    let base_usd = job.size / (1.0 + hedge_ratio);
+    let quote_usd = job.size - base_usd;
+
+    println!("{}", "---".repeat(20));
+    println!("Hedge: {}", match job.hedge {
+        Hedge::Equal => "equal",
+        Hedge::Beta => "beta",
+        Hedge::Vol => "vol",
+    });
+    println!("Hedge Ratio: {:.4}", hedge_ratio);
+    println!("{} Notional: {:.2}", job.base.to_uppercase(), base_usd);
+    println!("{} Notional: {:.2}\n", job.quote.to_uppercase(), quote_usd);
+
+    let base_sz = base_usd / base_market_price;
+    let quote_sz = quote_usd / quote_market_price;
+
+    // Takes the base share of order size and longs/shorts Asset X
+    let base_order = {
+        let order = OrderRequest {
+            cloid: None,
+            asset: base_asset,
+            is_buy: job.is_buy,
+            limit_px: parse_price(if job.is_buy {
+                base_market_price * (1.0 + slippage)
+            } else {
+                base_market_price * (1.0 - slippage)
+            }),
+            sz: parse_size(base_sz, base_sz_decimals),
+            reduce_only: false,
+            order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+        };
+
+        println!("{}", "---".repeat(20));
+        println!("Order 1 of 2");
+        println!("Side: {}", long_side);
+        println!("Size in {}: {}", job.base, parse_size(base_sz, base_sz_decimals));
+        println!(
+            "Size in USD: {}",
+            parse_size(base_sz * base_market_price, base_sz_decimals)
+        );
+        println!("Market price: {}\n", base_market_price);
+        println!("{}: {}\n", job.spread_name(), job.fmt(current_spread));
+
+        order
+    };
+
+    // takes the quote share of order size and shorts/longs Asset Y
+    let quote_order = {
+        let order = OrderRequest {
+            cloid: None,
+            asset: quote_asset,
+            is_buy: !job.is_buy,
+            limit_px: parse_price(if job.is_buy {
+                quote_market_price * (1.0 - slippage)
+            } else {
+                quote_market_price * (1.0 + slippage)
+            }),
+            sz: parse_size(quote_sz, quote_sz_decimals),
+            reduce_only: false,
+            order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+        };
+
+        println!("{}", "---".repeat(20));
+        println!("Order 2 of 2");
+        println!("Side: {}", short_side);
+        println!("Size in {}: {}", job.quote, parse_size(quote_sz, quote_sz_decimals));
+        println!(
+            "Size in USD: {}",
+            parse_size(quote_sz * quote_market_price, quote_sz_decimals)
+        );
+        println!("Market price: {}\n", quote_market_price);
+        println!("{}: {}\n", job.spread_name(), job.fmt(current_spread));
+
+        order
+    };
+
+    let mut legs = [
+        Leg::new(&job.base, base_order, base_sz_decimals, base_market_price),
+        Leg::new(&job.quote, quote_order, quote_sz_decimals, quote_market_price),
+    ];
+
+    let hedged = execute_legs(
+        feed,
+        exchange,
+        wallet.clone(),
+        &mut legs,
+        (job.recovery == Recovery::Retry).then_some(job.max_slippage),
+        true,
+        journal,
+    )
+    .await;
+
+    if !hedged {
+        return;
+    }
+
+    let position = match PairPosition::new(job, &legs, current_spread) {
+        Ok(position) => position,
+        Err(err) => {
+            println!("Failed to save the pair, its legs are open: {:#?}", err);
+            return;
+        }
+    };
+
+    println!("Pair {} successfully saved ✔️\n---", position.id);
+
+    monitor(position, feed, exchange, wallet, assets, journal).await;
+}
+
+/// Watches an open pair until its tp, sl or exit z-score is hit and closes both legs. The saved
+/// pair is re-read every check so tp and sl changes and a close from another process are seen.
+pub async fn monitor(
+    mut position: PairPosition,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) {
+    let mut stats = None;
+
+    println!("Monitoring pair {} for tp, sl or exit z-score\n---", position.id);
+
+    loop {
+        match PairPosition::load(position.id) {
+            Ok(Some(latest)) => position = latest,
+            Ok(None) => {
+                println!("Pair {} no longer exists, stopping", position.id);
+                return;
+            }
+            Err(err) => println!("Failed to read pair {}: {:#?}", position.id, err),
+        }
+
+        let job = &position.job;
+
+        if position.status == PairStatus::Closed {
+            println!("Pair {} was closed", position.id);
+            return;
+        }
+
+        if job.tp.is_none() && job.sl.is_none() && job.exit_z.is_none() {
+            println!(
+                "Pair {} has no tp, sl or exit z-score to watch. Set one with `hl pair set-tp` and resume with `hl pair resume`",
+                position.id
+            );
+            return;
+        }
+
+        let (base_market_price, quote_market_price, current_spread) = match job.current_spread(feed).await {
+            Ok(spread) => spread,
+            Err(err) => {
+                println!("Failed to fetch {}: {:#?}. Checking again in 5 seconds\n---", job.spread_name().to_lowercase(), err);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        // the live z-score is printed whenever candle history is available
+        let z = match job.z_score(feed.info(), current_spread, &mut stats).await {
+            Ok(z) => Some(z),
+            Err(err) if job.exit_z.is_some() => {
+                println!("Failed to compute z-score: {:#?}. Checking again in 5 seconds\n---", err);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            Err(_) => None,
+        };
+
+        let mut reached = false;
+
+        // check if tp or sl has been reached
+        if let Some(tp) = job.tp {
+            if job.is_buy && current_spread >= tp {
+                println!("Take profit reached: {} >= {}", job.fmt(current_spread), tp);
+                reached = true;
+            }
+
+            if !job.is_buy && current_spread <= tp {
+                println!("Take profit reached: {} <= {}", job.fmt(current_spread), tp);
+                reached = true;
+            }
+        }
+
+        if let Some(sl) = job.sl {
+            if job.is_buy && current_spread <= sl {
+                println!("Stop loss reached: {} <= {}", job.fmt(current_spread), sl);
+                reached = true;
+            }
+
+            if !job.is_buy && current_spread >= sl {
+                println!("Stop loss reached: {} >= {}", job.fmt(current_spread), sl);
+                reached = true;
+            }
+        }
+
+        if let (Some(exit_z), Some(z)) = (job.exit_z, z) {
+            if job.is_buy && z >= -exit_z {
+                println!("Exit z-score reached: {:.2} >= {}", z, -exit_z);
+                reached = true;
+            }
+
+            if !job.is_buy && z <= exit_z {
+                println!("Exit z-score reached: {:.2} <= {}", z, exit_z);
+                reached = true;
+            }
+        }
+
+        if reached {
+            break;
+        }
+
+        println!(
+            "Current {}: {}, Z-Score: {}, Tp: {}, Sl: {}, Exit Z-Score: {}, PnL: {:.2}. Checking again in 5 seconds\n---",
+            job.spread_name(),
+            job.fmt(current_spread),
+            z.map_or("-".to_string(), |z| format!("{:.2}", z)),
+            job.tp.map_or("-".to_string(), |tp| job.fmt(tp)),
+            job.sl.map_or("-".to_string(), |sl| job.fmt(sl)),
+            job.exit_z.map_or("-".to_string(), |exit_z| {
+                format!("{}", if job.is_buy { -exit_z } else { exit_z })
+            }),
+            position.pnl(base_market_price, quote_market_price),
+        );
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+
+    close(&mut position, feed, exchange, wallet, assets, journal).await;
+}
+
+/// Closes both legs of a pair together at market, returns true if both fully closed. What is
+/// left of a leg that didn't fully close stays saved so closing again finishes it.
+pub async fn close(
+    position: &mut PairPosition,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) -> bool {
+    let slippage = 3.0 / 100.0;
+    let job = position.job.clone();
+
+    let (base_sz_decimals, base_asset) = match assets.get(&job.base.to_uppercase()) {
+        Some(asset) => *asset,
+        None => {
+            println!("Failed to find base asset:  {}", job.base);
+            return false;
+        }
+    };
+
+    let (quote_sz_decimals, quote_asset) = match assets.get(&job.quote.to_uppercase()) {
+        Some(asset) => *asset,
+        None => {
+            println!("Failed to find quote asset:  {}", job.quote);
+            return false;
+        }
+    };
+
+    let (base_market_price, quote_market_price, current_spread) = match job.current_spread(feed).await {
+        Ok(spread) => spread,
+        Err(err) => {
+            println!("Failed to fetch {}: {:#?}", job.spread_name().to_lowercase(), err);
+            return false;
+        }
+    };
+
+    let (long_side, short_side) = if job.is_buy {
+        ("Buy", "Sell")
+    } else {
+        ("Sell", "Buy")
+    };
+
+    // place exit orders
+    let exit_base_order = OrderRequest {
+        cloid: None,
+        asset: base_asset,
+        is_buy: !job.is_buy,
+        limit_px: parse_price(if job.is_buy {
+            base_market_price * (1.0 - slippage)
+        } else {
+            base_market_price * (1.0 + slippage)
+        }),
+        sz: parse_size(position.base_sz, base_sz_decimals),
+        reduce_only: true,
+        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+    };
+
+    println!("{}", "---".repeat(20));
+    println!("Order 1 of 2");
+    println!("Side: {}", short_side);
+    println!("Size in {}: {}", job.base, parse_size(position.base_sz, base_sz_decimals));
+    println!("{}: {}\n", job.spread_name(), job.fmt(current_spread));
+
+    let exit_quote_order = OrderRequest {
+        cloid: None,
+        asset: quote_asset,
+        is_buy: job.is_buy,
+        limit_px: parse_price(if job.is_buy {
+            quote_market_price * (1.0 + slippage)
+        } else {
+            quote_market_price * (1.0 - slippage)
+        }),
+        sz: parse_size(position.quote_sz, quote_sz_decimals),
+        reduce_only: true,
+        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+    };
+
+    println!("{}", "---".repeat(20));
+    println!("Order 2 of 2");
+    println!("Side: {}", long_side);
+    println!("Size in {}: {}", job.quote, parse_size(position.quote_sz, quote_sz_decimals));
+    println!("{}: {}\n", job.spread_name(), job.fmt(current_spread));
+
+    let pnl = position.pnl(base_market_price, quote_market_price);
+
+    let mut legs = [
+        Leg::new(&job.base, exit_base_order, base_sz_decimals, base_market_price),
+        Leg::new(&job.quote, exit_quote_order, quote_sz_decimals, quote_market_price),
+    ];
+
+    // an exit is never unwound, what is left of it is retried
+    let closed = execute_legs(
+        feed,
+        exchange,
+        wallet.clone(),
+        &mut legs,
+        Some(slippage),
+        false,
+        journal,
+    )
+    .await;
+
+    if closed {
+        position.status = PairStatus::Closed;
+        position.exit_spread = Some(current_spread);
+        position.closed_at = Some(now());
+
+        println!("Pair {} closed with a PnL of about {:.2} USD ✔️\n---", position.id, pnl);
+    } else {
+        position.base_sz = (position.base_sz - legs[0].filled_sz).max(0.0);
+        position.quote_sz = (position.quote_sz - legs[1].filled_sz).max(0.0);
+    }
+
+    if let Err(err) = position.save() {
+        println!("Failed to update pair {}: {:#?}", position.id, err);
+    }
+
+    closed
+}
+
+/// One leg of a pair or basket and what was done to fill it
+pub struct Leg {
+    symbol: String,
+    order: OrderRequest,
+    sz_decimals: u32,
+    /// Market price the leg was priced at, the retry slippage budget is measured from it
+    market_price: f64,
+    pub filled_sz: f64,
+    pub filled_usd: f64,
+    /// Part of the filled size that came from retries
+    retried_sz: f64,
+    unwound_sz: f64,
+}
+
+impl Leg {
+    pub fn new(symbol: &str, order: OrderRequest, sz_decimals: u32, market_price: f64) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            order,
+            sz_decimals,
+            market_price,
+            filled_sz: 0.0,
+            filled_usd: 0.0,
+            retried_sz: 0.0,
+            unwound_sz: 0.0,
+        }
+    }
+
+    /// Size still to fill, rounded to the asset's size decimals
+    fn missing(&self) -> f64 {
+        let sz = self.order.sz.parse::<f64>().unwrap_or(0.0);
+
+        parse_size((sz - self.filled_sz).max(0.0), self.sz_decimals)
+            .parse::<f64>()
+            .unwrap_or(0.0)
+    }
+
+    fn side(&self, is_buy: bool) -> &'static str {
+        if is_buy {
+            "Buy"
+        } else {
+            "Sell"
+        }
+    }
+}
+
+/// Submits every leg in a single request and checks what filled. A leg that didn't fully fill is
+/// retried at market while its price stays within `retry_slippage` of the price it was sized at.
+/// If a leg is still missing and `unwind` is set, the filled legs are closed at market so no
+/// naked position is left. Prints what was done and returns true if every leg filled.
+pub async fn execute_legs(
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    legs: &mut [Leg],
+    retry_slippage: Option<f64>,
+    unwind: bool,
+    journal: &Journal,
+) -> bool {
+    let orders = legs.iter().map(|leg| leg.order.clone()).collect();
+
+    match place_order(exchange, wallet.clone(), orders, journal).await {
+        Ok(Response::Ok(response)) => {
+            if let Some(StatusType::Statuses(statuses)) = response.data {
+                for (leg, status) in legs.iter_mut().zip(statuses.iter()) {
+                    match status {
+                        Status::Filled(order) => {
+                            println!("Order {} was successfully filled.", order.oid);
+
+                            leg.filled_sz = order.total_sz.parse::<f64>().unwrap_or(0.0);
+                            leg.filled_usd = leg.filled_sz * order.avg_px.parse::<f64>().unwrap_or(0.0);
+                        }
+                        Status::Error(msg) => {
+                            println!("{} leg failed with error: {:#?}", leg.symbol, msg);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(Response::Err(err)) => println!("Request failed: {:#?}", err),
+        Err(err) => println!("Request failed: {:#?}", err),
+    }
+
+    if let Some(retry_slippage) = retry_slippage {
+        for leg in legs.iter_mut() {
+            for _ in 0..LEG_RETRIES {
+                let missing = leg.missing();
+                if missing <= 0.0 {
+                    break;
+                }
+
+                let market_price = match feed.mark_px(&leg.symbol).await {
+                    Ok(market_price) => market_price,
+                    Err(err) => {
+                        println!("Failed to fetch {} price: {:#?}", leg.symbol, err);
+                        continue;
+                    }
+                };
+
+                let limit_px = if leg.order.is_buy {
+                    leg.market_price * (1.0 + retry_slippage)
+                } else {
+                    leg.market_price * (1.0 - retry_slippage)
+                };
+
+                if (leg.order.is_buy && market_price > limit_px) || (!leg.order.is_buy && market_price < limit_px) {
+                    println!(
+                        "{} moved to {} beyond the {}% slippage budget, not retrying",
+                        leg.symbol,
+                        market_price,
+                        retry_slippage * 100.0
+                    );
+                    break;
+                }
+
+                println!("Retrying {} {} {}", leg.side(leg.order.is_buy), missing, leg.symbol);
+
+                let slice = Slice {
+                    asset: leg.order.asset,
+                    sz_decimals: leg.sz_decimals,
+                    is_buy: leg.order.is_buy,
+                    sz: missing,
+                    market_price,
+                    limit_px: Some(limit_px),
+                    // the missing size of an exit is closed, never opened the other way
+                    reduce_only: leg.order.reduce_only,
+                };
+
+                match place_slice(exchange, wallet.clone(), slice, journal).await {
+                    Ok((filled_sz, filled_usd)) => {
+                        leg.filled_sz += filled_sz;
+                        leg.filled_usd += filled_usd;
+                        leg.retried_sz += filled_sz;
+                    }
+                    Err(err) => println!("Retry failed: {:#?}", err),
+                }
+
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+
+    let complete = legs.iter().all(|leg| leg.missing() <= 0.0);
+
+    if !complete && unwind {
+        println!("A leg didn't fill, unwinding the filled legs");
+
+        for leg in legs.iter_mut().filter(|leg| leg.filled_sz > 0.0) {
+            let market_price = match feed.mark_px(&leg.symbol).await {
+                Ok(market_price) => market_price,
+                Err(err) => {
+                    println!("Failed to fetch {} price, {} is left open: {:#?}", leg.symbol, leg.symbol, err);
+                    continue;
+                }
+            };
+
+            let slice = Slice {
+                asset: leg.order.asset,
+                sz_decimals: leg.sz_decimals,
+                is_buy: !leg.order.is_buy,
+                sz: leg.filled_sz,
+                market_price,
+                limit_px: None,
+                reduce_only: true,
+            };
+
+            match place_slice(exchange, wallet.clone(), slice, journal).await {
+                Ok((unwound_sz, _)) => leg.unwound_sz = unwound_sz,
+                Err(err) => println!("Failed to unwind {}: {:#?}", leg.symbol, err),
+            }
+        }
+    }
+
+    println!("{}", "---".repeat(20));
+    for leg in legs.iter() {
+        println!(
+            "{} {}: requested {}, filled {}{}{}{}",
+            leg.side(leg.order.is_buy),
+            leg.symbol,
+            leg.order.sz,
+            parse_size(leg.filled_sz, leg.sz_decimals),
+            if leg.filled_sz > 0.0 {
+                format!(" at {}", parse_price(leg.filled_usd / leg.filled_sz))
+            } else {
+                String::new()
+            },
+            if leg.retried_sz > 0.0 {
+                format!(" ({} on retry)", parse_size(leg.retried_sz, leg.sz_decimals))
+            } else {
+                String::new()
+            },
+            if leg.unwound_sz > 0.0 {
+                format!(", unwound {}", parse_size(leg.unwound_sz, leg.sz_decimals))
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    let open = legs
+        .iter()
+        .filter(|leg| parse_size(leg.filled_sz - leg.unwound_sz, leg.sz_decimals).parse::<f64>().unwrap_or(0.0) > 0.0)
+        .count();
+
+    match (complete, unwind) {
+        (true, _) => println!("Every leg filled ✔️\n"),
+        (false, true) if open == 0 => println!("The legs were unwound, no position is left open\n"),
+        (false, true) => println!("The legs couldn't be fully unwound, check your open positions\n"),
+        (false, false) => println!("Not every leg filled, check your open positions\n"),
+    }
+
+    complete
+}
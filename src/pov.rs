@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::LocalWallet;
+use hyperliquid::{utils::parse_size, Exchange};
+
+use crate::{
+    journal::Journal,
+    market::MarketFeed,
+    twap::{now, place_slice, Slice, VolumeWindow},
+};
+
+/// Seconds between volume checks
+const CHECK_INTERVAL: u64 = 10;
+
+/// Smallest order value accepted by the exchange in USD
+const MIN_ORDER_USD: f64 = 10.0;
+
+pub struct PovOrder {
+    pub symbol: String,
+    pub is_buy: bool,
+    /// Total order size in USD
+    pub size: f64,
+    /// Fraction of the traded volume to execute e.g 0.05 for 5%
+    pub rate: f64,
+    /// Volume traded beyond this price isn't participated in
+    pub limit_px: Option<f64>,
+    /// Unix timestamp (secs) to stop at, even if the order isn't fully executed
+    pub deadline: Option<u64>,
+}
+
+impl PovOrder {
+    fn side(&self) -> &'static str {
+        if self.is_buy {
+            "Buy"
+        } else {
+            "Sell"
+        }
+    }
+}
+
+/// Executes an order as a percentage of the volume traded in the asset. Every few seconds the
+/// volume traded since the last check is read from the trades feed and the part of it owed is
+/// placed at market.
+pub async fn run(
+    order: PovOrder,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    (sz_decimals, asset): (u32, u32),
+    journal: &Journal,
+) {
+    let mut observed_usd = 0.0;
+    let mut executed_sz = 0.0;
+    let mut executed_usd = 0.0;
+    // our own fills show up in the trades feed during the next check
+    let mut own_usd = 0.0;
+
+    let mut window = match VolumeWindow::start(feed, &order.symbol).await {
+        Ok(window) => window,
+        Err(err) => {
+            println!("Failed to fetch traded volume: {:#?}", err);
+            return;
+        }
+    };
+
+    println!(
+        "{} ${} of {} at {}% of the traded volume\n---",
+        order.side(),
+        order.size,
+        order.symbol,
+        order.rate * 100.0
+    );
+
+    // anything less is dust left by size rounding
+    while order.size - executed_usd >= 1.0 {
+        if order.deadline.is_some_and(|deadline| now() >= deadline) {
+            println!("Deadline reached");
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+
+        let market_price = match feed.mark_px(&order.symbol).await {
+            Ok(market_price) => market_price,
+            Err(err) => {
+                println!("Failed to fetch market price: {:#?}", err);
+                continue;
+            }
+        };
+
+        if let Some(limit_px) = order.limit_px {
+            let beyond_limit = if order.is_buy {
+                market_price > limit_px
+            } else {
+                market_price < limit_px
+            };
+
+            if beyond_limit {
+                println!(
+                    "Market price {} is beyond the limit price {}, not participating\n---",
+                    market_price, limit_px
+                );
+                // the volume traded meanwhile isn't participated in
+                if let Err(err) = window.read(feed).await {
+                    println!("Failed to fetch traded volume: {:#?}", err);
+                }
+                own_usd = 0.0;
+                continue;
+            }
+        }
+
+        let traded_usd = match window.read(feed).await {
+            Ok(traded_usd) => (traded_usd - own_usd).max(0.0),
+            Err(err) => {
+                println!("Failed to fetch traded volume: {:#?}", err);
+                continue;
+            }
+        };
+        own_usd = 0.0;
+        observed_usd += traded_usd;
+
+        let remaining_usd = order.size - executed_usd;
+        let mut owed_usd = (observed_usd * order.rate).min(order.size) - executed_usd;
+
+        // don't leave a remainder too small to be placed
+        if owed_usd >= MIN_ORDER_USD && remaining_usd - owed_usd < MIN_ORDER_USD {
+            owed_usd = remaining_usd;
+        }
+
+        if owed_usd >= MIN_ORDER_USD.min(remaining_usd) {
+            let sz = owed_usd / market_price;
+
+            println!("{}", "---".repeat(20));
+            println!("Side: {}", order.side());
+            println!("Size in {}: {}", order.symbol, parse_size(sz, sz_decimals));
+            println!("Size in USD: {}", parse_size(sz * market_price, sz_decimals));
+            println!("Market price: {}\n", market_price);
+
+            let slice = Slice {
+                asset,
+                sz_decimals,
+                is_buy: order.is_buy,
+                sz,
+                market_price,
+                limit_px: order.limit_px,
+                reduce_only: false,
+            };
+
+            match place_slice(exchange, wallet.clone(), slice, journal).await {
+                Ok((filled_sz, filled_usd)) => {
+                    executed_sz += filled_sz;
+                    executed_usd += filled_usd;
+                    own_usd = filled_usd;
+                }
+                Err(err) => {
+                    println!("{:#?}", err);
+                    break;
+                }
+            }
+        }
+
+        println!(
+            "Executed {} {} (${:.2}) of ${:.2} target, ${} total. Observed volume: ${:.2}",
+            executed_sz,
+            order.symbol,
+            executed_usd,
+            (observed_usd * order.rate).min(order.size),
+            order.size,
+            observed_usd
+        );
+        println!("{}", "-".repeat(5));
+    }
+
+    println!("{}", "---".repeat(20));
+    println!(
+        "Pov finished: executed {} {} (${:.2}) of ${}",
+        executed_sz, order.symbol, executed_usd, order.size
+    );
+    if observed_usd > 0.0 {
+        println!(
+            "Participation: {:.2}% of ${:.2} traded",
+            executed_usd / observed_usd * 100.0,
+            observed_usd
+        );
+    }
+}
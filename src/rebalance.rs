@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid::{
+    types::exchange::request::{Limit, OrderRequest, OrderType, Tif},
+    utils::{parse_price, parse_size},
+    Info,
+};
+
+use crate::helpers::asset_ctx;
+
+/// Deltas smaller than this in USD are below the exchange minimum and aren't placed
+const MIN_ORDER_USD: f64 = 10.0;
+
+/// The order needed to move one asset from its current notional to its target
+pub struct Delta {
+    pub symbol: String,
+    pub asset: u32,
+    pub sz_decimals: u32,
+    pub mark_px: f64,
+    /// Signed notional of the open position in USD, negative when short
+    pub current_usd: f64,
+    /// Signed target notional in USD
+    pub target_usd: f64,
+    /// Why no order is placed for this asset
+    pub skip: Option<&'static str>,
+}
+
+impl Delta {
+    /// Signed USD to buy, negative to sell
+    pub fn usd(&self) -> f64 {
+        self.target_usd - self.current_usd
+    }
+}
+
+/// Reads the open positions and mark prices and computes the order needed for every target.
+/// Assets whose weight drifted from its target by no more than `band` are skipped.
+pub async fn deltas(
+    info: &Info,
+    wallet: &LocalWallet,
+    assets: &HashMap<String, (u32, u32)>,
+    targets: &[(String, f64)],
+    notional: f64,
+    band: f64,
+) -> Result<Vec<Delta>, anyhow::Error> {
+    let state = info.user_state(wallet.address()).await?;
+    let asset_ctxs = info.contexts().await?;
+
+    let mut deltas = Vec::with_capacity(targets.len());
+
+    for (symbol, weight) in targets {
+        if deltas.iter().any(|delta: &Delta| delta.symbol == *symbol) {
+            return Err(anyhow::anyhow!("{} has more than one target", symbol));
+        }
+
+        let (sz_decimals, asset) = *assets
+            .get(symbol)
+            .ok_or_else(|| anyhow::anyhow!("Failed to find asset: {}", symbol))?;
+
+        let mark_px = asset_ctx(&asset_ctxs, symbol)?
+            .ok_or_else(|| anyhow::anyhow!("Failed to find asset ctx: {}", symbol))?
+            .mark_px
+            .parse::<f64>()?;
+
+        // positive for long, negative for short
+        let szi = state
+            .asset_positions
+            .iter()
+            .find(|ap| ap.position.coin.to_uppercase() == *symbol)
+            .map(|ap| ap.position.szi.parse::<f64>())
+            .transpose()?
+            .unwrap_or(0.0);
+
+        let current_usd = szi * mark_px;
+        let target_usd = weight * notional;
+
+        let mut delta = Delta {
+            symbol: symbol.clone(),
+            asset,
+            sz_decimals,
+            mark_px,
+            current_usd,
+            target_usd,
+            skip: None,
+        };
+
+        if (delta.usd() / notional).abs() <= band {
+            delta.skip = Some("within band");
+        } else if delta.usd().abs() < MIN_ORDER_USD {
+            delta.skip = Some("below minimum order");
+        }
+
+        deltas.push(delta);
+    }
+
+    Ok(deltas)
+}
+
+/// Prints the current and target weight of every asset with the order that rebalances it
+pub fn preview(deltas: &[Delta], notional: f64) {
+    println!("{}", "---".repeat(20));
+    println!(
+        "{:<8}{:>12}{:>12}{:>8}{:>16}{:>14}  Note",
+        "Asset", "Current", "Target", "Side", "Size", "Size in USD"
+    );
+
+    for delta in deltas {
+        let usd = delta.usd();
+
+        println!(
+            "{:<8}{:>11.2}%{:>11.2}%{:>8}{:>16}{:>14.2}  {}",
+            delta.symbol,
+            delta.current_usd / notional * 100.0,
+            delta.target_usd / notional * 100.0,
+            if usd > 0.0 { "Buy" } else { "Sell" },
+            parse_size(usd.abs() / delta.mark_px, delta.sz_decimals),
+            usd.abs(),
+            delta.skip.unwrap_or("")
+        );
+    }
+
+    println!(
+        "\nTurnover: ${:.2}\n",
+        deltas
+            .iter()
+            .filter(|delta| delta.skip.is_none())
+            .map(|delta| delta.usd().abs())
+            .sum::<f64>()
+    );
+}
+
+/// One order per delta that isn't skipped, IOC with 3% slippage or resting GTC at the mark price
+pub fn orders(deltas: &[Delta], limit: bool) -> Vec<OrderRequest> {
+    let slippage = 3.0 / 100.0;
+
+    deltas
+        .iter()
+        .filter(|delta| delta.skip.is_none())
+        .map(|delta| {
+            let is_buy = delta.usd() > 0.0;
+
+            let (order_type, limit_px) = if limit {
+                (OrderType::Limit(Limit { tif: Tif::Gtc }), delta.mark_px)
+            } else if is_buy {
+                (OrderType::Limit(Limit { tif: Tif::Ioc }), delta.mark_px * (1.0 + slippage))
+            } else {
+                (OrderType::Limit(Limit { tif: Tif::Ioc }), delta.mark_px * (1.0 - slippage))
+            };
+
+            OrderRequest {
+                cloid: None,
+                asset: delta.asset,
+                is_buy,
+                limit_px: parse_price(limit_px),
+                sz: parse_size(delta.usd().abs() / delta.mark_px, delta.sz_decimals),
+                reduce_only: false,
+                order_type,
+            }
+        })
+        .collect()
+}
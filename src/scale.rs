@@ -0,0 +1,118 @@
+use hyperliquid::utils::{parse_price, parse_size};
+
+use crate::types::{Distribution, Weight};
+
+pub struct Rung {
+    pub price: f64,
+    /// Size in the base asset
+    pub sz: f64,
+    /// Size in USD
+    pub usd: f64,
+}
+
+/// Splits `size` (USD) into `count` limit orders between `lower` and `upper`, ordered from the
+/// rung nearest the market (upper for a buy, lower for a sell) to the deepest one. A single rung
+/// is placed in the middle of the range.
+pub fn ladder(
+    is_buy: bool,
+    size: f64,
+    count: u32,
+    lower: f64,
+    upper: f64,
+    distribution: Distribution,
+    weight: &Weight,
+) -> Result<Vec<Rung>, String> {
+    if count == 0 {
+        return Err("Number of intervals must be greater than 0".into());
+    }
+
+    if lower <= 0.0 || upper < lower {
+        return Err("Lower price must be greater than 0 and not above the upper price".into());
+    }
+
+    let weights = match weight {
+        Weight::Flat => vec![1.0; count as usize],
+        Weight::Increasing => (1..=count).map(|i| i as f64).collect(),
+        Weight::Decreasing => (1..=count).rev().map(|i| i as f64).collect(),
+        Weight::Custom(weights) => {
+            if weights.len() != count as usize {
+                return Err(format!(
+                    "Expected {} custom weights, one per interval, got {}",
+                    count,
+                    weights.len()
+                ));
+            }
+
+            weights.clone()
+        }
+    };
+    let total_weight = weights.iter().sum::<f64>();
+
+    let (near, deep) = if is_buy { (upper, lower) } else { (lower, upper) };
+
+    let rungs = weights
+        .iter()
+        .enumerate()
+        .map(|(i, weight)| {
+            let price = if count == 1 {
+                (lower + upper) / 2.0
+            } else {
+                let last = (count - 1) as f64;
+
+                match distribution {
+                    Distribution::Linear => near + (deep - near) * (i as f64 / last),
+                    Distribution::Geometric => near * (deep / near).powf(i as f64 / last),
+                    Distribution::Exponential => {
+                        near + (deep - near) * (2f64.powi(i as i32) - 1.0) / (2f64.powf(last) - 1.0)
+                    }
+                }
+            };
+
+            let usd = size * weight / total_weight;
+
+            Rung {
+                price,
+                sz: usd / price,
+                usd,
+            }
+        })
+        .collect();
+
+    Ok(rungs)
+}
+
+/// Prints each rung's price and size with the average entry if every rung up to it fills
+pub fn preview(rungs: &[Rung], symbol: &str, sz_decimals: u32) {
+    println!("{}", "---".repeat(20));
+    println!(
+        "{:<6}{:>14}{:>16}{:>14}{:>16}",
+        "Rung",
+        "Price",
+        format!("Size in {}", symbol.to_uppercase()),
+        "Size in USD",
+        "Avg Entry"
+    );
+
+    let (mut total_sz, mut total_usd) = (0.0, 0.0);
+
+    for (i, rung) in rungs.iter().enumerate() {
+        total_sz += rung.sz;
+        total_usd += rung.usd;
+
+        println!(
+            "{:<6}{:>14}{:>16}{:>14.2}{:>16}",
+            i + 1,
+            parse_price(rung.price),
+            parse_size(rung.sz, sz_decimals),
+            rung.usd,
+            parse_price(total_usd / total_sz)
+        );
+    }
+
+    println!(
+        "\nTotal: {} {} (${:.2})\n",
+        parse_size(total_sz, sz_decimals),
+        symbol.to_uppercase(),
+        total_usd
+    );
+}
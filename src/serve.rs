@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use ethers::signers::{LocalWallet, Signer};
+use futures::{future::BoxFuture, FutureExt};
+use hyper::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use hyperliquid::{
+    types::{
+        exchange::{
+            request::{CancelRequest, Limit, OrderRequest, OrderType, Tif},
+            response::{Response as ExchangeResponse, Status, StatusType},
+        },
+        info::response::{OpenOrder, UserState},
+        Side,
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::{
+    daemon::{self, DaemonRequest, DaemonResponse, Job},
+    helpers::{asset_ctx, close_order, market_px, place_batch, place_order, position_szi, read_body, status, token_eq},
+    journal::Journal,
+    scale,
+    twap::TwapJob,
+    types::{Distribution, TimeSpan, Weight},
+};
+
+/// An error answered with its status code as `{"error": message}`
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(message: impl ToString) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message.to_string())
+    }
+
+    fn exchange(message: impl ToString) -> Self {
+        ApiError(StatusCode::BAD_GATEWAY, message.to_string())
+    }
+}
+
+type ApiResult = Result<Value, ApiError>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderBody {
+    asset: String,
+    side: String,
+    /// Order size in USD
+    size: f64,
+    /// Resting limit price, the order is placed at market with 3% slippage otherwise
+    price: Option<f64>,
+    #[serde(default)]
+    reduce_only: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScaleBody {
+    asset: String,
+    side: String,
+    /// Total size in USD
+    size: f64,
+    count: u32,
+    lower: f64,
+    upper: f64,
+    distribution: Option<String>,
+    weight: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TwapBody {
+    asset: String,
+    side: String,
+    /// Total size in USD
+    size: f64,
+    /// Time between slices e.g 5m
+    interval: String,
+    count: u32,
+}
+
+/// The exchange calls the API makes for the account it serves, e.g answered by a mock in tests
+pub trait Backend: Send + Sync {
+    fn mark_px<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<f64, anyhow::Error>>;
+
+    fn user_state(&self) -> BoxFuture<'_, Result<UserState, anyhow::Error>>;
+
+    fn open_orders(&self) -> BoxFuture<'_, Result<Vec<OpenOrder>, anyhow::Error>>;
+
+    /// Places orders in one request, returning each order's status
+    fn place_orders<'a>(
+        &'a self,
+        orders: Vec<OrderRequest>,
+        journal: &'a Journal,
+    ) -> BoxFuture<'a, Result<Vec<Status>, anyhow::Error>>;
+
+    /// Places orders all or nothing, see [`place_batch`]
+    fn place_batch<'a>(
+        &'a self,
+        orders: Vec<OrderRequest>,
+        journal: &'a Journal,
+    ) -> BoxFuture<'a, Result<Vec<Status>, anyhow::Error>>;
+
+    fn cancel_orders(&self, cancels: Vec<CancelRequest>) -> BoxFuture<'_, Result<(), anyhow::Error>>;
+}
+
+/// Sends the API's calls to the exchange, signed with the wallet
+pub struct Live {
+    info: Arc<Info>,
+    exchange: Arc<Exchange>,
+    wallet: Arc<LocalWallet>,
+}
+
+impl Live {
+    pub fn new(info: Arc<Info>, exchange: Arc<Exchange>, wallet: Arc<LocalWallet>) -> Self {
+        Self {
+            info,
+            exchange,
+            wallet,
+        }
+    }
+}
+
+impl Backend for Live {
+    fn mark_px<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<f64, anyhow::Error>> {
+        async move {
+            let asset_ctxs = self.info.contexts().await?;
+
+            Ok(asset_ctx(&asset_ctxs, symbol)?
+                .ok_or_else(|| anyhow::anyhow!("Failed to find asset ctx: {}", symbol))?
+                .mark_px
+                .parse::<f64>()?)
+        }
+        .boxed()
+    }
+
+    fn user_state(&self) -> BoxFuture<'_, Result<UserState, anyhow::Error>> {
+        async move { Ok(self.info.user_state(self.wallet.address()).await?) }.boxed()
+    }
+
+    fn open_orders(&self) -> BoxFuture<'_, Result<Vec<OpenOrder>, anyhow::Error>> {
+        async move { Ok(self.info.open_orders(self.wallet.address()).await?) }.boxed()
+    }
+
+    fn place_orders<'a>(
+        &'a self,
+        orders: Vec<OrderRequest>,
+        journal: &'a Journal,
+    ) -> BoxFuture<'a, Result<Vec<Status>, anyhow::Error>> {
+        async move {
+            match place_order(&self.exchange, self.wallet.clone(), orders, journal).await? {
+                ExchangeResponse::Ok(order) => match order.data {
+                    Some(StatusType::Statuses(statuses)) => Ok(statuses),
+                    _ => Err(anyhow::anyhow!("Unexpected response")),
+                },
+                ExchangeResponse::Err(err) => Err(anyhow::anyhow!(err)),
+            }
+        }
+        .boxed()
+    }
+
+    fn place_batch<'a>(
+        &'a self,
+        orders: Vec<OrderRequest>,
+        journal: &'a Journal,
+    ) -> BoxFuture<'a, Result<Vec<Status>, anyhow::Error>> {
+        place_batch(&self.exchange, self.wallet.clone(), orders, journal).boxed()
+    }
+
+    fn cancel_orders(&self, cancels: Vec<CancelRequest>) -> BoxFuture<'_, Result<(), anyhow::Error>> {
+        async move {
+            match self.exchange.cancel_order(self.wallet.clone(), cancels, None).await? {
+                ExchangeResponse::Ok(_) => Ok(()),
+                ExchangeResponse::Err(err) => Err(anyhow::anyhow!(err)),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Serves the CLI operations as a JSON API, every request must carry the bearer token
+pub struct Api {
+    backend: Box<dyn Backend>,
+    assets: HashMap<String, (u32, u32)>,
+    profile: String,
+    token: String,
+}
+
+impl Api {
+    pub fn new(
+        backend: impl Backend + 'static,
+        assets: HashMap<String, (u32, u32)>,
+        profile: String,
+        token: String,
+    ) -> Self {
+        Self {
+            backend: Box::new(backend),
+            assets,
+            profile,
+            token,
+        }
+    }
+
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+
+        let authorized = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| token_eq(token, &self.token));
+
+        let result = if authorized {
+            self.route(&method, &path, request).await
+        } else {
+            Err(ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token".into()))
+        };
+
+        let (status, body) = match result {
+            Ok(body) => (StatusCode::OK, body),
+            Err(ApiError(status, message)) => (status, json!({ "error": message })),
+        };
+
+        println!("{} {} {}", method, path, status.as_u16());
+
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap_or_default()
+    }
+
+    async fn route(&self, method: &Method, path: &str, request: Request<Body>) -> ApiResult {
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+        match (method, segments.as_slice()) {
+            (&Method::GET, ["balances"]) => self.balances().await,
+            (&Method::GET, ["positions"]) => self.positions().await,
+            (&Method::GET, ["orders"]) => self.orders().await,
+            (&Method::POST, ["orders"]) => self.place(body(request).await?, path).await,
+            (&Method::DELETE, ["orders", asset, oid]) => self.cancel(asset, oid, path).await,
+            (&Method::POST, ["positions", asset, "close"]) => self.close(asset, path).await,
+            (&Method::POST, ["scale"]) => self.scale(body(request).await?, path).await,
+            (&Method::GET, ["jobs"]) => jobs().await,
+            (&Method::POST, ["jobs", "twap"]) => self.twap(body(request).await?, path).await,
+            (&Method::POST, ["jobs"]) => submit(body(request).await?, path).await,
+            (&Method::DELETE, ["jobs", id]) => cancel_job(id).await,
+            _ => Err(ApiError(StatusCode::NOT_FOUND, format!("No route for {} {}", method, path))),
+        }
+    }
+
+    /// Opens the journal with the request recorded as the command its orders are tagged with
+    fn journal(&self, path: &str, strategy: &str) -> Result<Journal, ApiError> {
+        let mut journal = Journal::open()
+            .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+        if let Err(err) = journal.record_command(&format!("serve {}", path), &self.profile, strategy) {
+            println!("Failed to journal command: {:#?}", err);
+        }
+
+        Ok(journal)
+    }
+
+    fn asset(&self, symbol: &str) -> Result<(u32, u32), ApiError> {
+        self.assets
+            .get(&symbol.to_uppercase())
+            .copied()
+            .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Failed to find asset: {}", symbol)))
+    }
+
+    async fn mark_px(&self, symbol: &str) -> Result<f64, ApiError> {
+        self.backend.mark_px(symbol).await.map_err(ApiError::exchange)
+    }
+
+    async fn balances(&self) -> ApiResult {
+        let state = self.backend.user_state().await.map_err(ApiError::exchange)?;
+
+        let margin = state.margin_summary;
+        let cross_margin = state.cross_margin_summary;
+
+        Ok(json!({
+            "margin": {
+                "accountValue": margin.account_value,
+                "totalMarginUsed": margin.total_margin_used,
+                "totalNtlPos": margin.total_ntl_pos,
+                "totalRawUsd": margin.total_raw_usd,
+            },
+            "crossMargin": {
+                "accountValue": cross_margin.account_value,
+                "totalMarginUsed": cross_margin.total_margin_used,
+                "totalNtlPos": cross_margin.total_ntl_pos,
+                "totalRawUsd": cross_margin.total_raw_usd,
+            },
+        }))
+    }
+
+    async fn positions(&self) -> ApiResult {
+        let state = self.backend.user_state().await.map_err(ApiError::exchange)?;
+
+        Ok(state
+            .asset_positions
+            .iter()
+            .map(|ap| {
+                json!({
+                    "asset": ap.position.coin,
+                    "size": ap.position.szi,
+                    "entryPx": ap.position.entry_px,
+                    "liquidationPx": ap.position.liquidation_px,
+                    "positionValue": ap.position.position_value,
+                    "unrealizedPnl": ap.position.unrealized_pnl,
+                    "marginUsed": ap.position.margin_used,
+                })
+            })
+            .collect())
+    }
+
+    async fn orders(&self) -> ApiResult {
+        let orders = self.backend.open_orders().await.map_err(ApiError::exchange)?;
+
+        Ok(orders
+            .iter()
+            .map(|order| {
+                json!({
+                    "asset": order.coin,
+                    "oid": order.oid,
+                    "side": if let Side::B = order.side { "buy" } else { "sell" },
+                    "limitPx": order.limit_px,
+                    "size": order.sz,
+                    "timestamp": order.timestamp,
+                })
+            })
+            .collect())
+    }
+
+    async fn place(&self, order: OrderBody, path: &str) -> ApiResult {
+        let is_buy = side(&order.side)?;
+        let (sz_decimals, asset) = self.asset(&order.asset)?;
+
+        if order.size <= 0.0 {
+            return Err(ApiError::bad_request("Size must be greater than 0"));
+        }
+
+        let market_price = self.mark_px(&order.asset).await?;
+
+        let (order_type, limit_price) = match order.price {
+            Some(price) => (OrderType::Limit(Limit { tif: Tif::Gtc }), price),
+            None => (OrderType::Limit(Limit { tif: Tif::Ioc }), market_px(market_price, is_buy)),
+        };
+
+        let request = OrderRequest {
+            cloid: None,
+            asset,
+            is_buy,
+            limit_px: parse_price(limit_price),
+            sz: parse_size(order.size / order.price.unwrap_or(market_price), sz_decimals),
+            reduce_only: order.reduce_only,
+            order_type,
+        };
+
+        let journal = self.journal(path, if is_buy { "buy" } else { "sell" })?;
+
+        let statuses = self
+            .backend
+            .place_orders(vec![request], &journal)
+            .await
+            .map_err(ApiError::exchange)?;
+
+        Ok(statuses.iter().map(status).collect())
+    }
+
+    async fn cancel(&self, symbol: &str, oid: &str, path: &str) -> ApiResult {
+        let (_, asset) = self.asset(symbol)?;
+        let oid = oid
+            .parse::<u64>()
+            .map_err(|_| ApiError::bad_request(format!("Invalid order id: {}", oid)))?;
+
+        self.journal(path, "cancel")?;
+
+        self.backend
+            .cancel_orders(vec![CancelRequest { asset, oid }])
+            .await
+            .map_err(ApiError::exchange)?;
+
+        Ok(json!({ "cancelled": oid }))
+    }
+
+    /// Closes the whole position in an asset at market with a reduce-only order
+    async fn close(&self, symbol: &str, path: &str) -> ApiResult {
+        let (sz_decimals, asset) = self.asset(symbol)?;
+
+        let state = self.backend.user_state().await.map_err(ApiError::exchange)?;
+
+        let szi = position_szi(&state, symbol).map_err(ApiError::exchange)?;
+
+        if szi == 0.0 {
+            return Err(ApiError(StatusCode::NOT_FOUND, format!("No open position in {}", symbol)));
+        }
+
+        let market_price = self.mark_px(symbol).await?;
+        let request = close_order(asset, sz_decimals, szi, 1.0, market_price);
+
+        let journal = self.journal(path, "close")?;
+
+        let statuses = self
+            .backend
+            .place_orders(vec![request], &journal)
+            .await
+            .map_err(ApiError::exchange)?;
+
+        Ok(statuses.iter().map(status).collect())
+    }
+
+    async fn scale(&self, body: ScaleBody, path: &str) -> ApiResult {
+        let is_buy = side(&body.side)?;
+        let (sz_decimals, asset) = self.asset(&body.asset)?;
+
+        let distribution: Distribution = body
+            .distribution
+            .as_deref()
+            .unwrap_or("linear")
+            .try_into()
+            .map_err(ApiError::bad_request)?;
+
+        let weight: Weight = body
+            .weight
+            .as_deref()
+            .unwrap_or("flat")
+            .try_into()
+            .map_err(ApiError::bad_request)?;
+
+        let rungs = scale::ladder(is_buy, body.size, body.count, body.lower, body.upper, distribution, &weight)
+            .map_err(ApiError::bad_request)?;
+
+        let orders = rungs
+            .iter()
+            .map(|rung| OrderRequest {
+                cloid: None,
+                asset,
+                is_buy,
+                limit_px: parse_price(rung.price),
+                sz: parse_size(rung.sz, sz_decimals),
+                reduce_only: false,
+                order_type: OrderType::Limit(Limit { tif: Tif::Gtc }),
+            })
+            .collect::<Vec<_>>();
+
+        let journal = self.journal(path, "scale")?;
+
+        let statuses = self
+            .backend
+            .place_batch(orders, &journal)
+            .await
+            .map_err(ApiError::exchange)?;
+
+        Ok(statuses.iter().map(status).collect())
+    }
+
+    /// Saves a twap job and submits it to the daemon
+    async fn twap(&self, body: TwapBody, path: &str) -> ApiResult {
+        let is_buy = side(&body.side)?;
+        self.asset(&body.asset)?;
+
+        let TimeSpan(interval) = body.interval.as_str().try_into().map_err(ApiError::bad_request)?;
+
+        if interval.is_zero() || body.count == 0 || body.size <= 0.0 {
+            return Err(ApiError::bad_request("Size, interval and count must be greater than 0"));
+        }
+
+        let job = TwapJob::new(&body.asset, is_buy, body.size, interval, body.count)
+            .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+        let id = daemon::submit(&format!("serve {}", path), Job::Twap { id: job.id })
+            .await
+            .map_err(daemon_error)?;
+
+        Ok(json!({ "id": id, "twapId": job.id }))
+    }
+}
+
+fn side(side: &str) -> Result<bool, ApiError> {
+    match side.to_lowercase().as_str() {
+        "buy" => Ok(true),
+        "sell" => Ok(false),
+        _ => Err(ApiError::bad_request(format!("Invalid side {}, expected buy or sell", side))),
+    }
+}
+
+async fn body<T: DeserializeOwned>(request: Request<Body>) -> Result<T, ApiError> {
+    let bytes = read_body(request.into_body())
+        .await
+        .map_err(|(status, message)| ApiError(status, message))?;
+
+    serde_json::from_slice(&bytes).map_err(|err| ApiError::bad_request(format!("Invalid body: {}", err)))
+}
+
+/// The daemon hosts long-running jobs, it being down is reported as unavailable
+fn daemon_error(err: anyhow::Error) -> ApiError {
+    ApiError(StatusCode::SERVICE_UNAVAILABLE, err.to_string())
+}
+
+async fn jobs() -> ApiResult {
+    match daemon::request(&DaemonRequest::Jobs).await.map_err(daemon_error)? {
+        DaemonResponse::Jobs { jobs } => {
+            serde_json::to_value(jobs).map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))
+        }
+        DaemonResponse::Error { message } => Err(ApiError::bad_request(message)),
+        _ => Err(daemon_error(anyhow::anyhow!("Unexpected daemon response"))),
+    }
+}
+
+/// Submits a job as the daemon takes it e.g `{"kind": "pair", ...}`
+async fn submit(job: Job, path: &str) -> ApiResult {
+    let id = daemon::submit(&format!("serve {}", path), job)
+        .await
+        .map_err(daemon_error)?;
+
+    Ok(json!({ "id": id }))
+}
+
+async fn cancel_job(id: &str) -> ApiResult {
+    let id = id
+        .parse::<u64>()
+        .map_err(|_| ApiError::bad_request(format!("Invalid job id: {}", id)))?;
+
+    match daemon::request(&DaemonRequest::Cancel { id }).await.map_err(daemon_error)? {
+        DaemonResponse::Cancelled { id } => Ok(json!({ "cancelled": id })),
+        DaemonResponse::Error { message } => Err(ApiError(StatusCode::NOT_FOUND, message)),
+        _ => Err(daemon_error(anyhow::anyhow!("Unexpected daemon response"))),
+    }
+}
+
+/// Serves the API on `bind` until ctrl-c
+pub async fn serve(api: Api, bind: SocketAddr) -> Result<(), anyhow::Error> {
+    let api = Arc::new(api);
+
+    let make_service = make_service_fn(move |_| {
+        let api = api.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let api = api.clone();
+
+                async move { Ok::<_, Infallible>(api.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&bind)?.serve(make_service);
+
+    println!("Serving the API on http://{}\n---", bind);
+
+    server
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
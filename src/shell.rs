@@ -0,0 +1,338 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clap::Command;
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::Address,
+};
+use home::home_dir;
+use hyperliquid::Info;
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::FileHistory,
+    validate::Validator,
+    Context, Editor, Helper,
+};
+use tokio::task::{spawn_blocking, spawn_local, LocalSet};
+
+use crate::{
+    batch,
+    command::command,
+    startup::{redact, run, secrets, Session},
+    types::{Config, MarginType},
+};
+
+/// Seconds between refreshes of the account value shown in the prompt
+const STATUS_INTERVAL: u64 = 5;
+
+/// Completes subcommand names, and asset names once past them
+struct ShellHelper {
+    command: Command,
+    assets: Vec<String>,
+}
+
+impl ShellHelper {
+    fn new(session: &Session) -> Self {
+        let mut assets = session
+            .assets
+            .keys()
+            .map(|asset| asset.to_lowercase())
+            .collect::<Vec<_>>();
+        assets.sort();
+
+        Self {
+            command: command(),
+            assets,
+        }
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+
+        // conditions and legs put assets after e.g `(`, `,` or `:` rather than a space
+        let start = line
+            .rfind(|c: char| c.is_whitespace() || "(,:/".contains(c))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let word = line[start..].to_lowercase();
+
+        let mut current = &self.command;
+        let mut at_subcommand = true;
+        for name in line[..start].split_whitespace() {
+            match current.find_subcommand(name) {
+                Some(subcommand) => current = subcommand,
+                None => {
+                    at_subcommand = false;
+                    break;
+                }
+            }
+        }
+
+        let candidates = if at_subcommand && current.has_subcommands() {
+            current
+                .get_subcommands()
+                .map(|subcommand| subcommand.get_name().to_string())
+                .chain(line[..start].trim().is_empty().then(|| "exit".to_string()))
+                .collect::<Vec<_>>()
+        } else {
+            self.assets.clone()
+        };
+
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(&word))
+                .map(|candidate| Pair {
+                    display: candidate.clone(),
+                    replacement: candidate,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// What the prompt shows, the account value is refreshed in the background
+struct Status {
+    info: Arc<Info>,
+    profile: String,
+    address: Option<Address>,
+    cross: bool,
+    account_value: Option<f64>,
+}
+
+impl Status {
+    fn new(config: &Config, session: &Session) -> Self {
+        let mut status = Self {
+            info: session.info.clone(),
+            profile: String::new(),
+            address: None,
+            cross: true,
+            account_value: None,
+        };
+        status.update(config, session);
+
+        status
+    }
+
+    /// Picks up a new wallet, chain or margin type after a command changed the config
+    fn update(&mut self, config: &Config, session: &Session) {
+        let address = config
+            .private_key
+            .parse::<LocalWallet>()
+            .ok()
+            .map(|wallet| wallet.address());
+
+        if address != self.address {
+            self.account_value = None;
+        }
+
+        self.info = session.info.clone();
+        self.profile = serde_json::to_string(&config.chain)
+            .unwrap_or_default()
+            .trim_matches('"')
+            .to_string();
+        self.address = address;
+        self.cross = matches!(config.default_margin, MarginType::Cross);
+    }
+
+    fn prompt(&self) -> String {
+        match self.account_value {
+            Some(account_value) => format!("hl {} ${:.2} > ", self.profile, account_value),
+            None => format!("hl {} > ", self.profile),
+        }
+    }
+}
+
+async fn refresh(status: Arc<Mutex<Status>>) {
+    loop {
+        let (info, address, cross) = {
+            let status = status.lock().expect("Status lock poisoned");
+            (status.info.clone(), status.address, status.cross)
+        };
+
+        if let Some(address) = address {
+            if let Ok(state) = info.user_state(address).await {
+                let account_value = if cross {
+                    state.cross_margin_summary.account_value
+                } else {
+                    state.margin_summary.account_value
+                };
+
+                let mut status = status.lock().expect("Status lock poisoned");
+                if status.address == Some(address) {
+                    status.account_value = account_value.parse::<f64>().ok();
+                }
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(STATUS_INTERVAL)).await;
+    }
+}
+
+/// Reads commands until exit, running each against the same session. A command that panics or
+/// is interrupted with ctrl-c only ends itself, not the shell.
+pub async fn start(config: &mut Config, session: Session) {
+    LocalSet::new().run_until(repl(config, session)).await
+}
+
+async fn repl(config: &mut Config, mut session: Session) {
+    let history = home_dir().map(|home| home.join(".hyperliquid").join("shell_history"));
+
+    let mut editor = match Editor::<ShellHelper, FileHistory>::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            println!("Failed to open the shell: {:#?}", err);
+            return;
+        }
+    };
+    editor.set_helper(Some(ShellHelper::new(&session)));
+
+    if let Some(history) = history.as_ref() {
+        // there's no history before the first session
+        let _ = editor.load_history(history);
+    }
+
+    let status = Arc::new(Mutex::new(Status::new(config, &session)));
+    let refresher = spawn_local(refresh(status.clone()));
+
+    println!("Type commands without the leading hl, tab completes commands and assets, help lists them and exit leaves\n---");
+
+    loop {
+        let prompt = status.lock().expect("Status lock poisoned").prompt();
+
+        let (returned, line) = match spawn_blocking(move || {
+            let line = editor.readline(&prompt);
+            (editor, line)
+        })
+        .await
+        {
+            Ok(read) => read,
+            Err(err) => {
+                println!("Failed to read the command: {:#?}", err);
+                refresher.abort();
+                return;
+            }
+        };
+        editor = returned;
+
+        let line = match line {
+            Ok(line) => line.trim().to_string(),
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                println!("Failed to read the command: {:#?}", err);
+                break;
+            }
+        };
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line == "exit" || line == "quit" {
+            break;
+        }
+
+        let words = match shlex::split(&line) {
+            Some(words) => words,
+            None => {
+                println!("Invalid command: unbalanced quotes");
+                continue;
+            }
+        };
+        let words = std::iter::once("hl".to_string()).chain(words).collect::<Vec<_>>();
+
+        let parsed = command().try_get_matches_from(&words);
+
+        // a private key, token or secret never goes to the history file
+        let has_secret = match parsed.as_ref() {
+            Ok(matches) => !secrets(matches).is_empty(),
+            Err(_) => words.iter().skip(1).any(|word| {
+                word == "login" || word.starts_with("--token") || word.starts_with("--secret")
+            }),
+        };
+
+        if !has_secret {
+            if let Err(err) = editor.add_history_entry(line.as_str()) {
+                println!("Failed to add the command to the history: {:#?}", err);
+            }
+        }
+
+        let matches = match parsed {
+            Ok(matches) => matches,
+            Err(err) => {
+                let _ = err.print();
+                continue;
+            }
+        };
+
+        let chain_changed = matches.subcommand_name() == Some("chain");
+
+        // the command runs on its own task so a panic or ctrl-c ends only the command
+        let mut command_config = config.clone();
+        let command_session = session.clone();
+        let command_line = redact(&words, &matches);
+
+        let mut task = spawn_local(async move {
+            match matches.subcommand() {
+                Some(("run", run_matches)) => {
+                    batch::execute(&mut command_config, &command_session, run_matches).await
+                }
+                _ => {
+                    run(&mut command_config, &command_session, matches, &command_line, false).await;
+                }
+            }
+            command_config
+        });
+
+        tokio::select! {
+            result = &mut task => match result {
+                Ok(updated) => *config = updated,
+                Err(_) => println!("---\nCommand failed"),
+            },
+            _ = tokio::signal::ctrl_c() => {
+                task.abort();
+                println!("\n---\nCommand interrupted");
+            }
+        }
+
+        if chain_changed {
+            match Session::new(config.chain).await {
+                Ok(new_session) => {
+                    session = new_session;
+                    editor.set_helper(Some(ShellHelper::new(&session)));
+                }
+                Err(err) => println!("Failed to switch chain: {:#?}", err),
+            }
+        }
+
+        status.lock().expect("Status lock poisoned").update(config, &session);
+    }
+
+    refresher.abort();
+
+    if let Some(history) = history.as_ref() {
+        if let Err(err) = editor.save_history(history) {
+            println!("Failed to save the shell history: {:#?}", err);
+        }
+    }
+}
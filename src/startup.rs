@@ -1,32 +1,154 @@
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
+use clap::ArgMatches;
 use ethers::signers::{LocalWallet, Signer};
 use hyperliquid::{types::{exchange::{request::{ Limit, OrderRequest, OrderType, Tif, TpSl, Trigger }, response::{Response, Status, StatusType}}, Chain, Side}, utils::{parse_price, parse_size}, Exchange, Hyperliquid, Info};
 
-use crate::{command::command, types::{OrderSize, TpSl as TPSL, LimitPrice, MarginType, SzPerInterval, TwapInterval, Pair, Config}, helpers::asset_ctx};
+use crate::{command::command, types::{OrderSize, TpSl as TPSL, LimitPrice, MarginType, SzPerInterval, TwapInterval, TimeSpan, TimeOfDay, MissedRuns, Pair, Percent, Distribution, Weight, Targets, Hedge, Spread, Recovery, BasketLegs, Sink, Config}, helpers::{asset_ctx, place_batch, place_order}, chase::{self, Chase}, ladder::{self, TpLadder}, scale, journal::Journal, market::MarketFeed, pair::{self, PairJob, PairPosition, PairStatus}, basket::{self, BasketJob, BasketTrigger}, pov::{self, PovOrder}, iceberg::{self, IcebergJob, IcebergStatus}, grid::{self, GridJob, GridStatus, SimulatedExchange, Venue}, dca::{self, DcaJob}, alert::{self, Alert}, rebalance, serve::{self, Api}, webhook::{self, Receiver}, shell, batch, strategy::{self, Strategy}, daemon::{self, DaemonRequest, DaemonResponse, Job}, twap::{self, TwapJob, TwapStatus}, when::{self, Condition, WhenJob}};
 
 
+/// Clients, asset metadata and market data shared by every command of a run, or of a whole shell
+#[derive(Clone)]
+pub struct Session {
+    pub info: Arc<Info>,
+    pub exchange: Arc<Exchange>,
+    pub assets: HashMap<String, (u32, u32)>,
+    pub feed: MarketFeed,
+}
+
+impl Session {
+    pub async fn new(chain: Chain) -> Result<Self, anyhow::Error> {
+        let info: Arc<Info> = Arc::new(Hyperliquid::new(chain));
+        let exchange: Arc<Exchange> = Arc::new(Hyperliquid::new(chain));
+
+        let metadata = info.metadata().await?;
+
+        let assets = metadata
+            .universe
+            .into_iter()
+            .enumerate()
+            .map(|(i, asset)| (asset.name.to_uppercase(), (asset.sz_decimals as u32, i as u32)))
+            .collect::<HashMap<String, (u32, u32)>>();
+
+        let feed = MarketFeed::new(info.clone(), Duration::from_secs(1));
+
+        Ok(Self {
+            info,
+            exchange,
+            assets,
+            feed,
+        })
+    }
+}
+
 pub async fn startup(config: &mut Config) {
+    let session = Session::new(config.chain).await.expect("Failed to fetch metadata");
+
+    let matches = command().get_matches();
+
+    match matches.subcommand() {
+        Some(("shell", _)) => {
+            shell::start(config, session).await;
+            return;
+        }
+        Some(("run", matches)) => {
+            batch::execute(config, &session, matches).await;
+            return;
+        }
+        _ => {}
+    }
+
+    let command_line = redact(&std::env::args().collect::<Vec<_>>(), &matches);
+
+    run(config, &session, matches, &command_line, false).await;
+}
+
+/// Arguments whose values never make it to the journal or the shell history
+const SECRET_ARGS: [&str; 3] = ["private_key", "token", "secret"];
+
+/// Values given to secret arguments anywhere in the command
+pub fn secrets(matches: &ArgMatches) -> Vec<String> {
+    let mut secrets = vec![];
+    let mut current = Some(matches);
+
+    while let Some(matches) = current {
+        for id in SECRET_ARGS {
+            if let Ok(Some(value)) = matches.try_get_one::<String>(id) {
+                secrets.push(value.clone());
+            }
+        }
+        current = matches.subcommand().map(|(_, sub_matches)| sub_matches);
+    }
 
+    secrets
+}
 
+/// Joins the words of a command with the values of its secret arguments, e.g `--token abc` or
+/// `--secret=abc`, replaced by `<redacted>`
+pub fn redact(words: &[String], matches: &ArgMatches) -> String {
+    let secrets = secrets(matches);
+
+    words
+        .iter()
+        .map(|word| {
+            let secret = secrets.iter().find(|secret| {
+                *word == **secret
+                    || word
+                        .strip_suffix(secret.as_str())
+                        .is_some_and(|flag| flag.starts_with('-') && flag.ends_with('='))
+            });
 
-    let chain = config.chain;
+            match secret {
+                Some(secret) => format!("{}<redacted>", &word[..word.len() - secret.len()]),
+                None => word.clone(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    let info: Info = Hyperliquid::new(chain);
-    let exchange: Exchange = Hyperliquid::new(chain);
+/// Runs one parsed command against the session's clients, returning false when it failed. With
+/// `preview` the order commands print the orders they resolve to and stop before placing them
+pub async fn run(config: &mut Config, session: &Session, matches: ArgMatches, command_line: &str, preview: bool) -> bool {
+    let Session {
+        info,
+        exchange,
+        assets,
+        feed,
+    } = session.clone();
+
+    let mut journal = match Journal::open() {
+        Ok(journal) => journal,
+        Err(err) => {
+            println!("Warning: failed to open trade journal, orders of this command won't be journaled: {:#?}", err);
+            Journal::in_memory().expect("Failed to open an in-memory journal")
+        }
+    };
 
-    let metadata = info.metadata().await.expect("Failed to fetch metadata");
+    let profile = serde_json::to_string(&config.chain)
+        .unwrap_or_default()
+        .trim_matches('"')
+        .to_string();
+
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        // login is never journaled, it carries the private key, and a preview places nothing
+        if !preview && name != "journal" && name != "daemon" && name != "login" {
+            let strategy = match sub_matches.subcommand_name() {
+                Some(sub_name) => format!("{} {}", name, sub_name),
+                None => name.to_string(),
+            };
 
-    let assets = metadata
-        .universe
-        .into_iter()
-        .enumerate()
-        .map(|(i, asset)| (asset.name.to_uppercase(), (asset.sz_decimals as u32, i as u32)))
-        .collect::<HashMap<String, (u32, u32)>>();
+            if let Err(err) = journal.record_command(command_line, &profile, &strategy) {
+                println!("Failed to journal command: {:#?}", err);
+            }
+        }
+    }
 
-    match command().get_matches().subcommand() {
+    match matches.subcommand() {
         Some(("login", matches)) => {
             let private_key = matches
                 .get_one::<String>("private_key")
@@ -36,7 +158,7 @@ pub async fn startup(config: &mut Config) {
                 Ok(wallet) => wallet,
                 Err(_) => {
                     println!("Error: Invalid private key");
-                    return;
+                    return false;
                 }
             };
 
@@ -46,7 +168,10 @@ pub async fn startup(config: &mut Config) {
 
             match config.save() {
                 Ok(_) => println!("Wallet successfully saved ✔️\n---"),
-                Err(err) => println!("Failed to save wallet: {:#?}", err),
+                Err(err) => {
+                    println!("Failed to save wallet: {:#?}", err);
+                    return false;
+                }
             }
         }
 
@@ -61,7 +186,7 @@ pub async fn startup(config: &mut Config) {
                 "arbitrum" => Chain::Arbitrum,
                 _ => {
                     println!("Invalid chain, expected 'arbitrum testnet', 'arbitrum-goerli' or 'arbitrum'");
-                    return;
+                    return false;
                 }
             };
 
@@ -70,7 +195,10 @@ pub async fn startup(config: &mut Config) {
 
             match config.save() {
                 Ok(_) => println!("Chain successfully saved ✔️\n---"),
-                Err(err) => println!("Failed to save chain: {:#?}", err),
+                Err(err) => {
+                    println!("Failed to save chain: {:#?}", err);
+                    return false;
+                }
             }
         }
 
@@ -83,7 +211,7 @@ pub async fn startup(config: &mut Config) {
                             Ok(wallet) => wallet,
                             Err(_) => {
                                 println!("Error: Invalid private key");
-                                return;
+                                return false;
                             }
                         }
                 );
@@ -97,7 +225,7 @@ pub async fn startup(config: &mut Config) {
                 // ensure leverage is between 1 and 100
                 if !(1..=100).contains(&leverage) {
                     println!("Leverage must be between 1 and 100");
-                    return;
+                    return false;
                 }
 
                 // loop through all assets and update leverage
@@ -114,7 +242,10 @@ pub async fn startup(config: &mut Config) {
                     match  exchange.update_leverage(wallet.clone(),leverage, v.1, is_cross).await {
                         Ok(_) => {
                         }
-                        Err(err) => println!("Failed to update leverage: {:#?}", err),
+                        Err(err) => {
+                            println!("Failed to update leverage: {:#?}", err);
+                            return false;
+                        }
                     }
                     
                 }
@@ -131,7 +262,7 @@ pub async fn startup(config: &mut Config) {
                     Ok(sz) => sz,
                     Err(err) => {
                         println!("Failed to parse order size: {:#?}", err);
-                        return;
+                        return false;
                     }
                 };
 
@@ -146,7 +277,10 @@ pub async fn startup(config: &mut Config) {
                 config.default_size = sz;
                 match config.save() {
                     Ok(_) => println!("Successfully updated default size ✔️\n---"),
-                    Err(err) => println!("Failed to update default size: {:#?}", err),
+                    Err(err) => {
+                        println!("Failed to update default size: {:#?}", err);
+                        return false;
+                    }
                 }
             }
 
@@ -160,7 +294,7 @@ pub async fn startup(config: &mut Config) {
                     "i" => MarginType::Isolated,
                     _ => {
                         println!("Invalid margin type, expected 'c' or 'i'");
-                        return;
+                        return false;
                     }
                 };
 
@@ -174,7 +308,10 @@ pub async fn startup(config: &mut Config) {
 
                 match config.save() {
                     Ok(_) => println!("Successfully updated default asset ✔️\n---"),
-                    Err(err) => println!("Failed to update default asset: {:#?}", err),
+                    Err(err) => {
+                        println!("Failed to update default asset: {:#?}", err);
+                        return false;
+                    }
                 }
 
             }
@@ -190,7 +327,10 @@ pub async fn startup(config: &mut Config) {
 
                 match config.save() {
                     Ok(_) => println!("Successfully updated default asset ✔️\n---"),
-                    Err(err) => println!("Failed to update default asset: {:#?}", err),
+                    Err(err) => {
+                        println!("Failed to update default asset: {:#?}", err);
+                        return false;
+                    }
                 }
 
                 
@@ -198,10 +338,129 @@ pub async fn startup(config: &mut Config) {
             }
             _ => {
                 println!("Invalid command");
-                return;
+                return false;
             }
 
         },
+        Some(("tp", matches)) if matches.subcommand_name() == Some("ladder") => {
+            let matches = matches
+                .subcommand_matches("ladder")
+                .expect("Ladder subcommand is matched above");
+
+            let symbol = matches
+                .get_one::<String>("asset")
+                .expect("Asset is required");
+
+            let sz: OrderSize = matches
+                .get_one::<String>("size")
+                .expect("Order size is required")
+                .as_str()
+                .try_into()
+                .expect("Failed to parse order size");
+
+            let from: TPSL = matches
+                .get_one::<String>("from")
+                .expect("From price is required")
+                .as_str()
+                .try_into()
+                .expect("Invalid from price, valid values e.g 10% | +10 | 1900");
+
+            let to: TPSL = matches
+                .get_one::<String>("to")
+                .expect("To price is required")
+                .as_str()
+                .try_into()
+                .expect("Invalid to price, valid values e.g 10% | +10 | 1900");
+
+            let rungs = matches
+                .get_one::<String>("rungs")
+                .expect("Number of rungs is required")
+                .parse::<u32>()
+                .expect("Invalid number of rungs, expected a whole number");
+
+            let share = match sz {
+                OrderSize::Percent(sz) if sz > 0 && sz <= 100 => sz as f64 / 100.0,
+                _ => {
+                    println!("{}", "-".repeat(35));
+
+                    println!("\nOnly a % of the position between 1% and 100% is supported");
+                    return false;
+                }
+            };
+
+            if rungs == 0 {
+                println!("Number of rungs must be greater than 0");
+                return false;
+            }
+
+            let wallet = Arc::new(
+                match config
+                    .private_key
+                    .parse::<LocalWallet>() {
+                        Ok(wallet) => wallet,
+                        Err(_) => {
+                            println!("Error: Invalid private key");
+                            return false;
+                        }
+                    }
+            );
+
+            let (_, entry_price, is_long) = match ladder::position(&info, &wallet, symbol).await {
+                Ok(Some(position)) => position,
+                Ok(None) => {
+                    println!("{}", "-".repeat(35));
+
+                    println!("\nNo open order for {}", symbol);
+                    return false;
+                }
+                Err(err) => {
+                    println!("Failed to fetch open positions: {:#?}", err);
+                    return false;
+                }
+            };
+
+            let price = |tp: TPSL| match tp {
+                TPSL::Absolute(value) => entry_price + if is_long { value } else { -value },
+                TPSL::Percent(value) => {
+                    entry_price
+                        * if is_long {
+                            (100.0 + value) / 100.0
+                        } else {
+                            (100.0 - value) / 100.0
+                        }
+                }
+                TPSL::Fixed(value) => value,
+            };
+
+            let (sz_decimals, asset) = *assets
+                .get(&symbol.to_uppercase())
+                .expect("Failed to find asset");
+
+            let tp_ladder = TpLadder {
+                symbol: symbol.to_uppercase(),
+                asset,
+                sz_decimals,
+                share,
+                prices: ladder::prices(price(from), price(to), rungs),
+                trigger: matches.get_flag("trigger"),
+            };
+
+            println!("Entry price: {}", entry_price);
+
+            if preview {
+                let prices = tp_ladder.prices.iter().map(|price| parse_price(*price)).collect::<Vec<_>>();
+                println!("Rung prices: {}", prices.join(", "));
+                return true;
+            }
+
+            if let Err(err) = tp_ladder
+                .run(&info, &exchange, wallet, matches.get_flag("watch"), &journal)
+                .await
+            {
+                println!("{:#?}", err);
+                return false;
+            }
+        }
         Some(("tp", matches)) => {
             let sz: OrderSize = matches
                 .get_one::<String>("size")
@@ -230,7 +489,7 @@ pub async fn startup(config: &mut Config) {
                         Ok(wallet) => wallet,
                         Err(_) => {
                             println!("Error: Invalid private key");
-                            return;
+                            return false;
                         }
                     }
             );
@@ -254,7 +513,7 @@ pub async fn startup(config: &mut Config) {
                             println!("{}", "-".repeat(35));
 
                             println!("\nNo open order for {}", symbol);
-                            return;
+                            return false;
                         }
                     };
 
@@ -284,7 +543,7 @@ pub async fn startup(config: &mut Config) {
                     println!("{}", "-".repeat(35));
 
                     println!("\nOnly % of order to tp is supported for now");
-                    return;
+                    return false;
                 }
             };
 
@@ -330,11 +589,15 @@ pub async fn startup(config: &mut Config) {
             );
             println!("Entry price: {}", entry_price);
 
-            match exchange.place_order(wallet.clone(), vec![order], None).await {
+            if preview {
+                return true;
+            }
+
+            match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
                 Ok(order) => match order {
                     Response::Err(err) => {
                         println!("{:#?}", err);
-                        return;
+                        return false;
                     
                     }
                     
@@ -367,7 +630,7 @@ pub async fn startup(config: &mut Config) {
                 }},
                 Err(err) => {
                     println!("{:#?}", err);
-                    return;
+                    return false;
                 }
             }
         }
@@ -397,7 +660,7 @@ pub async fn startup(config: &mut Config) {
                             Ok(wallet) => wallet,
                             Err(_) => {
                                 println!("Error: Invalid private key");
-                                return;
+                                return false;
                             }
                         }
                 );
@@ -419,7 +682,7 @@ pub async fn startup(config: &mut Config) {
                         None => {
                             println!("{}", "-".repeat(35));
                             println!("No open position found for {}", symbol);
-                            return;
+                            return false;
                         }
                     };
 
@@ -447,7 +710,7 @@ pub async fn startup(config: &mut Config) {
                     println!("{}", "-".repeat(35));
 
                     println!("\nOnly % of order to tp is supported for now");
-                    return;
+                    return false;
                 }
             };
 
@@ -494,11 +757,15 @@ pub async fn startup(config: &mut Config) {
             );
             println!("Entry price: {}", entry_price);
 
-            match exchange.place_order(wallet.clone(), vec![order], None).await {
+            if preview {
+                return true;
+            }
+
+            match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
                 Ok(order) => match order {
                     Response::Err(err) => {
                         println!("{:#?}", err);
-                        return;
+                        return false;
                     
                     }
                     
@@ -529,7 +796,7 @@ pub async fn startup(config: &mut Config) {
                 }},
                 Err(err) => {
                     println!("{:#?}", err);
-                    return;
+                    return false;
                 }
             }
         }
@@ -565,6 +832,32 @@ pub async fn startup(config: &mut Config) {
                 )
             });
 
+            let chase = matches.get_flag("chase").then(|| {
+                let max_reprices = matches
+                    .get_one::<String>("max_reprices")
+                    .expect("Max reprices has a default")
+                    .parse::<u32>()
+                    .expect("Invalid max reprices, expected a whole number");
+
+                let Percent(max_distance) = matches
+                    .get_one::<String>("max_distance")
+                    .expect("Max distance has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid max distance, expected a percentage e.g 0.5%");
+
+                Chase {
+                    max_reprices,
+                    max_distance,
+                    fallback_ioc: matches.get_flag("fallback_ioc"),
+                }
+            });
+
+            if chase.is_some() && matches.contains_id("price") {
+                println!("--chase places its own post-only price and can't be combined with --price");
+                return false;
+            }
+
             let wallet = Arc::new(
                 match config
                     .private_key
@@ -572,7 +865,7 @@ pub async fn startup(config: &mut Config) {
                         Ok(wallet) => wallet,
                         Err(_) => {
                             println!("Error: Invalid private key");
-                            return;
+                            return false;
                         }
                     }
             );
@@ -656,38 +949,88 @@ pub async fn startup(config: &mut Config) {
             );
             println!("Market price: {}\n", market_price);
 
-            match exchange.place_order(wallet.clone(), vec![ order], None).await {
-                Ok(order) => match order {
-                    Response::Err(err) => {
+            if preview {
+                println!("Limit price: {}", order.limit_px);
+                return true;
+            }
+
+            let (limit_price, sz) = if let Some(chase) = &chase {
+                let chased = match chase::run(
+                    chase,
+                    &info,
+                    &exchange,
+                    wallet.clone(),
+                    symbol,
+                    (sz_decimals, asset),
+                    true,
+                    sz,
+                    &journal,
+                )
+                .await
+                {
+                    Ok(chased) => chased,
+                    Err(err) => {
                         println!("{:#?}", err);
-                        return;
-                    
+                        return false;
                     }
-                    
-                    Response::Ok(order) => {
-                        match order.data.expect("expected order response data") {
-                            StatusType::Statuses(statuses) => {
-                                statuses.iter().for_each(|status| match status {
-                            Status::Filled(order) => {
-                                println!("Order {} was successfully filled.\n", order.oid);
-                            }
-                            Status::Resting(order) => {
-                                println!("Order {} was successfully placed.\n", order.oid);
-                            }
-                            Status::Error(msg) => {
-                                println!("Order failed with error: {:#?}\n", msg)
-                            }
-                            _ =>  unreachable!(),
-                        });
-                    },
-                    _ =>  unreachable!(),
+                };
+
+                println!("Filled {} {} (${:.2})", chased.filled_sz, symbol, chased.filled_usd);
+                if let Some((oid, px)) = chased.resting {
+                    println!("Order {} left resting at {}", oid, px);
                 }
-                }},
-                Err(err) => {
-                    println!("{:#?}", err);
-                    return;
+                println!();
+
+                let entry_px = match chased.entry_px() {
+                    Some(entry_px) => entry_px,
+                    None => {
+                        if tp.is_some() || sl.is_some() {
+                            println!("Nothing was filled or left resting, no take profit or stop loss was placed");
+                        }
+                        return false;
+                    }
+                };
+
+                // an order left resting may still fill in full, otherwise only the fills are covered
+                let sz = if chased.resting.is_some() { sz } else { chased.filled_sz };
+
+                (entry_px, sz)
+            } else {
+                match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
+                    Ok(order) => match order {
+                        Response::Err(err) => {
+                            println!("{:#?}", err);
+                            return false;
+
+                        }
+
+                        Response::Ok(order) => {
+                            match order.data.expect("expected order response data") {
+                                StatusType::Statuses(statuses) => {
+                                    statuses.iter().for_each(|status| match status {
+                                Status::Filled(order) => {
+                                    println!("Order {} was successfully filled.\n", order.oid);
+                                }
+                                Status::Resting(order) => {
+                                    println!("Order {} was successfully placed.\n", order.oid);
+                                }
+                                Status::Error(msg) => {
+                                    println!("Order failed with error: {:#?}\n", msg)
+                                }
+                                _ =>  unreachable!(),
+                            });
+                        },
+                        _ =>  unreachable!(),
+                    }
+                    }},
+                    Err(err) => {
+                        println!("{:#?}", err);
+                        return false;
+                    }
                 }
-            }
+
+                (limit_price, sz)
+            };
 
             // tp
             if tp.is_some() {
@@ -726,11 +1069,11 @@ pub async fn startup(config: &mut Config) {
                 println!("Entry price: {}", order.limit_px);
                 println!("Market price: {}\n", market_price);
 
-                match exchange.place_order(wallet.clone(), vec![order], None).await {
+                match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
                     Ok(order) => match order {
                         Response::Err(err) => {
                             println!("{:#?}", err);
-                            return;
+                            return false;
                         
                         }
                         
@@ -761,7 +1104,7 @@ pub async fn startup(config: &mut Config) {
                     },
                     Err(err) => {
                         println!("{:#?}", err);
-                        return;
+                        return false;
                     }
                 }
             }
@@ -802,11 +1145,11 @@ pub async fn startup(config: &mut Config) {
                 println!("Entry price: {}", order.limit_px);
                 println!("Market price: {}\n", market_price);
 
-                match exchange.place_order(wallet.clone(), vec![order], None).await {
+                match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
                     Ok(order) => match order {
                         Response::Err(err) => {
                             println!("{:#?}", err);
-                            return;
+                            return false;
                         
                         }
                         
@@ -837,7 +1180,7 @@ pub async fn startup(config: &mut Config) {
                     },
                     Err(err) => {
                         println!("{:#?}", err);
-                        return;
+                        return false;
                     }
                 }
             }
@@ -874,6 +1217,32 @@ pub async fn startup(config: &mut Config) {
                 )
             });
 
+            let chase = matches.get_flag("chase").then(|| {
+                let max_reprices = matches
+                    .get_one::<String>("max_reprices")
+                    .expect("Max reprices has a default")
+                    .parse::<u32>()
+                    .expect("Invalid max reprices, expected a whole number");
+
+                let Percent(max_distance) = matches
+                    .get_one::<String>("max_distance")
+                    .expect("Max distance has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid max distance, expected a percentage e.g 0.5%");
+
+                Chase {
+                    max_reprices,
+                    max_distance,
+                    fallback_ioc: matches.get_flag("fallback_ioc"),
+                }
+            });
+
+            if chase.is_some() && matches.contains_id("price") {
+                println!("--chase places its own post-only price and can't be combined with --price");
+                return false;
+            }
+
             let wallet = Arc::new(
                 match config
                     .private_key
@@ -881,7 +1250,7 @@ pub async fn startup(config: &mut Config) {
                         Ok(wallet) => wallet,
                         Err(_) => {
                             println!("Error: Invalid private key");
-                            return;
+                            return false;
                         }
                     }
             );
@@ -966,38 +1335,88 @@ pub async fn startup(config: &mut Config) {
             );
             println!("Market price: {}\n", market_price);
 
-            match exchange.place_order(wallet.clone(), vec![order], None).await {
-                Ok(order) => match order {
-                    Response::Err(err) => {
+            if preview {
+                println!("Limit price: {}", order.limit_px);
+                return true;
+            }
+
+            let (limit_price, sz) = if let Some(chase) = &chase {
+                let chased = match chase::run(
+                    chase,
+                    &info,
+                    &exchange,
+                    wallet.clone(),
+                    symbol,
+                    (sz_decimals, asset),
+                    false,
+                    sz,
+                    &journal,
+                )
+                .await
+                {
+                    Ok(chased) => chased,
+                    Err(err) => {
                         println!("{:#?}", err);
-                        return;
-                    
+                        return false;
                     }
-                    
-                    Response::Ok(order) => {
-                        match order.data.expect("expected order response data") {
-                            StatusType::Statuses(statuses) => {
-                                statuses.iter().for_each(|status| match status {
-                            Status::Filled(order) => {
-                                println!("Order {} was successfully filled.\n", order.oid);
-                            }
-                            Status::Resting(order) => {
-                                println!("Order {} was successfully placed.\n", order.oid);
-                            }
-                            Status::Error(msg) => {
-                                println!("Order failed with error: {:#?}\n", msg)
-                            }
-                            _ =>  unreachable!(),
-                        });
-                    },
-                    _ =>  unreachable!(),
+                };
+
+                println!("Filled {} {} (${:.2})", chased.filled_sz, symbol, chased.filled_usd);
+                if let Some((oid, px)) = chased.resting {
+                    println!("Order {} left resting at {}", oid, px);
                 }
-                }},
-                Err(err) => {
-                    println!("{:#?}", err);
-                    return;
+                println!();
+
+                let entry_px = match chased.entry_px() {
+                    Some(entry_px) => entry_px,
+                    None => {
+                        if tp.is_some() || sl.is_some() {
+                            println!("Nothing was filled or left resting, no take profit or stop loss was placed");
+                        }
+                        return false;
+                    }
+                };
+
+                // an order left resting may still fill in full, otherwise only the fills are covered
+                let sz = if chased.resting.is_some() { sz } else { chased.filled_sz };
+
+                (entry_px, sz)
+            } else {
+                match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
+                    Ok(order) => match order {
+                        Response::Err(err) => {
+                            println!("{:#?}", err);
+                            return false;
+
+                        }
+
+                        Response::Ok(order) => {
+                            match order.data.expect("expected order response data") {
+                                StatusType::Statuses(statuses) => {
+                                    statuses.iter().for_each(|status| match status {
+                                Status::Filled(order) => {
+                                    println!("Order {} was successfully filled.\n", order.oid);
+                                }
+                                Status::Resting(order) => {
+                                    println!("Order {} was successfully placed.\n", order.oid);
+                                }
+                                Status::Error(msg) => {
+                                    println!("Order failed with error: {:#?}\n", msg)
+                                }
+                                _ =>  unreachable!(),
+                            });
+                        },
+                        _ =>  unreachable!(),
+                    }
+                    }},
+                    Err(err) => {
+                        println!("{:#?}", err);
+                        return false;
+                    }
                 }
-            }
+
+                (limit_price, sz)
+            };
 
             if tp.is_some() {
                 let trigger_price = match tp {
@@ -1034,11 +1453,11 @@ pub async fn startup(config: &mut Config) {
                 println!("Entry price: {}", order.limit_px);
                 println!("Market price: {}\n", market_price);
 
-                match exchange.place_order(wallet.clone(), vec![order], None).await {
+                match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
                     Ok(order) => match order {
                         Response::Err(err) => {
                             println!("{:#?}", err);
-                            return;
+                            return false;
                         
                         }
                         
@@ -1069,7 +1488,7 @@ pub async fn startup(config: &mut Config) {
                     },
                     Err(err) => {
                         println!("{:#?}", err);
-                        return;
+                        return false;
                     }
                 }
             }
@@ -1109,11 +1528,11 @@ pub async fn startup(config: &mut Config) {
                 println!("Entry price: {}", order.limit_px);
                 println!("Market price: {}\n", market_price);
 
-                match exchange.place_order(wallet.clone(), vec![order], None).await {
+                match place_order(&exchange, wallet.clone(), vec![order], &journal).await {
                     Ok(order) => match order {
                         Response::Err(err) => {
                             println!("{:#?}", err);
-                            return;
+                            return false;
                         
                         }
                         
@@ -1144,14 +1563,14 @@ pub async fn startup(config: &mut Config) {
                     }},
                     Err(err) => {
                         println!("{:#?}", err);
-                        return;
+                        return false;
                     }
                 }
             }
         }
 
         Some(("scale", matches)) => match matches.subcommand() {
-            Some(("buy", matches)) => {
+            Some((side @ ("buy" | "sell"), matches)) => {
                 let sz_per_interval: SzPerInterval = matches
                     .get_one::<String>("size_per_interval")
                     .expect("Order size is required")
@@ -1175,121 +1594,47 @@ pub async fn startup(config: &mut Config) {
                     .parse::<f64>()
                     .expect("Failed to parse upper price bracket");
 
-                    let wallet = Arc::new(
-                        match config
-                            .private_key
-                            .parse::<LocalWallet>() {
-                                Ok(wallet) => wallet,
-                                Err(_) => {
-                                    println!("Error: Invalid private key");
-                                    return;
-                                }
-                            }
-                    );
-                // ----------------------------------------------
-
-                let asset_ctxs = info
-                    .contexts()
-                    .await
-                    .expect("Failed to fetch asset ctxs");
+                let distribution: Distribution = matches
+                    .get_one::<String>("distribution")
+                    .expect("Distribution has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid distribution, expected linear, geometric or exponential");
 
-                let asset_ctx =
-                asset_ctx(&asset_ctxs,symbol)
-                    .expect("Failed to fetch asset ctxs")
-                    .expect("Failed to find asset");
+                let weight: Weight = matches
+                    .get_one::<String>("weight")
+                    .expect("Weight has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid weight, expected flat, increasing, decreasing or custom weights e.g 1,2,4");
 
-                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
+                let is_buy = side == "buy";
 
                 let (sz_decimals, asset) = *assets
                     .get(&symbol.to_uppercase())
                     .expect("Failed to find asset");
 
-                let interval = (upper - lower) / (sz_per_interval.interval - 1) as f64;
-
-                let sz = (sz_per_interval.size / sz_per_interval.interval as f64) / market_price;
-
-                for i in 0..sz_per_interval.interval {
-                    let limit_price = lower + (interval * i as f64);
-
-                    println!("{}", "---".repeat(20));
-                    println!("Order {} of {}", i + 1, sz_per_interval.interval);
-                    println!("Side: Buy");
-                    println!("Size in {symbol}: {}", parse_size(sz, sz_decimals));
-                    println!(
-                        "Size in USD: {}",
-                        parse_size(sz * market_price, sz_decimals)
-                    );
-                    println!("Entry price: {}", parse_price(limit_price));
-                    println!("Market price: {}\n", market_price);
-
-                    let order = OrderRequest {
-                        cloid: None,
-                        asset,
-                        is_buy: true,
-                        limit_px: parse_price(limit_price),
-                        sz: parse_size(sz, sz_decimals),
-                        reduce_only: false,
-                        order_type: OrderType::Limit(Limit { tif: Tif::Gtc }),
-                    };
-
-                    match exchange.place_order(wallet.clone(), vec![order], None).await {
-                        Ok(order) => match order {
-                            Response::Err(err) => {
-                                println!("{:#?}", err);
-                                return;
-                            
-                            }
-                            
-                            Response::Ok(order) => {
-                                match order.data.expect("expected order response data") {
-                                    StatusType::Statuses(statuses) => {
-                                        statuses.iter().for_each(|status| match status {
-                                    Status::Filled(order) => {
-                                        println!("Order {} was successfully filled.\n", order.oid)
-                                    }
-                                    Status::Resting(order) => {
-                                        println!("Order {} was successfully placed.\n", order.oid)
-                                    }
-                                    Status::Error(msg) => {
-                                        println!("Order failed with error: {:#?}\n", msg)
-                                    }
-                                    _ =>  unreachable!(),
-                                });
-                            },
-                            _ =>  unreachable!(),
-                        }
-                        }},
-                        Err(err) => {
-                            println!("{:#?}", err);
-                            return;
-                        }
+                let rungs = match scale::ladder(
+                    is_buy,
+                    sz_per_interval.size,
+                    sz_per_interval.interval,
+                    lower,
+                    upper,
+                    distribution,
+                    &weight,
+                ) {
+                    Ok(rungs) => rungs,
+                    Err(err) => {
+                        println!("{}", err);
+                        return false;
                     }
-                }
-            }
+                };
 
-            Some(("sell", matches)) => {
-                let sz_per_interval: SzPerInterval = matches
-                    .get_one::<String>("size_per_interval")
-                    .expect("Order size is required")
-                    .as_str()
-                    .try_into()
-                    .expect("Failed to parse order size");
+                scale::preview(&rungs, symbol, sz_decimals);
 
-                let symbol = matches
-                    .get_one::<String>("asset")
-                    .expect("Asset is required");
-                let lower = matches
-                    .get_one::<String>("lower")
-                    .expect("Lower price bracket is required")
-                    .as_str()
-                    .parse::<f64>()
-                    .expect("Failed to parse lower price bracket");
-                let upper = matches
-                    .get_one::<String>("upper")
-                    .expect("Upper price bracket is required")
-                    .as_str()
-                    .parse::<f64>()
-                    .expect("Failed to parse upper price bracket");
+                if preview || matches.get_flag("preview") {
+                    return true;
+                }
 
                 let wallet = Arc::new(
                     match config
@@ -1298,11 +1643,11 @@ pub async fn startup(config: &mut Config) {
                             Ok(wallet) => wallet,
                             Err(_) => {
                                 println!("Error: Invalid private key");
-                                return;
+                                return false;
                             }
                         }
                 );
-                //------------------------------------
+                // ----------------------------------------------
 
                 let asset_ctxs = info
                     .contexts()
@@ -1313,83 +1658,53 @@ pub async fn startup(config: &mut Config) {
                 asset_ctx(&asset_ctxs,symbol)
                     .expect("Failed to fetch asset ctxs")
                     .expect("Failed to find asset");
-                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
-
-                let (sz_decimals, asset) = *assets
-                    .get(&symbol.to_uppercase())
-                    .expect("Failed to find asset");
-
-                
-
-                let interval = (upper - lower) / (sz_per_interval.interval - 1) as f64;
 
-                let sz = (sz_per_interval.size / sz_per_interval.interval as f64) / market_price;
-
-                for i in 0..sz_per_interval.interval {
-                    let limit_price = lower + (interval * i as f64);
-
-                    println!("{}", "---".repeat(20));
-                    println!("Order {} of {}", i + 1, sz_per_interval.interval);
-                    println!("Side: Sell");
-                    println!("Size in {symbol}: {}", parse_size(sz, sz_decimals));
-                    println!(
-                        "Size in USD: {}",
-                        parse_size(sz * market_price, sz_decimals)
-                    );
-                    println!("Entry price: {}", parse_price(limit_price));
-                    println!("Market price: {}\n", market_price);
+                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
 
-                    let order = OrderRequest {
+                let orders = rungs
+                    .iter()
+                    .map(|rung| OrderRequest {
                         cloid: None,
                         asset,
-                        is_buy: false,
-                        limit_px: parse_price(limit_price),
-                        sz: parse_size(sz, sz_decimals),
+                        is_buy,
+                        limit_px: parse_price(rung.price),
+                        sz: parse_size(rung.sz, sz_decimals),
                         reduce_only: false,
                         order_type: OrderType::Limit(Limit { tif: Tif::Gtc }),
-                    };
+                    })
+                    .collect::<Vec<_>>();
 
-                    match exchange.place_order(wallet.clone(), vec![order], None).await {
-                        Ok(order) => match order {
-                            Response::Err(err) => {
-                                println!("{:#?}", err);
-                                return;
-                            
-                            }
-                            
-                            Response::Ok(order) => {
-                                match order.data.expect("expected order response data") {
-                                    StatusType::Statuses(statuses) => {
-                                        statuses.iter().for_each(|status| match status {
-                                    Status::Filled(order) => {
-                                        println!("Order {} was successfully filled.\n", order.oid)
-                                    }
-                                    Status::Resting(order) => {
-                                        println!("Order {} was successfully placed.\n", order.oid)
-                                    }
-                                    Status::Error(msg) => {
-                                        println!("Order failed with error: {:#?}\n", msg)
-                                    }
-                                    _ =>  unreachable!(),
-                                });
-                            },
-                            _ =>  unreachable!(),
+                println!("Side: {}", if is_buy { "Buy" } else { "Sell" });
+                println!("Market price: {}\n", market_price);
+
+                let statuses = match place_batch(&exchange, wallet.clone(), orders, &journal).await {
+                    Ok(statuses) => statuses,
+                    Err(err) => {
+                        println!("{:#?}", err);
+                        return false;
+                    }
+                };
+
+                for (i, (rung, status)) in rungs.iter().zip(statuses.iter()).enumerate() {
+                    match status {
+                        Status::Filled(order) => {
+                            println!("Order {} of {} ({}) at {} was successfully filled.", i + 1, rungs.len(), order.oid, parse_price(rung.price))
                         }
-                        }},
-                        Err(err) => {
-                            println!("{:#?}", err);
-                            return;
+                        Status::Resting(order) => {
+                            println!("Order {} of {} ({}) at {} was successfully placed.", i + 1, rungs.len(), order.oid, parse_price(rung.price))
                         }
+                        _ => unreachable!(),
                     }
                 }
             }
             _ => {
                 println!("No matching pattern");
+                return false;
             }
         },
         Some(("twap", matches)) => {
             match matches.subcommand() {
-                Some(("buy", matches)) => {
+                Some((side @ ("buy" | "sell"), matches)) => {
                     let sz: OrderSize = matches
                         .get_one::<String>("size")
                         .expect("Size is required")
@@ -1405,10 +1720,41 @@ pub async fn startup(config: &mut Config) {
                     .expect("Interval is required")
                     .as_str()
                     .try_into().expect(
-                        "Invalid interval value, correct format is <time between interval in mins, number of intervals> e.g 5,10",
+                        "Invalid interval value, correct format is <time between interval in mins or with a s/m/h unit, number of intervals> e.g 5,10 or 30s,20",
                     );
 
+                    let size_jitter: Option<Percent> = matches.get_one::<String>("size_jitter").map(|jitter| {
+                        jitter.as_str().try_into().expect("Invalid size jitter, expected a percentage e.g 20%")
+                    });
+
+                    let interval_jitter: Option<Percent> = matches.get_one::<String>("interval_jitter").map(|jitter| {
+                        jitter.as_str().try_into().expect("Invalid interval jitter, expected a percentage e.g 20%")
+                    });
+
+                    let limit_px: Option<LimitPrice> = matches.get_one::<String>("limit").map(|price| {
+                        price.as_str().try_into().expect("Failed to parse limit price")
+                    });
+
+                    let max_participation: Option<Percent> = matches.get_one::<String>("max_participation").map(|rate| {
+                        rate.as_str().try_into().expect("Invalid max participation, expected a percentage e.g 10%")
+                    });
+
+                    let sz = match sz {
+                        OrderSize::Absolute(sz) => sz,
+
+                        _ => {
+                            println!("{}", "-".repeat(35));
+
+                            println!("\nOnly absolute order size is supported for now");
+                            return false;
+                        }
+                    };
+
+                    let asset = *assets
+                        .get(&symbol.to_uppercase())
+                        .expect("Failed to find asset");
 
+                    // an invalid key fails before the job is saved, not leaving a running twap behind
                     let wallet = Arc::new(
                         match config
                             .private_key
@@ -1416,90 +1762,183 @@ pub async fn startup(config: &mut Config) {
                                 Ok(wallet) => wallet,
                                 Err(_) => {
                                     println!("Error: Invalid private key");
-                                    return;
+                                    return false;
                                 }
                             }
                     );
 
-                    let sz = match sz {
-                        OrderSize::Absolute(sz) => sz,
+                    let mut job = match TwapJob::new(
+                        symbol,
+                        side == "buy",
+                        sz,
+                        interval.interval,
+                        interval.num_of_orders,
+                    ) {
+                        Ok(job) => job,
+                        Err(err) => {
+                            println!("Failed to save twap: {:#?}", err);
+                            return false;
+                        }
+                    };
 
-                        _ => {
-                            println!("{}", "-".repeat(35));
+                    job.size_jitter = size_jitter.map_or(0.0, |Percent(jitter)| jitter);
+                    job.interval_jitter = interval_jitter.map_or(0.0, |Percent(jitter)| jitter);
+                    job.limit_px = limit_px.map(|LimitPrice::Absolute(price)| price);
+                    job.defer_on_limit = matches
+                        .get_one::<String>("on_limit")
+                        .map(|on_limit| on_limit.as_str())
+                        != Some("skip");
+                    job.max_participation = max_participation.map(|Percent(rate)| rate);
+
+                    match job.save() {
+                        Ok(_) => println!("Twap {} successfully saved ✔️\n---", job.id),
+                        Err(err) => {
+                            println!("Failed to save twap: {:#?}", err);
+                            return false;
+                        }
+                    }
 
-                            println!("\nOnly absolute order size is supported for now");
-                            return;
+                    if matches.get_flag("daemon") {
+                        match daemon::submit(command_line, Job::Twap { id: job.id }).await {
+                            Ok(id) => println!("Twap {} submitted to the daemon as job {} ✔️\n---", job.id, id),
+                            Err(err) => {
+                                println!("Failed to submit twap {}: {:#?}", job.id, err);
+
+                                // nothing runs it, paused it can be resumed without --force
+                                job.status = TwapStatus::Paused;
+                                match job.save() {
+                                    Ok(_) => println!("Twap {} is paused, run `hl twap resume {}` to start it", job.id, job.id),
+                                    Err(err) => println!("Failed to update twap: {:#?}", err),
+                                }
+                                return false;
+                            }
                         }
-                    } / interval.num_of_orders as f64;
+                        return true;
+                    }
 
-                    let (sz_decimals, asset) = *assets
-                        .get(&symbol.to_uppercase())
-                        .expect("Failed to find asset");
+                    let feed = feed.clone();
 
-                    let slippage = 3.0 / 100.0;
+                    twap::run(job, &feed, &exchange, wallet, asset, &journal).await;
+                }
+                Some(("list", _)) => {
+                    let jobs = TwapJob::list().expect("Failed to read twap jobs");
 
-                    for i in 1..=interval.num_of_orders {
-                        let asset_ctxs = info
-                            .contexts()
-                            .await
-                            .expect("Failed to fetch asset ctxs");
+                    for job in jobs.iter() {
+                        job.print();
+                    }
 
-                        let market_price =
-                            asset_ctx(&asset_ctxs,&symbol.to_uppercase())
-                            .expect("Failed to fetch asset ctxs")
-                            .expect("Failed to find asset")
-                            .mark_px
-                            .parse::<f64>()
-                            .unwrap();
+                    println!("{}", "---".repeat(20));
+                    println!("\nTotal Twaps: {}", jobs.len());
+                }
+                Some((action @ ("status" | "pause" | "resume" | "cancel"), matches)) => {
+                    let id = matches
+                        .get_one::<String>("id")
+                        .expect("Twap id is required")
+                        .parse::<u64>()
+                        .expect("Failed to parse twap id");
+
+                    let mut job = match TwapJob::load(id).expect("Failed to read twap job") {
+                        Some(job) => job,
+                        None => {
+                            println!("No twap with id {}", id);
+                            return false;
+                        }
+                    };
 
-                        let sz = sz / market_price;
-                        let limit_price = market_price * (1.0 + slippage);
+                    match action {
+                        "status" => job.print(),
+                        "pause" | "cancel" => {
+                            if !matches!(job.status, TwapStatus::Running | TwapStatus::Paused) {
+                                println!("Twap {} is already {}", id, job.status_name().to_lowercase());
+                                return false;
+                            }
 
-                        println!("{}", "---".repeat(20));
-                        println!("Order {} of {}", i, interval.num_of_orders);
-                        println!("Side: Buy");
-                        println!("Size in {symbol}: {}", parse_size(sz, sz_decimals));
-                        println!(
-                            "Size in USD: {}",
-                            parse_size(sz * market_price, sz_decimals)
-                        );
-                        println!("Market price: {}\n", market_price);
-
-                        let order = OrderRequest {
-                            cloid: None,
-                            asset,
-                            is_buy: true,
-                            limit_px: parse_price(limit_price),
-                            sz: parse_size(sz, sz_decimals),
-                            reduce_only: false,
-                            order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                        };
+                            job.status = if action == "pause" {
+                                TwapStatus::Paused
+                            } else {
+                                TwapStatus::Cancelled
+                            };
 
-                        match exchange.place_order(wallet.clone(), vec![order], None).await {
-                            Ok(order) => match order {
-                                Response::Err(err) => {
-                                    println!("{:#?}", err);
-                                    return;
-                                }
-                                Response::Ok(_order) => {
-                                    // println!("Order placed: {:#?}", order);
-                                    println!("Buy order was successfully placed.\n")
+                            match job.save() {
+                                Ok(_) => println!("Twap {} successfully {} ✔️\n---", id, job.status_name().to_lowercase()),
+                                Err(err) => {
+                                    println!("Failed to update twap: {:#?}", err);
+                                    return false;
                                 }
-                            },
-                            Err(err) => {
-                                println!("{:#?}", err);
-                                return;
                             }
                         }
+                        _ => {
+                            let force = matches.get_flag("force");
+
+                            match job.status {
+                                TwapStatus::Paused => {}
+                                TwapStatus::Running if job.is_stale() || force => {}
+                                TwapStatus::Running => {
+                                    println!("Twap {} is still running, use --force if its process has exited", id);
+                                    return false;
+                                }
+                                _ => {
+                                    println!("Twap {} is already {}", id, job.status_name().to_lowercase());
+                                    return false;
+                                }
+                            }
+
+                            let asset = *assets
+                                .get(&job.symbol)
+                                .expect("Failed to find asset");
+
+                            // resume from the last completed slice without waiting out the old interval
+                            job.status = TwapStatus::Running;
+                            job.next_due = twap::now();
+                            job.save().expect("Failed to update twap");
+
+                            println!("Resuming twap {} at slice {} of {}\n---", id, job.slices_done + 1, job.num_of_orders);
+
+                            if matches.get_flag("daemon") {
+                                match daemon::submit(command_line, Job::Twap { id }).await {
+                                    Ok(daemon_id) => println!("Twap {} submitted to the daemon as job {} ✔️\n---", id, daemon_id),
+                                    Err(err) => {
+                                        println!("Failed to submit twap {}: {:#?}", id, err);
+
+                                        // nothing runs it, paused it can be resumed again without --force
+                                        job.status = TwapStatus::Paused;
+                                        if let Err(err) = job.save() {
+                                            println!("Failed to update twap: {:#?}", err);
+                                        }
+                                        return false;
+                                    }
+                                }
+                                return true;
+                            }
+
+                            let wallet = Arc::new(
+                                match config
+                                    .private_key
+                                    .parse::<LocalWallet>() {
+                                        Ok(wallet) => wallet,
+                                        Err(_) => {
+                                            println!("Error: Invalid private key");
+                                            return false;
+                                        }
+                                    }
+                            );
+
+                            let feed = feed.clone();
 
-                        if i != interval.num_of_orders {
-                            println!("Waiting for {} minutes", interval.interval.as_secs() / 60);
-                            println!("{}", "-".repeat(5));
-                            tokio::time::sleep(interval.interval).await;
+                            twap::run(job, &feed, &exchange, wallet, asset, &journal).await;
                         }
                     }
                 }
-                Some(("sell", matches)) => {
+                _ => {
+                    println!("No matching pattern");
+                    return false;
+                }
+            }
+        }
+
+        Some(("pov", matches)) => {
+            match matches.subcommand() {
+                Some((side @ ("buy" | "sell"), matches)) => {
                     let sz: OrderSize = matches
                         .get_one::<String>("size")
                         .expect("Size is required")
@@ -1511,23 +1950,20 @@ pub async fn startup(config: &mut Config) {
                         .get_one::<String>("asset")
                         .expect("Asset is required");
 
-                    let interval: TwapInterval = matches.get_one::<String>("interval")
-                    .expect("Interval is required")
-                    .as_str()
-                    .try_into().expect(
-                        "Invalid interval value, correct format is <time between interval in mins, number of intervals> e.g 5,10",
-                    );
-                    let wallet = Arc::new(
-                        match config
-                            .private_key
-                            .parse::<LocalWallet>() {
-                                Ok(wallet) => wallet,
-                                Err(_) => {
-                                    println!("Error: Invalid private key");
-                                    return;
-                                }
-                            }
-                    );
+                    let Percent(rate) = matches
+                        .get_one::<String>("rate")
+                        .expect("Rate is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Invalid rate, expected a percentage e.g 5%");
+
+                    let limit_px: Option<LimitPrice> = matches.get_one::<String>("limit").map(|price| {
+                        price.as_str().try_into().expect("Failed to parse limit price")
+                    });
+
+                    let deadline: Option<TimeSpan> = matches.get_one::<String>("deadline").map(|deadline| {
+                        deadline.as_str().try_into().expect("Invalid deadline, expected a time in mins or with a s/m/h unit e.g 30 or 2h")
+                    });
 
                     let sz = match sz {
                         OrderSize::Absolute(sz) => sz,
@@ -1536,101 +1972,1311 @@ pub async fn startup(config: &mut Config) {
                             println!("{}", "-".repeat(35));
 
                             println!("\nOnly absolute order size is supported for now");
-                            return;
+                            return false;
                         }
-                    } / interval.num_of_orders as f64;
+                    };
+
+                    if rate <= 0.0 || rate > 1.0 {
+                        println!("Rate must be greater than 0% and at most 100%");
+                        return false;
+                    }
 
-                    let (sz_decimals, asset) = *assets
+                    let asset = *assets
                         .get(&symbol.to_uppercase())
                         .expect("Failed to find asset");
 
-                    let slippage = 3.0 / 100.0;
-
-                    for i in 1..=interval.num_of_orders {
-                        let asset_ctxs = info
-                            .contexts()
-                            .await
-                            .expect("Failed to fetch asset ctxs");
-
-                        let market_price =
-                            asset_ctx(&asset_ctxs,&symbol.to_uppercase())
-                            .expect("Failed to fetch asset ctxs")
-                            .expect("Failed to find asset")
-                            .mark_px
-                            .parse::<f64>()
-                            .unwrap();
-
-                        let sz = sz / market_price;
-                        let limit_price = market_price * (1.0 - slippage);
-
-                        println!("{}", "---".repeat(20));
-                        println!("Order {} of {}", i, interval.num_of_orders);
-                        println!("Side: Sell");
-                        println!("Size in {symbol}: {}", parse_size(sz, sz_decimals));
-                        println!(
-                            "Size in USD: {}",
-                            parse_size(sz * market_price, sz_decimals)
-                        );
-                        println!("Market price: {}\n", market_price);
-
-                        let order = OrderRequest {
-                            cloid: None,
-                            asset,
-                            is_buy: false,
-                            limit_px: parse_price(limit_price),
-                            sz: parse_size(sz, sz_decimals),
-                            reduce_only: false,
-                            order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                        };
-
-                        match exchange.place_order(wallet.clone(), vec![order], None).await {
-                            Ok(order) => match order {
-                                Response::Err(err) => {
-                                    println!("{:#?}", err);
-                                    return;
-                                }
-                                Response::Ok(_order) => {
-                                    // println!("Order placed: {:#?}", order);
-                                    println!("Sell order was successfully placed.\n")
+                    let wallet = Arc::new(
+                        match config
+                            .private_key
+                            .parse::<LocalWallet>() {
+                                Ok(wallet) => wallet,
+                                Err(_) => {
+                                    println!("Error: Invalid private key");
+                                    return false;
                                 }
-                            },
-                            Err(err) => {
-                                println!("{:#?}", err);
-                                return;
                             }
-                        }
+                    );
 
-                        if i != interval.num_of_orders {
-                            println!("Waiting for {} minutes", interval.interval.as_secs() / 60);
-                            println!("{}", "-".repeat(5));
-                            tokio::time::sleep(interval.interval).await;
-                        }
-                    }
+                    let order = PovOrder {
+                        symbol: symbol.to_uppercase(),
+                        is_buy: side == "buy",
+                        size: sz,
+                        rate,
+                        limit_px: limit_px.map(|LimitPrice::Absolute(price)| price),
+                        deadline: deadline.map(|TimeSpan(deadline)| twap::now() + deadline.as_secs()),
+                    };
+
+                    let feed = feed.clone();
+
+                    pov::run(order, &feed, &exchange, wallet, asset, &journal).await;
                 }
                 _ => {
                     println!("No matching pattern");
+                    return false;
                 }
             }
         }
 
-        Some(("view", matches)) => match matches.subcommand_name() {
-            Some("upnl") => {
-                let wallet = Arc::new(
-                    match config
-                        .private_key
-                        .parse::<LocalWallet>() {
-                            Ok(wallet) => wallet,
-                            Err(_) => {
-                                println!("Error: Invalid private key");
-                                return;
-                            }
-                        }
-                );
-
-                let state = info
-                    .user_state(wallet.address())
-                    .await
-                    .expect("Failed to fetch unrealized pnl");
+        Some(("iceberg", matches)) => {
+            match matches.subcommand() {
+                Some((side @ ("buy" | "sell"), matches)) => {
+                    let sz: OrderSize = matches
+                        .get_one::<String>("size")
+                        .expect("Size is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse order size");
+
+                    let clip: OrderSize = matches
+                        .get_one::<String>("show")
+                        .expect("Clip size is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse clip size");
+
+                    let symbol = matches
+                        .get_one::<String>("asset")
+                        .expect("Asset is required");
+
+                    let LimitPrice::Absolute(price) = matches
+                        .get_one::<String>("price")
+                        .expect("Price is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse limit price");
+
+                    let (sz, clip) = match (sz, clip) {
+                        (OrderSize::Absolute(sz), OrderSize::Absolute(clip)) => (sz, clip),
+
+                        _ => {
+                            println!("{}", "-".repeat(35));
+
+                            println!("\nOnly absolute order size is supported for now");
+                            return false;
+                        }
+                    };
+
+                    if price <= 0.0 || clip <= 0.0 || clip > sz {
+                        println!("Price and clip size must be greater than 0 and the clip can't be larger than the total");
+                        return false;
+                    }
+
+                    let asset = *assets
+                        .get(&symbol.to_uppercase())
+                        .expect("Failed to find asset");
+
+                    let wallet = Arc::new(
+                        match config
+                            .private_key
+                            .parse::<LocalWallet>() {
+                                Ok(wallet) => wallet,
+                                Err(_) => {
+                                    println!("Error: Invalid private key");
+                                    return false;
+                                }
+                            }
+                    );
+
+                    // convert $sz to base asset at the limit price
+                    let job = match IcebergJob::new(symbol, side == "buy", price, sz / price, clip / price) {
+                        Ok(job) => job,
+                        Err(err) => {
+                            println!("Failed to save iceberg: {:#?}", err);
+                            return false;
+                        }
+                    };
+
+                    println!("Iceberg {} successfully saved ✔️\n---", job.id);
+
+                    iceberg::run(job, &info, &exchange, wallet, asset, &journal).await;
+                }
+                Some(("list", _)) => {
+                    let jobs = IcebergJob::list().expect("Failed to read iceberg jobs");
+
+                    for job in jobs.iter() {
+                        job.print();
+                    }
+
+                    println!("{}", "---".repeat(20));
+                    println!("\nTotal Icebergs: {}", jobs.len());
+                }
+                Some((action @ ("status" | "cancel"), matches)) => {
+                    let id = matches
+                        .get_one::<String>("id")
+                        .expect("Iceberg id is required")
+                        .parse::<u64>()
+                        .expect("Failed to parse iceberg id");
+
+                    let mut job = match IcebergJob::load(id).expect("Failed to read iceberg job") {
+                        Some(job) => job,
+                        None => {
+                            println!("No iceberg with id {}", id);
+                            return false;
+                        }
+                    };
+
+                    if action == "status" {
+                        job.print();
+                        return true;
+                    }
+
+                    if job.status != IcebergStatus::Running {
+                        println!("Iceberg {} is already {}", id, job.status_name().to_lowercase());
+                        return false;
+                    }
+
+                    // stop the running iceberg first so it doesn't place another clip
+                    job.status = IcebergStatus::Cancelled;
+                    if let Err(err) = job.save() {
+                        println!("Failed to update iceberg: {:#?}", err);
+                        return false;
+                    }
+
+                    println!("Iceberg {} successfully cancelled ✔️", id);
+
+                    // the iceberg is cancelled either way, a clip that couldn't be cancelled is reported
+                    if let Some(cloid) = job.clip_cloid {
+                        let (_, asset) = *assets
+                            .get(&job.symbol)
+                            .expect("Failed to find asset");
+
+                        let cancelled = match config.private_key.parse::<LocalWallet>() {
+                            Ok(wallet) => iceberg::cancel_clip(&exchange, Arc::new(wallet), asset, cloid).await,
+                            Err(_) => Err(anyhow::anyhow!("Invalid private key")),
+                        };
+
+                        if let Err(err) = cancelled {
+                            println!("Failed to cancel clip {}: {:#?}", cloid, err);
+                            println!(
+                                "Clip {} may still be resting, check your open orders",
+                                job.clip_oid.map_or(cloid.to_string(), |oid| oid.to_string())
+                            );
+                        }
+                    }
+
+                    println!("---");
+                }
+                _ => {
+                    println!("No matching pattern");
+                    return false;
+                }
+            }
+        }
+
+        Some(("grid", matches)) => {
+            match matches.subcommand() {
+                None => {
+                    let symbol = matches
+                        .get_one::<String>("asset")
+                        .expect("Asset is required");
+
+                    let LimitPrice::Absolute(lower) = matches
+                        .get_one::<String>("lower")
+                        .expect("Lower price is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse lower price");
+
+                    let LimitPrice::Absolute(upper) = matches
+                        .get_one::<String>("upper")
+                        .expect("Upper price is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse upper price");
+
+                    let levels = matches
+                        .get_one::<String>("levels")
+                        .expect("Number of levels is required")
+                        .parse::<u32>()
+                        .expect("Failed to parse number of levels");
+
+                    let size: OrderSize = matches
+                        .get_one::<String>("size")
+                        .expect("Size is required")
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse order size");
+
+                    let size = match size {
+                        OrderSize::Absolute(size) => size,
+                        _ => {
+                            println!("{}", "-".repeat(35));
+
+                            println!("\nOnly absolute order size is supported for now");
+                            return false;
+                        }
+                    };
+
+                    let stop_lower = matches.get_one::<String>("stop_lower").map(|stop| {
+                        let LimitPrice::Absolute(stop) = stop
+                            .as_str()
+                            .try_into()
+                            .expect("Failed to parse lower stop-loss bound");
+
+                        stop
+                    });
+
+                    let stop_upper = matches.get_one::<String>("stop_upper").map(|stop| {
+                        let LimitPrice::Absolute(stop) = stop
+                            .as_str()
+                            .try_into()
+                            .expect("Failed to parse upper stop-loss bound");
+
+                        stop
+                    });
+
+                    let paper = matches.get_flag("paper");
+
+                    let asset = *assets
+                        .get(&symbol.to_uppercase())
+                        .expect("Failed to find asset");
+
+                    // an invalid key fails before the grid is saved
+                    let venue = if paper {
+                        Venue::Simulated(SimulatedExchange::default())
+                    } else {
+                        let wallet = Arc::new(
+                            match config
+                                .private_key
+                                .parse::<LocalWallet>() {
+                                    Ok(wallet) => wallet,
+                                    Err(_) => {
+                                        println!("Error: Invalid private key");
+                                        return false;
+                                    }
+                                }
+                        );
+
+                        Venue::Live {
+                            info: &info,
+                            exchange: &exchange,
+                            wallet,
+                            journal: &journal,
+                        }
+                    };
+
+                    let job = match GridJob::new(symbol, lower, upper, levels, size, stop_lower, stop_upper, paper) {
+                        Ok(job) => job,
+                        Err(err) => {
+                            println!("{}", err);
+                            return false;
+                        }
+                    };
+
+                    println!("Grid {} successfully saved ✔️\n---", job.id);
+
+                    let feed = feed.clone();
+
+                    if let Err(err) = grid::run(job, &feed, venue, asset).await {
+                        println!("Grid stopped with error: {:#?}", err);
+                        return false;
+                    }
+                }
+                Some(("list", _)) => {
+                    let jobs = GridJob::list().expect("Failed to read grid jobs");
+
+                    for job in jobs.iter() {
+                        job.print();
+                    }
+
+                    println!("{}", "---".repeat(20));
+                    println!("\nTotal Grids: {}", jobs.len());
+                }
+                Some((action @ ("status" | "cancel" | "resume"), matches)) => {
+                    let id = matches
+                        .get_one::<String>("id")
+                        .expect("Grid id is required")
+                        .parse::<u64>()
+                        .expect("Failed to parse grid id");
+
+                    let mut job = match GridJob::load(id).expect("Failed to read grid job") {
+                        Some(job) => job,
+                        None => {
+                            println!("No grid with id {}", id);
+                            return false;
+                        }
+                    };
+
+                    if action == "status" {
+                        job.print();
+
+                        for order in job.orders.iter() {
+                            println!(
+                                "Level {}: {} {} {} at {} (order {})",
+                                order.level + 1,
+                                if order.is_buy { "Buy" } else { "Sell" },
+                                order.sz,
+                                job.symbol,
+                                parse_price(job.prices[order.level]),
+                                order.oid
+                            );
+                        }
+                        return true;
+                    }
+
+                    if job.status != GridStatus::Running {
+                        println!("Grid {} is already {}", id, job.status_name().to_lowercase());
+                        return false;
+                    }
+
+                    if action == "resume" {
+                        if matches.get_flag("daemon") {
+                            match daemon::submit(command_line, Job::Grid { id }).await {
+                                Ok(job_id) => println!("Grid {} submitted to the daemon as job {} ✔️\n---", id, job_id),
+                                Err(err) => {
+                                    println!("Failed to submit grid {}: {:#?}", id, err);
+                                    return false;
+                                }
+                            }
+                            return true;
+                        }
+
+                        let asset = *assets
+                            .get(&job.symbol)
+                            .expect("Failed to find asset");
+
+                        let venue = if job.paper {
+                            // the simulated orders only lived in the interrupted run, place the grid again
+                            job.orders.clear();
+                            job.pending.clear();
+
+                            Venue::Simulated(SimulatedExchange::default())
+                        } else {
+                            let wallet = Arc::new(
+                                match config
+                                    .private_key
+                                    .parse::<LocalWallet>() {
+                                        Ok(wallet) => wallet,
+                                        Err(_) => {
+                                            println!("Error: Invalid private key");
+                                            return false;
+                                        }
+                                    }
+                            );
+
+                            Venue::Live {
+                                info: &info,
+                                exchange: &exchange,
+                                wallet,
+                                journal: &journal,
+                            }
+                        };
+
+                        let feed = feed.clone();
+
+                        if let Err(err) = grid::run(job, &feed, venue, asset).await {
+                            println!("Grid stopped with error: {:#?}", err);
+                            return false;
+                        }
+                        return true;
+                    }
+
+                    // stop the running grid first so it doesn't flip another level
+                    job.status = GridStatus::Cancelled;
+                    if let Err(err) = job.save() {
+                        println!("Failed to update grid: {:#?}", err);
+                        return false;
+                    }
+
+                    if !job.paper {
+                        let wallet = Arc::new(
+                            match config
+                                .private_key
+                                .parse::<LocalWallet>() {
+                                    Ok(wallet) => wallet,
+                                    Err(_) => {
+                                        println!("Error: Invalid private key");
+                                        return false;
+                                    }
+                                }
+                        );
+
+                        let (_, asset) = *assets
+                            .get(&job.symbol)
+                            .expect("Failed to find asset");
+
+                        let mut venue = Venue::Live {
+                            info: &info,
+                            exchange: &exchange,
+                            wallet,
+                            journal: &journal,
+                        };
+
+                        let oids = job.orders.iter().map(|order| order.oid).collect();
+                        if let Err(err) = venue.cancel(asset, oids).await {
+                            println!("Failed to cancel grid orders: {:#?}", err);
+                            return false;
+                        }
+                    }
+
+                    println!("Grid {} successfully cancelled ✔️\n---", id);
+                }
+                _ => {
+                    println!("No matching pattern");
+                    return false;
+                }
+            }
+        }
+
+        Some(("dca", matches)) => match matches.subcommand() {
+            Some(("add", matches)) => {
+                let symbol = matches
+                    .get_one::<String>("asset")
+                    .expect("Asset is required");
+
+                if !assets.contains_key(&symbol.to_uppercase()) {
+                    println!("Failed to find asset: {}", symbol);
+                    return false;
+                }
+
+                let usd = matches
+                    .get_one::<String>("usd")
+                    .expect("Amount is required")
+                    .parse::<f64>()
+                    .expect("Failed to parse amount, expected a USD amount e.g 100");
+
+                let TimeSpan(every) = matches
+                    .get_one::<String>("every")
+                    .expect("Interval is required")
+                    .as_str()
+                    .try_into()
+                    .expect("Failed to parse interval, expected e.g 1d or 12h");
+
+                let at = matches.get_one::<String>("at").map(|at| {
+                    let TimeOfDay(at) = at
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse time of day, expected HH:MM e.g 14:00");
+
+                    at
+                });
+
+                let max_price = matches.get_one::<String>("max_price").map(|price| {
+                    let LimitPrice::Absolute(price) = price
+                        .as_str()
+                        .try_into()
+                        .expect("Failed to parse max price");
+
+                    price
+                });
+
+                let missed: MissedRuns = matches
+                    .get_one::<String>("missed")
+                    .expect("Missed-run policy has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid missed-run policy, expected skip, once or all");
+
+                if usd <= 0.0 {
+                    println!("Amount must be greater than 0");
+                    return false;
+                }
+
+                let job = match DcaJob::new(symbol, usd, every, at, max_price, missed) {
+                    Ok(job) => job,
+                    Err(err) => {
+                        println!("{}", err);
+                        return false;
+                    }
+                };
+
+                println!("Dca {} successfully saved ✔️", job.id);
+                job.print();
+            }
+            Some(("list", _)) => {
+                let jobs = DcaJob::list().expect("Failed to read dca jobs");
+
+                for job in jobs.iter() {
+                    job.print();
+
+                    let runs = journal.dca_runs(job.id, 3).expect("Failed to read dca runs");
+                    for run in runs.iter() {
+                        println!(
+                            "  {} {}: ${} {}",
+                            run.created_at,
+                            run.outcome,
+                            run.usd,
+                            match &run.detail {
+                                Some(detail) => detail.clone(),
+                                None => format!("filled {} at ${:.2}", run.filled_sz, run.filled_usd),
+                            }
+                        );
+                    }
+                }
+
+                println!("{}", "---".repeat(20));
+                println!("\nTotal Dca Jobs: {}", jobs.len());
+            }
+            Some(("remove", matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Dca id is required")
+                    .parse::<u64>()
+                    .expect("Failed to parse dca id");
+
+                match DcaJob::remove(id) {
+                    Ok(true) => println!("Dca {} successfully removed ✔️\n---", id),
+                    Ok(false) => {
+                        println!("No dca with id {}", id);
+                        return false;
+                    }
+                    Err(err) => {
+                        println!("Failed to remove dca: {:#?}", err);
+                        return false;
+                    }
+                }
+            }
+            Some(("run", matches)) => {
+                if matches.get_flag("daemon") {
+                    match daemon::submit(command_line, Job::Dca).await {
+                        Ok(id) => println!("Dca scheduler submitted to the daemon as job {} ✔️\n---", id),
+                        Err(err) => {
+                            println!("Failed to submit the dca scheduler: {:#?}", err);
+                            return false;
+                        }
+                    }
+                    return true;
+                }
+
+                let wallet = Arc::new(
+                    match config
+                        .private_key
+                        .parse::<LocalWallet>() {
+                            Ok(wallet) => wallet,
+                            Err(_) => {
+                                println!("Error: Invalid private key");
+                                return false;
+                            }
+                        }
+                );
+
+                let feed = feed.clone();
+
+                match dca::run_due(&feed, &exchange, wallet, &assets, &journal).await {
+                    Ok(executed) => println!("{} dca jobs were due", executed),
+                    Err(err) => {
+                        println!("Failed to run dca jobs: {:#?}", err);
+                        return false;
+                    }
+                }
+            }
+            _ => {
+                println!("Invalid command: expected commands: (add, list, remove, run)");
+                return false;
+            }
+        },
+
+        Some(("alert", matches)) => match matches.subcommand() {
+            Some(("add", matches)) => {
+                let condition = matches
+                    .get_many::<String>("condition")
+                    .expect("Condition is required")
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let condition: Condition = match condition.as_str().try_into() {
+                    Ok(condition) => condition,
+                    Err(err) => {
+                        println!("Invalid condition: {}", err);
+                        return false;
+                    }
+                };
+
+                for symbol in condition.symbols().iter() {
+                    if !assets.contains_key(symbol) {
+                        println!("Failed to find asset: {}", symbol);
+                        return false;
+                    }
+                }
+
+                let sinks = matches
+                    .get_many::<String>("notify")
+                    .expect("Sink has a default")
+                    .map(|sink| Sink::try_from(sink.as_str()))
+                    .collect::<Result<Vec<_>, _>>();
+
+                let sinks = match sinks {
+                    Ok(sinks) => sinks,
+                    Err(err) => {
+                        println!("{}", err);
+                        return false;
+                    }
+                };
+
+                let alert = match Alert::new(condition, sinks) {
+                    Ok(alert) => alert,
+                    Err(err) => {
+                        println!("Failed to create alert: {:#?}", err);
+                        return false;
+                    }
+                };
+
+                alert.print();
+                println!("{}", "---".repeat(20));
+                println!("Alert {} successfully added ✔️, it is checked while `hl alert watch` runs\n---", alert.id);
+            }
+            Some(("list", _)) => {
+                let alerts = Alert::list().expect("Failed to read alerts");
+
+                for alert in alerts.iter() {
+                    alert.print();
+                }
+
+                println!("{}", "---".repeat(20));
+                println!("\nTotal Alerts: {}", alerts.len());
+            }
+            Some(("remove", matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Alert id is required")
+                    .parse::<u64>()
+                    .expect("Failed to parse alert id");
+
+                match Alert::remove(id) {
+                    Ok(true) => println!("Alert {} successfully removed ✔️\n---", id),
+                    Ok(false) => {
+                        println!("No alert with id {}", id);
+                        return false;
+                    }
+                    Err(err) => {
+                        println!("Failed to remove alert: {:#?}", err);
+                        return false;
+                    }
+                }
+            }
+            Some(("test", matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Alert id is required")
+                    .parse::<u64>()
+                    .expect("Failed to parse alert id");
+
+                match Alert::load(id).expect("Failed to read alert") {
+                    Some(alert) => alert.notify("test notification").await,
+                    None => {
+                        println!("No alert with id {}", id);
+                        return false;
+                    }
+                }
+            }
+            Some(("watch", matches)) => {
+                if matches.get_flag("daemon") {
+                    match daemon::submit(command_line, Job::Alerts).await {
+                        Ok(id) => println!("Alert watcher submitted to the daemon as job {} ✔️\n---", id),
+                        Err(err) => {
+                            println!("Failed to submit the alert watcher: {:#?}", err);
+                            return false;
+                        }
+                    }
+                    return true;
+                }
+
+                let wallet = Arc::new(
+                    match config
+                        .private_key
+                        .parse::<LocalWallet>() {
+                            Ok(wallet) => wallet,
+                            Err(_) => {
+                                println!("Error: Invalid private key");
+                                return false;
+                            }
+                        }
+                );
+
+                let feed = feed.clone();
+
+                println!("Watching alerts, stop with ctrl-c\n---");
+
+                alert::watch(&feed, wallet).await;
+            }
+            _ => {
+                println!("Invalid command: expected commands: (add, list, remove, test, watch)");
+                return false;
+            }
+        },
+
+        Some(("shell", _)) => {
+            println!("Already in the shell");
+        }
+
+        Some(("run", _)) => {
+            println!("A batch can't run another batch");
+        }
+
+        Some(("strategy", matches)) => match matches.subcommand() {
+            Some(("run", matches)) => {
+                let file = matches.get_one::<String>("file").expect("File is required");
+                let symbol = matches
+                    .get_one::<String>("asset")
+                    .unwrap_or(&config.default_asset)
+                    .to_uppercase();
+
+                let TimeSpan(interval) = match matches
+                    .get_one::<String>("interval")
+                    .expect("Interval has a default")
+                    .as_str()
+                    .try_into() {
+                        Ok(interval) => interval,
+                        Err(err) => {
+                            println!("Invalid interval: {}", err);
+                            return false;
+                        }
+                    };
+
+                let asset = match assets.get(&symbol) {
+                    Some(asset) => *asset,
+                    None => {
+                        println!("Failed to find asset: {}", symbol);
+                        return false;
+                    }
+                };
+
+                let strategy = match Strategy::load(Path::new(file), &symbol) {
+                    Ok(strategy) => strategy,
+                    Err(err) => {
+                        println!("Failed to load strategy: {}", err);
+                        return false;
+                    }
+                };
+
+                let venue = if matches.get_flag("paper") {
+                    Venue::Simulated(SimulatedExchange::default())
+                } else {
+                    let wallet = Arc::new(
+                        match config
+                            .private_key
+                            .parse::<LocalWallet>() {
+                                Ok(wallet) => wallet,
+                                Err(_) => {
+                                    println!("Error: Invalid private key");
+                                    return false;
+                                }
+                            }
+                    );
+
+                    Venue::Live {
+                        info: &info,
+                        exchange: &exchange,
+                        wallet,
+                        journal: &journal,
+                    }
+                };
+
+                println!("Running {} on {} every {}s, ctrl-c stops it\n---", file, symbol, interval.as_secs());
+
+                if let Err(err) = strategy::run(strategy, &feed, venue, asset, interval).await {
+                    println!("Strategy stopped with error: {}", err);
+                    return false;
+                }
+            }
+            Some(("test", matches)) => {
+                let file = matches.get_one::<String>("file").expect("File is required");
+                let data = matches.get_one::<String>("data").expect("Data is required");
+                let symbol = matches
+                    .get_one::<String>("asset")
+                    .unwrap_or(&config.default_asset)
+                    .to_uppercase();
+
+                let asset = match assets.get(&symbol) {
+                    Some(asset) => *asset,
+                    None => {
+                        println!("Failed to find asset: {}", symbol);
+                        return false;
+                    }
+                };
+
+                let records = match strategy::load_records(Path::new(data)) {
+                    Ok(records) => records,
+                    Err(err) => {
+                        println!("Failed to read recorded data: {}", err);
+                        return false;
+                    }
+                };
+
+                let strategy = match Strategy::load(Path::new(file), &symbol) {
+                    Ok(strategy) => strategy,
+                    Err(err) => {
+                        println!("Failed to load strategy: {}", err);
+                        return false;
+                    }
+                };
+
+                println!("Replaying {} mark prices of {} through {}\n---", records.len(), symbol, file);
+
+                match strategy::test(strategy, &records, asset).await {
+                    Ok(()) => println!("---\nStrategy passed ✔️"),
+                    Err(err) => {
+                        println!("---\nStrategy failed: {}", err);
+                        return false;
+                    }
+                }
+            }
+            Some(("record", matches)) => {
+                let symbol = matches
+                    .get_one::<String>("asset")
+                    .expect("Asset is required")
+                    .to_uppercase();
+                let data = matches.get_one::<String>("data").expect("Data is required");
+
+                let TimeSpan(interval) = match matches
+                    .get_one::<String>("interval")
+                    .expect("Interval has a default")
+                    .as_str()
+                    .try_into() {
+                        Ok(interval) => interval,
+                        Err(err) => {
+                            println!("Invalid interval: {}", err);
+                            return false;
+                        }
+                    };
+
+                if !assets.contains_key(&symbol) {
+                    println!("Failed to find asset: {}", symbol);
+                    return false;
+                }
+
+                println!("Recording {} every {}s to {}, ctrl-c stops it\n---", symbol, interval.as_secs(), data);
+
+                if let Err(err) = strategy::record(&feed, &symbol, Path::new(data), interval).await {
+                    println!("Failed to record mark prices: {:#?}", err);
+                    return false;
+                }
+            }
+            _ => {
+                println!("Invalid command: expected commands: (run, test, record)");
+                return false;
+            }
+        },
+
+        Some(("serve", matches)) => {
+            let bind = match matches
+                .get_one::<String>("bind")
+                .expect("Bind address has a default")
+                .parse::<SocketAddr>() {
+                    Ok(bind) => bind,
+                    Err(_) => {
+                        println!("Invalid bind address, expected e.g 127.0.0.1:8080");
+                        return false;
+                    }
+                };
+
+            let token = match matches
+                .get_one::<String>("token")
+                .cloned()
+                .or_else(|| std::env::var("HL_API_TOKEN").ok())
+            {
+                Some(token) if token.trim().is_empty() => {
+                    println!("The API token can't be empty, pass --token or HL_API_TOKEN or leave both unset to generate one");
+                    return false;
+                }
+                Some(token) => token,
+                None => {
+                    let token = uuid::Uuid::new_v4().simple().to_string();
+                    println!("Generated API token: {}", token);
+                    token
+                }
+            };
+
+            let wallet = Arc::new(
+                match config
+                    .private_key
+                    .parse::<LocalWallet>() {
+                        Ok(wallet) => wallet,
+                        Err(_) => {
+                            println!("Error: Invalid private key");
+                            return false;
+                        }
+                    }
+            );
+
+            let api = Api::new(serve::Live::new(info.clone(), exchange.clone(), wallet), assets.clone(), profile, token);
+
+            if let Err(err) = serve::serve(api, bind).await {
+                println!("Server failed: {:#?}", err);
+                return false;
+            }
+        }
+
+        Some(("webhook", matches)) => {
+            let port = *matches.get_one::<u16>("port").expect("Port has a default");
+
+            let secret = match matches
+                .get_one::<String>("secret")
+                .cloned()
+                .or_else(|| std::env::var("HL_WEBHOOK_SECRET").ok())
+            {
+                Some(secret) if secret.trim().is_empty() => {
+                    println!("The webhook secret can't be empty, pass --secret or HL_WEBHOOK_SECRET or leave both unset to generate one");
+                    return false;
+                }
+                Some(secret) => secret,
+                None => {
+                    let secret = uuid::Uuid::new_v4().simple().to_string();
+                    println!("Generated webhook secret: {}", secret);
+                    secret
+                }
+            };
+
+            let TimeSpan(dedup_window) = match matches
+                .get_one::<String>("dedup-window")
+                .expect("Dedup window has a default")
+                .as_str()
+                .try_into() {
+                    Ok(dedup_window) => dedup_window,
+                    Err(err) => {
+                        println!("Invalid dedup window: {}", err);
+                        return false;
+                    }
+                };
+
+            let max_per_minute = *matches
+                .get_one::<usize>("max-per-minute")
+                .expect("Max per minute has a default");
+
+            let wallet = Arc::new(
+                match config
+                    .private_key
+                    .parse::<LocalWallet>() {
+                        Ok(wallet) => wallet,
+                        Err(_) => {
+                            println!("Error: Invalid private key");
+                            return false;
+                        }
+                    }
+            );
+
+            let cross = matches!(config.default_margin, MarginType::Cross);
+
+            let receiver = Receiver::new(
+                info.clone(),
+                exchange.clone(),
+                wallet,
+                assets.clone(),
+                profile,
+                secret,
+                cross,
+                dedup_window,
+                max_per_minute,
+            );
+
+            if let Err(err) = webhook::listen(receiver, SocketAddr::from(([0, 0, 0, 0], port))).await {
+                println!("Webhook receiver failed: {:#?}", err);
+                return false;
+            }
+        }
+
+        Some(("rebalance", matches)) => {
+            let Targets(targets) = matches
+                .get_one::<String>("targets")
+                .expect("Targets are required")
+                .as_str()
+                .try_into()
+                .expect("Failed to parse targets, expected asset=weight pairs e.g btc=40%,eth=-30%");
+
+            let notional = matches
+                .get_one::<String>("notional")
+                .expect("Notional is required")
+                .parse::<f64>()
+                .expect("Failed to parse notional, expected a USD amount e.g 10000");
+
+            let Percent(band) = matches
+                .get_one::<String>("band")
+                .expect("Band has a default")
+                .as_str()
+                .try_into()
+                .expect("Invalid band, expected a percentage e.g 2%");
+
+            if notional <= 0.0 {
+                println!("Notional must be greater than 0");
+                return false;
+            }
+
+            let wallet = Arc::new(
+                match config
+                    .private_key
+                    .parse::<LocalWallet>() {
+                        Ok(wallet) => wallet,
+                        Err(_) => {
+                            println!("Error: Invalid private key");
+                            return false;
+                        }
+                    }
+            );
+
+            let deltas = match rebalance::deltas(&info, &wallet, &assets, &targets, notional, band).await {
+                Ok(deltas) => deltas,
+                Err(err) => {
+                    println!("{}", err);
+                    return false;
+                }
+            };
+
+            rebalance::preview(&deltas, notional);
+
+            if preview || matches.get_flag("preview") {
+                return true;
+            }
+
+            let orders = rebalance::orders(&deltas, matches.get_flag("limit"));
+            if orders.is_empty() {
+                println!("Every asset is within its band, nothing to rebalance");
+                return true;
+            }
+
+            match place_batch(&exchange, wallet.clone(), orders, &journal).await {
+                Ok(statuses) => {
+                    statuses.iter().for_each(|status| match status {
+                        Status::Filled(order) => {
+                            println!("Order {} was successfully filled.", order.oid);
+                        }
+                        Status::Resting(order) => {
+                            println!("Order {} was successfully placed.", order.oid);
+                        }
+                        _ => {}
+                    });
+
+                    println!("\nRebalanced {} assets ✔️", statuses.len());
+                }
+                Err(err) => {
+                    println!("{:#?}", err);
+                    return false;
+                }
+            }
+        }
+
+        Some(("when", matches)) => {
+            let condition: Condition = match matches
+                .get_one::<String>("condition")
+                .expect("Condition is required")
+                .as_str()
+                .try_into() {
+                    Ok(condition) => condition,
+                    Err(err) => {
+                        println!("Invalid condition: {}", err);
+                        return false;
+                    }
+                };
+
+            let symbol = matches
+                .get_one::<String>("asset")
+                .expect("Asset is required")
+                .to_uppercase();
+
+            for symbol in condition.symbols().iter().chain([&symbol]) {
+                if !assets.contains_key(symbol) {
+                    println!("Failed to find asset: {}", symbol);
+                    return false;
+                }
+            }
+
+            let order_size: OrderSize = matches
+                .get_one::<String>("size")
+                .expect("Order size is required")
+                .as_str()
+                .try_into()
+                .expect("Failed to parse order size");
+
+            let limit_px = matches
+                .get_one::<String>("price")
+                .map(|price| price.parse::<f64>().expect("Invalid limit price"));
+
+            let expires_at = matches.get_one::<String>("expire").map(|expire| {
+                let TimeSpan(expire) = expire
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid expiry, expected e.g 30m, 4h or 1d");
+
+                twap::now() + expire.as_secs()
+            });
+
+            let wallet = Arc::new(
+                match config
+                    .private_key
+                    .parse::<LocalWallet>() {
+                        Ok(wallet) => wallet,
+                        Err(_) => {
+                            println!("Error: Invalid private key");
+                            return false;
+                        }
+                    }
+            );
+
+            let size = match order_size {
+                OrderSize::Absolute(sz) => sz,
+                OrderSize::Percent(sz) => {
+                    let state = info
+                        .user_state(wallet.address())
+                        .await
+                        .expect("Failed to fetch balance");
+
+                    let balance = match config.default_margin {
+                        MarginType::Cross => state.cross_margin_summary.account_value,
+                        MarginType::Isolated => state.margin_summary.account_value,
+                    };
+
+                    let balance = balance.parse::<f64>().expect("Failed to parse balance");
+
+                    balance * (sz as f64 / 100.0)
+                }
+            };
+
+            let job = WhenJob {
+                condition,
+                symbol,
+                is_buy: matches.get_one::<String>("side").expect("Side is required") == "buy",
+                size,
+                limit_px,
+                expires_at,
+            };
+
+            if matches.get_flag("daemon") {
+                match daemon::submit(command_line, Job::When(job.clone())).await {
+                    Ok(id) => println!("{} submitted to the daemon as job {} ✔️\n---", job.describe(), id),
+                    Err(err) => {
+                        println!("Failed to submit {}: {:#?}", job.describe(), err);
+                        return false;
+                    }
+                }
+                return true;
+            }
+
+            let feed = feed.clone();
+
+            when::run(job, &feed, &exchange, wallet, &assets, &journal).await;
+        }
+
+        Some(("basket", matches)) => match matches.subcommand() {
+            Some((side @ ("buy" | "sell"), matches)) => {
+                let size: f64 = match matches
+                    .get_one::<String>("size")
+                    .expect("Order size required")
+                    .as_str()
+                    .try_into()
+                    .expect("Failed to parse order size")
+                {
+                    OrderSize::Absolute(size) => size,
+                    _ => {
+                        println!("{}", "-".repeat(35));
+
+                        println!("\nOnly absolute order size is supported for now");
+                        return false;
+                    }
+                };
+
+                let BasketLegs(legs) = matches
+                    .get_one::<String>("legs")
+                    .expect("Basket legs are required")
+                    .as_str()
+                    .try_into()
+                    .expect("Failed to parse basket legs, expected asset:weight pairs e.g btc:+2,sol:-3");
+
+                let trigger = if matches.get_flag("index") {
+                    BasketTrigger::Index
+                } else {
+                    BasketTrigger::Pnl
+                };
+
+                // pnl targets are in USD or a percentage of the size, the sl is a loss
+                let parse_target = |value: &String| -> f64 {
+                    match trigger {
+                        BasketTrigger::Index => value
+                            .parse::<f64>()
+                            .expect("Invalid index value, expected a number"),
+                        BasketTrigger::Pnl => match value.strip_suffix("%") {
+                            Some(percent) => {
+                                size * percent.parse::<f64>().expect("Invalid percentage") / 100.0
+                            }
+                            None => value
+                                .parse::<f64>()
+                                .expect("Invalid PnL, expected a USD amount or a percentage e.g 5%"),
+                        },
+                    }
+                };
+
+                let tp = matches.get_one::<String>("tp").map(parse_target);
+                let sl = matches.get_one::<String>("sl").map(parse_target).map(|sl| match trigger {
+                    BasketTrigger::Pnl => -sl.abs(),
+                    BasketTrigger::Index => sl,
+                });
+
+                let recovery: Recovery = matches
+                    .get_one::<String>("on_leg_failure")
+                    .expect("Leg recovery has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid leg recovery, expected retry or unwind");
+
+                let Percent(max_slippage) = matches
+                    .get_one::<String>("max_slippage")
+                    .expect("Max slippage has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid max slippage, expected a percentage e.g 1%");
+
+                let job = BasketJob {
+                    legs,
+                    is_buy: side == "buy",
+                    size,
+                    tp,
+                    sl,
+                    trigger,
+                    recovery,
+                    max_slippage,
+                };
+
+                if matches.get_flag("daemon") {
+                    match daemon::submit(command_line, Job::Basket(job.clone())).await {
+                        Ok(id) => println!("{} submitted to the daemon as job {} ✔️\n---", job.describe(), id),
+                        Err(err) => {
+                            println!("Failed to submit {}: {:#?}", job.describe(), err);
+                            return false;
+                        }
+                    }
+                    return true;
+                }
+
+                let wallet = Arc::new(
+                    match config
+                        .private_key
+                        .parse::<LocalWallet>() {
+                            Ok(wallet) => wallet,
+                            Err(_) => {
+                                println!("Error: Invalid private key");
+                                return false;
+                            }
+                        }
+                );
+
+                let feed = feed.clone();
+
+                basket::run(job, &feed, &exchange, wallet, &assets, &journal).await;
+            }
+
+            _ => {
+                println!("Invalid command: expected commands: (buy, sell)");
+                return false;
+            }
+        },
+
+        Some(("view", matches)) => match matches.subcommand_name() {
+            Some("upnl") => {
+                let wallet = Arc::new(
+                    match config
+                        .private_key
+                        .parse::<LocalWallet>() {
+                            Ok(wallet) => wallet,
+                            Err(_) => {
+                                println!("Error: Invalid private key");
+                                return false;
+                            }
+                        }
+                );
+
+                let state = info
+                    .user_state(wallet.address())
+                    .await
+                    .expect("Failed to fetch unrealized pnl");
 
                 let open_positions = state
                     .asset_positions
@@ -1654,7 +3300,7 @@ pub async fn startup(config: &mut Config) {
                             Ok(wallet) => wallet,
                             Err(_) => {
                                 println!("Error: Invalid private key");
-                                return;
+                                return false;
                             }
                         }
                 );
@@ -1694,7 +3340,7 @@ pub async fn startup(config: &mut Config) {
                             Ok(wallet) => wallet,
                             Err(_) => {
                                 println!("Error: Invalid private key");
-                                return;
+                                return false;
                             }
                         }
                 );
@@ -1720,697 +3366,54 @@ pub async fn startup(config: &mut Config) {
                         .parse::<LocalWallet>() {
                             Ok(wallet) => wallet,
                             Err(_) => {
-                                println!("Error: Invalid private key");
-                                return;
-                            }
-                        }
-                );
-                let state = info.user_state(wallet.address()).await.unwrap();
-
-                let open_positions = state
-                    .asset_positions
-                    .iter()
-                    .filter(|ap| ap.position.entry_px.is_some())
-                    .collect::<Vec<_>>();
-
-                let repeat = 35;
-                for op in open_positions.iter() {
-                    let entry_position = op.position.entry_px.as_ref().unwrap();
-
-                    println!("{}", format!("{}", "_".repeat(repeat)));
-                    println!();
-                    println!("Asset: {}", op.position.coin);
-                    println!("Entry Price: {:#?}", entry_position);
-                    println!("Position Size: {}", format!("{}", op.position.szi));
-                    println!(
-                        "Position Value: {}",
-                        format!("${}", op.position.position_value)
-                    );
-                    println!(
-                        "Return on Equity: {}",
-                        format!("{}%", op.position.return_on_equity)
-                    );
-                    println!(
-                        "Unrealized Pnl: {}",
-                        format!("${}", op.position.unrealized_pnl)
-                    );
-                }
-
-                println!("{}", format!("{}", "_".repeat(repeat)));
-                println!("\nTotal Open Positions: {}", open_positions.len());
-            }
-            _ => {
-                println!(
-                        " Invalid command: expected commands: (view upnl, view wallet balance, view unfilled orders, view open positions"
-                    );
-            }
-        },
-
-        Some(("pair", matches)) => match matches.subcommand() {
-            Some(("buy", matches)) => {
-                let sz: f64 = match matches
-                    .get_one::<String>("size")
-                    .expect("Order size required")
-                    .as_str()
-                    .try_into()
-                    .expect("Failed to parse order size")
-                {
-                    OrderSize::Absolute(sz) => sz,
-                    _ => {
-                        println!("{}", "-".repeat(35));
-
-                        println!("\nOnly absolute order size is supported for now");
-                        return;
-                    }
-                };
-
-                let pair: Pair = matches
-                    .get_one::<String>("pair")
-                    .expect("Pair is required")
-                    .as_str()
-                    .try_into()
-                    .expect("Failed to parse pair");
-
-                let limit_price: LimitPrice = matches
-                    .get_one::<String>("price")
-                    .unwrap_or(&"@0".to_string())
-                    .as_str()
-                    .try_into()
-                    .expect("Failed to parse limit price");
-
-                let tp: Option<f64> = matches.get_one::<String>("tp").map(|price| {
-                        price.parse::<f64>().expect(
-                            "Invalid take profit value, expected a number or a percentage value e.g 10%",
-                        )
-                    });
-
-                let sl: Option<f64> = matches.get_one::<String>("sl").map(|price| {
-                    price.parse::<f64>().expect(
-                        "Invalid stop loss value, expected a number or a percentage value e.g 10%",
-                    )
-                });
-
-                let wallet = Arc::new(
-                    match config
-                        .private_key
-                        .parse::<LocalWallet>() {
-                            Ok(wallet) => wallet,
-                            Err(_) => {
-                                println!("Error: Invalid private key");
-                                return;
-                            }
-                        }
-                );
-
-                // ----------------------------------------------
-                let slippage = 3.0 / 100.0;
-
-                let base_sz = sz / 2.0;
-                let quote_sz = sz / 2.0;
-
-                let (base_sz_decimals, base_asset) = *assets
-                    .get(&pair.base.to_uppercase())
-                    .expect(&format!("Failed to find base asset:  {}", pair.base));
-
-                let (quote_sz_decimals, quote_asset) = *assets
-                    .get(&pair.quote.to_uppercase())
-                    .expect(&format!("Failed to find quote asset:  {}", pair.quote));
-
-                match limit_price {
-                    LimitPrice::Absolute(target) => {
-                        if target == 0.0 {
-                            // Takes 50% of order size and longs Asset X and
-                            {
-                                let asset_ctxs = info
-                                    .contexts()
-                                    .await
-                                    .expect("Failed to fetch asset ctxs");
-
-                                let asset_ctx =
-                                asset_ctx(&asset_ctxs,&pair.base)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!("Failed to find base asset:  {}", pair.base));
-
-                                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
-                                let limit_price = market_price * (1.0 + slippage);
-
-                                let sz = base_sz / market_price;
-
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: base_asset,
-                                    is_buy: true,
-                                    limit_px: parse_price(limit_price),
-                                    sz: parse_size(sz, base_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 1 of 2");
-                                println!("Side: Buy");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.base,
-                                    parse_size(sz, base_sz_decimals)
-                                );
-                                println!("Size in USD: {}", parse_size(base_sz, base_sz_decimals));
-                                println!("Market price: {}\n", market_price);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                    }
-                                }    
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
-
-                            // takes another 50% of order size and shorts Asset Y
-                            {
-                                let asset_ctxs = info
-                                    .contexts()
-                                    .await
-                                    .expect("Failed to fetch asset ctxs");
-
-                                let asset_ctx =
-                                asset_ctx(&asset_ctxs,&pair.quote)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!(
-                                        "Failed to find quote asset:  {}",
-                                        pair.quote
-                                    ));
-
-                                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
-                                let limit_price = market_price * (1.0 - slippage);
-
-                                let sz = quote_sz / market_price;
-
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: quote_asset,
-                                    is_buy: false,
-                                    limit_px: parse_price(limit_price),
-                                    sz: parse_size(sz, quote_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 2 of 2");
-                                println!("Side: Sell");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.quote,
-                                    parse_size(sz, quote_sz_decimals)
-                                );
-                                println!(
-                                    "Size in USD: {}",
-                                    parse_size(quote_sz, quote_sz_decimals)
-                                );
-                                println!("Market price: {}\n", market_price);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => 
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
-                        } else {
-                            // If limit price for eth/btc is .06, wait for the eth/btc ratio to become .06,
-                            // then long eth and short btc at market
-                            let (
-                                base_sz,
-                                base_market_price,
-                                quote_sz,
-                                quote_market_price,
-                                current_ratio,
-                            ) = loop {
-                                let asset_ctxs = info
-                                    .contexts()
-                                    .await
-                                    .expect("Failed to fetch asset ctxs");
-
-                                let base_limit_price = {
-                                    let base_asset_ctx =
-                                        asset_ctx(&asset_ctxs,&pair.base)
-                                        .expect("Failed to fetch asset ctxs")
-                                        .expect(&format!(
-                                            "Failed to find quote asset:  {}",
-                                            pair.quote
-                                        ));
-                                    base_asset_ctx.mark_px.parse::<f64>().unwrap()
-                                };
-
-                                let quote_market_price = {
-                                    let quote_asset_ctx =
-                                        asset_ctx(&asset_ctxs,&pair.quote)
-                                        .expect("Failed to fetch asset ctxs")
-                                        .expect(&format!(
-                                            "Failed to find quote asset:  {}",
-                                            pair.quote
-                                        ));
-
-                                    quote_asset_ctx.mark_px.parse::<f64>().unwrap()
-                                };
-
-                                let current_ratio =
-                                    format!("{:.2}", base_limit_price / quote_market_price)
-                                        .parse::<f64>()
-                                        .unwrap();
-
-                                if current_ratio >= target {
-                                    println!("Ratio reached: {} >= {}", current_ratio, target);
-                                    let base_sz = base_sz / base_limit_price;
-                                    let quote_sz = quote_sz / quote_market_price;
-
-                                    break (
-                                        base_sz,
-                                        base_limit_price,
-                                        quote_sz,
-                                        quote_market_price,
-                                        current_ratio,
-                                    );
-                                }
-
-                                println!(
-                                    "Current Ratio: {}, Target Ratio: {}, Diff: {}. Checking again in 5 seconds\n---",
-                                    format!("{:.2}", current_ratio).parse::<f64>().unwrap(),
-                                    format!("{:.2}", target).parse::<f64>().unwrap(),
-                                    format!("{:.2}", current_ratio - target).parse::<f64>().unwrap(),
-                                );
-                                tokio::time::sleep(Duration::from_secs(5)).await;
-                            };
-
-                            // send buy order request
-                            {
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: base_asset,
-                                    is_buy: true,
-                                    limit_px: parse_price(base_market_price * (1.0 + slippage)),
-                                    sz: parse_size(base_sz, base_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 1 of 2");
-                                println!("Side: Buy");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.base,
-                                    parse_size(base_sz, base_sz_decimals)
-                                );
-                                println!(
-                                    "Size in USD: {}",
-                                    parse_size(base_sz * base_market_price, base_sz_decimals)
-                                );
-                                println!("Market price: {}\n", base_market_price);
-                                println!("Ratio: {}\n", current_ratio);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => {
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
-                            // send sell order request
-                            {
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: quote_asset,
-                                    is_buy: false,
-                                    limit_px: parse_price(quote_market_price * (1.0 - slippage)),
-                                    sz: parse_size(quote_sz, quote_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 2 of 2");
-                                println!("Side: Sell");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.quote,
-                                    parse_size(quote_sz, quote_sz_decimals)
-                                );
-                                println!(
-                                    "Size in USD: {}",
-                                    parse_size(quote_sz * quote_market_price, quote_sz_decimals)
-                                );
-                                println!("Market price: {}\n", quote_market_price);
-                                println!("Ratio: {}\n", current_ratio);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => 
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
-                        }
-
-                        if tp.is_none() && sl.is_none() {
-                            return;
-                        };
-
-                        println!("Monitoring positions for tp or sl\n---");
-
-                        let (exit_long_order, exit_short_order, current_ratio) = loop {
-                            let asset_ctxs = info
-                                .contexts()
-                                .await
-                                .expect("Failed to fetch asset ctxs");
-                            
-                            let base_market_price = {
-                                let base_asset_ctx =
-                                    asset_ctx(&asset_ctxs,&pair.base)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!(
-                                        "Failed to find quote asset:  {}",
-                                        pair.quote
-                                    ));
-                                base_asset_ctx.mark_px.parse::<f64>().unwrap()
-                            };
-
-                            let quote_market_price = {
-                                let quote_asset_ctx =
-                                    asset_ctx(&asset_ctxs,&pair.quote)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!(
-                                        "Failed to find quote asset:  {}",
-                                        pair.quote
-                                    ));
-
-                                quote_asset_ctx.mark_px.parse::<f64>().unwrap()
-                            };
-
-                            let current_ratio =
-                                format!("{:.2}", base_market_price / quote_market_price)
-                                    .parse::<f64>()
-                                    .unwrap();
-
-                            // check if tp or sl has been reached
-                            if let Some(tp) = tp {
-                                if current_ratio >= tp {
-                                    println!("Take profit reached: {} >= {}", current_ratio, tp);
-
-                                    let exit_long_order = OrderRequest {
-                                        cloid: None,
-                                        asset: base_asset,
-                                        is_buy: false,
-                                        limit_px: parse_price(
-                                            base_market_price * (1.0 - slippage),
-                                        ),
-                                        sz: parse_size(base_sz, base_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    let exit_short_order = OrderRequest {
-                                        cloid: None,
-                                        asset: quote_asset,
-                                        is_buy: true,
-                                        limit_px: parse_price(
-                                            quote_market_price * (1.0 + slippage),
-                                        ),
-                                        sz: parse_size(quote_sz, quote_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    break (exit_long_order, exit_short_order, current_ratio);
-                                }
-                            }
-
-                            if let Some(sl) = sl {
-                                if current_ratio <= sl {
-                                    println!("Stop loss reached: {} <= {}", current_ratio, sl);
-
-                                    let exit_long_order = OrderRequest {
-                                        cloid: None,
-                                        asset: base_asset,
-                                        is_buy: false,
-                                        limit_px: parse_price(
-                                            base_market_price * (1.0 - slippage),
-                                        ),
-                                        sz: parse_size(base_sz, base_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    let exit_short_order = OrderRequest {
-                                        cloid: None,
-                                        asset: quote_asset,
-                                        is_buy: true,
-                                        limit_px: parse_price(
-                                            quote_market_price * (1.0 + slippage),
-                                        ),
-                                        sz: parse_size(quote_sz, quote_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    break (exit_long_order, exit_short_order, current_ratio);
-                                }
-                            }
-
-                            println!(
-                                "Current Ratio: {}, Target Ratio Tp: {}, Target Ratio Sl: {}, Tp Diff: {}, Sl Diff: {}. Checking again in 5 seconds\n---",
-                                format!("{:.2}", current_ratio).parse::<f64>().unwrap(),
-                                format!("{:.2}", tp.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                                format!("{:.2}", sl.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                                format!("{:.2}", current_ratio - tp.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                                format!("{:.2}", current_ratio - sl.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                            );
-
-                            tokio::time::sleep(Duration::from_secs(5)).await;
-                        };
-
-                        // place exit orders
-                        println!("{}", "---".repeat(20));
-                        println!("Order 1 of 2");
-                        println!("Side: Sell");
-                        println!(
-                            "Size in {}: {}",
-                            pair.base,
-                            parse_size(base_sz, base_sz_decimals)
-                        );
-                        println!("Ratio: {}\n", current_ratio);
-
-                        match exchange.place_order(wallet.clone(), vec![exit_long_order], None).await {
-                            Ok(order) => match order {
-                                Response::Err(err) => {
-                                    println!("{:#?}", err);
-                                    return;
-                                
-                                }
-                                
-                                Response::Ok(order) => {
-                                    match order.data.expect("expected order response data") {
-                                        StatusType::Statuses(statuses) => {
-                                            statuses.iter().for_each(|status| match status {
-                                        Status::Filled(order) => {
-                                            println!(
-                                                "Order {} was successfully filled.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Resting(order) => {
-                                            println!(
-                                                "Order {} was successfully placed.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Error(msg) => {
-                                            println!("Order failed with error: {:#?}\n", msg)
-                                        }
-                                        _ =>  unreachable!(),
-                                    });
-                                },
-                                _ =>  unreachable!(),
-                            }
-                            }},
-                            Err(err) => {
-                                println!("{:#?}", err);
-                                return;
+                                println!("Error: Invalid private key");
+                                return false;
                             }
                         }
+                );
+                let state = info.user_state(wallet.address()).await.unwrap();
 
-                        println!("{}", "---".repeat(20));
-                        println!("Order 2 of 2");
-                        println!("Side: Buy");
-                        println!(
-                            "Size in {}: {}",
-                            pair.quote,
-                            parse_size(quote_sz, quote_sz_decimals)
-                        );
-                        println!("Ratio: {}\n", current_ratio);
-
-                        match exchange.place_order(wallet.clone(), vec![exit_short_order], None).await {
-                            Ok(order) => match order {
-                                Response::Err(err) => {
-                                    println!("{:#?}", err);
-                                    return;
-                                
-                                }
-                                
-                                Response::Ok(order) => {
-                                    match order.data.expect("expected order response data") {
-                                        StatusType::Statuses(statuses) => {
-                                            statuses.iter().for_each(|status| match status {
-                                        Status::Filled(order) => {
-                                            println!(
-                                                "Order {} was successfully filled.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Resting(order) => {
-                                            println!(
-                                                "Order {} was successfully placed.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Error(msg) => {
-                                            println!("Order failed with error: {:#?}\n", msg)
-                                        }
-                                        _ =>  unreachable!(),
-                                    });
-                                },
-                                _ =>  unreachable!(),
-                            }
-                            }},
-                            Err(err) => {
-                                println!("{:#?}", err);
-                                return;
-                            }
-                        }
-                    }
+                let open_positions = state
+                    .asset_positions
+                    .iter()
+                    .filter(|ap| ap.position.entry_px.is_some())
+                    .collect::<Vec<_>>();
+
+                let repeat = 35;
+                for op in open_positions.iter() {
+                    let entry_position = op.position.entry_px.as_ref().unwrap();
+
+                    println!("{}", format!("{}", "_".repeat(repeat)));
+                    println!();
+                    println!("Asset: {}", op.position.coin);
+                    println!("Entry Price: {:#?}", entry_position);
+                    println!("Position Size: {}", format!("{}", op.position.szi));
+                    println!(
+                        "Position Value: {}",
+                        format!("${}", op.position.position_value)
+                    );
+                    println!(
+                        "Return on Equity: {}",
+                        format!("{}%", op.position.return_on_equity)
+                    );
+                    println!(
+                        "Unrealized Pnl: {}",
+                        format!("${}", op.position.unrealized_pnl)
+                    );
                 }
+
+                println!("{}", format!("{}", "_".repeat(repeat)));
+                println!("\nTotal Open Positions: {}", open_positions.len());
+            }
+            _ => {
+                println!(
+                        " Invalid command: expected commands: (view upnl, view wallet balance, view unfilled orders, view open positions"
+                    );
             }
-            Some(("sell", matches)) => {
+        },
+
+        Some(("pair", matches)) => match matches.subcommand() {
+            Some((side @ ("buy" | "sell"), matches)) => {
                 let sz: f64 = match matches
                     .get_one::<String>("size")
                     .expect("Order size required")
@@ -2423,7 +3426,7 @@ pub async fn startup(config: &mut Config) {
                         println!("{}", "-".repeat(35));
 
                         println!("\nOnly absolute order size is supported for now");
-                        return;
+                        return false;
                     }
                 };
 
@@ -2441,17 +3444,103 @@ pub async fn startup(config: &mut Config) {
                     .try_into()
                     .expect("Failed to parse limit price");
 
+                let tp: Option<f64> = matches.get_one::<String>("tp").map(|price| {
+                        price.parse::<f64>().expect(
+                            "Invalid take profit value, expected a number or a percentage value e.g 10%",
+                        )
+                    });
+
                 let sl: Option<f64> = matches.get_one::<String>("sl").map(|price| {
                     price.parse::<f64>().expect(
                         "Invalid stop loss value, expected a number or a percentage value e.g 10%",
                     )
                 });
 
-                let tp: Option<f64> = matches.get_one::<String>("tp").map(|price| {
-                    price.parse::<f64>().expect(
-                        "Invalid take profit value, expected a number or a percentage value e.g 10%",
-                    )
+                let hedge: Hedge = matches
+                    .get_one::<String>("hedge")
+                    .expect("Hedge has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid hedge, expected equal, beta or vol");
+
+                let spread: Spread = matches
+                    .get_one::<String>("spread")
+                    .expect("Spread has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid spread, expected ratio or log");
+
+                let precision = matches
+                    .get_one::<String>("precision")
+                    .expect("Precision has a default")
+                    .parse::<usize>()
+                    .expect("Invalid precision, expected a number of decimals");
+
+                let entry_z: Option<f64> = matches.get_one::<String>("entry_z").map(|z| {
+                    z.parse::<f64>().expect("Invalid entry z-score, expected a number e.g 2")
+                });
+
+                let exit_z: Option<f64> = matches.get_one::<String>("exit_z").map(|z| {
+                    z.parse::<f64>().expect("Invalid exit z-score, expected a number e.g 0.5")
                 });
+
+                let lookback = matches
+                    .get_one::<String>("lookback")
+                    .expect("Lookback has a default")
+                    .parse::<u64>()
+                    .expect("Invalid lookback, expected a number of hourly candles");
+
+                if lookback < 2 {
+                    println!("The lookback needs at least 2 candles");
+                    return false;
+                }
+
+                let recovery: Recovery = matches
+                    .get_one::<String>("on_leg_failure")
+                    .expect("Leg recovery has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid leg recovery, expected retry or unwind");
+
+                let Percent(max_slippage) = matches
+                    .get_one::<String>("max_slippage")
+                    .expect("Max slippage has a default")
+                    .as_str()
+                    .try_into()
+                    .expect("Invalid max slippage, expected a percentage e.g 1%");
+
+                let job = PairJob {
+                    base: pair.base,
+                    quote: pair.quote,
+                    is_buy: side == "buy",
+                    size: sz,
+                    target: match limit_price {
+                        LimitPrice::Absolute(target) if matches.contains_id("price") => Some(target),
+                        _ => None,
+                    },
+                    tp,
+                    sl,
+                    hedge,
+                    spread,
+                    precision,
+                    entry_z: entry_z.map(f64::abs),
+                    exit_z: exit_z.map(f64::abs),
+                    lookback,
+                    recovery,
+                    max_slippage,
+                };
+
+                if matches.get_flag("daemon") {
+                    match daemon::submit(command_line, Job::Pair(job.clone())).await {
+                        Ok(id) => println!("{} submitted to the daemon as job {} ✔️\n---", job.describe(), id),
+                        Err(err) => {
+                            println!("Failed to submit {}: {:#?}", job.describe(), err);
+                            return false;
+                        }
+                    }
+                    return true;
+                }
+
                 let wallet = Arc::new(
                     match config
                         .private_key
@@ -2459,606 +3548,337 @@ pub async fn startup(config: &mut Config) {
                             Ok(wallet) => wallet,
                             Err(_) => {
                                 println!("Error: Invalid private key");
-                                return;
+                                return false;
                             }
                         }
                 );
 
-                // ----------------------------------------------
-                let slippage = 3.0 / 100.0;
-
-                let base_sz = sz / 2.0;
-                let quote_sz = sz / 2.0;
-
-                let (base_sz_decimals, base_asset) = *assets
-                    .get(&pair.base.to_uppercase())
-                    .expect(&format!("Failed to find base asset:  {}", pair.base));
-
-                let (quote_sz_decimals, quote_asset) = *assets
-                    .get(&pair.quote.to_uppercase())
-                    .expect(&format!("Failed to find quote asset:  {}", pair.quote));
-
-                match limit_price {
-                    LimitPrice::Absolute(target) => {
-                        if target == 0.0 {
-                            // Takes 50% of order size and shorts Asset X and
-                            {
-                                let asset_ctxs = info
-                                    .contexts()
-                                    .await
-                                    .expect("Failed to fetch asset ctxs");
-
-                                let asset_ctx =
-                                asset_ctx(&asset_ctxs,&pair.base)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!("Failed to find base asset:  {}", pair.base));
-
-                                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
-                                let limit_price = market_price * (1.0 - slippage);
-
-                                let sz = base_sz / market_price;
-
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: base_asset,
-                                    is_buy: false,
-                                    limit_px: parse_price(limit_price),
-                                    sz: parse_size(sz, base_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 1 of 2");
-                                println!("Side: Sell");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.base,
-                                    parse_size(sz, base_sz_decimals)
-                                );
-                                println!("Size in USD: {}", parse_size(base_sz, base_sz_decimals));
-                                println!("Market price: {}\n", market_price);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => 
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
+                let feed = feed.clone();
 
-                            // takes another 50% of order size and longs Asset Y
-                            {
-                                let asset_ctxs = info
-                                    .contexts()
-                                    .await
-                                    .expect("Failed to fetch asset ctxs");
-
-                                let asset_ctx =
-                                asset_ctx(&asset_ctxs,&pair.quote)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!(
-                                        "Failed to find quote asset:  {}",
-                                        pair.quote
-                                    ));
-
-                                let market_price = asset_ctx.mark_px.parse::<f64>().unwrap();
-                                let limit_price = market_price * (1.0 + slippage);
-
-                                let sz = quote_sz / market_price;
-
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: quote_asset,
-                                    is_buy: true,
-                                    limit_px: parse_price(limit_price),
-                                    sz: parse_size(sz, quote_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 2 of 2");
-                                println!("Side: Buy");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.quote,
-                                    parse_size(sz, quote_sz_decimals)
-                                );
-                                println!(
-                                    "Size in USD: {}",
-                                    parse_size(quote_sz, quote_sz_decimals)
-                                );
-                                println!("Market price: {}\n", market_price);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => 
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
-                        } else {
-                            // If limit price for eth/btc is .06, wait for the eth/btc ratio to become .06,
-                            // then short eth and long btc at market
-
-                            let (
-                                base_sz,
-                                base_market_price,
-                                quote_sz,
-                                quote_market_price,
-                                current_ratio,
-                            ) = loop {
-                                let asset_ctxs = info
-                                    .contexts()
-                                    .await
-                                    .expect("Failed to fetch asset ctxs");
-
-                                let base_market_price = {
-                                    let base_asset_ctx =
-                                        asset_ctx(&asset_ctxs,&pair.base)
-                                        .expect("Failed to fetch asset ctxs")
-                                        .expect(&format!(
-                                            "Failed to find quote asset:  {}",
-                                            pair.quote
-                                        ));
-                                    base_asset_ctx.mark_px.parse::<f64>().unwrap()
-                                };
-
-                                let quote_market_price = {
-                                    let quote_asset_ctx =
-                                        asset_ctx(&asset_ctxs,&pair.quote)
-                                        .expect("Failed to fetch asset ctxs")
-                                        .expect(&format!(
-                                            "Failed to find quote asset:  {}",
-                                            pair.quote
-                                        ));
-
-                                    quote_asset_ctx.mark_px.parse::<f64>().unwrap()
-                                };
-
-                                let current_ratio =
-                                    format!("{:.2}", base_market_price / quote_market_price)
-                                        .parse::<f64>()
-                                        .unwrap();
-
-                                if current_ratio <= target {
-                                    println!("Ratio reached: {} <= {}", current_ratio, target);
-                                    let base_sz = base_sz / base_market_price;
-                                    let quote_sz = quote_sz / quote_market_price;
-
-                                    break (
-                                        base_sz,
-                                        base_market_price,
-                                        quote_sz,
-                                        quote_market_price,
-                                        current_ratio,
-                                    );
-                                }
+                pair::run(job, &feed, &exchange, wallet, &assets, &journal).await;
+            }
+            Some(("list", _)) => {
+                let positions = PairPosition::list().expect("Failed to read pairs");
+                let feed = feed.clone();
+
+                for position in positions.iter() {
+                    let prices = match (
+                        feed.mark_px(&position.job.base).await,
+                        feed.mark_px(&position.job.quote).await,
+                    ) {
+                        (Ok(base_price), Ok(quote_price)) => Some((base_price, quote_price)),
+                        _ => None,
+                    };
 
-                                println!(
-                                    "Current Ratio: {}, Target Ratio: {}, Diff: {}. Checking again in 5 seconds\n---",
-                                    format!("{:.2}", current_ratio).parse::<f64>().unwrap(),
-                                    format!("{:.2}", target).parse::<f64>().unwrap(),
-                                    format!("{:.2}", current_ratio - target).parse::<f64>().unwrap(),
-                                );
-                                tokio::time::sleep(Duration::from_secs(5)).await;
-                            };
+                    position.print(prices);
+                }
 
-                            // send sell order request
-                            {
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: base_asset,
-                                    is_buy: false,
-                                    limit_px: parse_price(base_market_price * (1.0 - slippage)),
-                                    sz: parse_size(base_sz, base_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 1 of 2");
-                                println!("Side: Sell");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.base,
-                                    parse_size(base_sz, base_sz_decimals)
-                                );
-                                println!(
-                                    "Size in USD: {}",
-                                    parse_size(base_sz * base_market_price, base_sz_decimals)
-                                );
-                                println!("Market price: {}\n", base_market_price);
-                                println!("Ratio: {}\n", current_ratio);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => 
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                                });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
+                println!("{}", "---".repeat(20));
+                println!("\nTotal Pairs: {}", positions.len());
+            }
+            Some((action @ ("status" | "close" | "set-tp" | "set-sl" | "resume"), matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Pair id is required")
+                    .parse::<u64>()
+                    .expect("Failed to parse pair id");
+
+                let mut position = match PairPosition::load(id).expect("Failed to read pair") {
+                    Some(position) => position,
+                    None => {
+                        println!("No pair with id {}", id);
+                        return false;
+                    }
+                };
 
-                            // send buy order request
-                            {
-                                let order = OrderRequest {
-                                    cloid: None,
-                                    asset: quote_asset,
-                                    is_buy: true,
-                                    limit_px: parse_price(quote_market_price * (1.0 + slippage)),
-                                    sz: parse_size(quote_sz, quote_sz_decimals),
-                                    reduce_only: false,
-                                    order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                };
-
-                                println!("{}", "---".repeat(20));
-                                println!("Order 2 of 2");
-                                println!("Side: Buy");
-                                println!(
-                                    "Size in {}: {}",
-                                    pair.quote,
-                                    parse_size(quote_sz, quote_sz_decimals)
-                                );
-                                println!(
-                                    "Size in USD: {}",
-                                    parse_size(quote_sz * quote_market_price, quote_sz_decimals)
-                                );
-                                println!("Market price: {}\n", quote_market_price);
-                                println!("Ratio: {}\n", current_ratio);
-
-                                match exchange.place_order(wallet.clone(), vec![order], None).await {
-                                    Ok(order) => 
-                                        match order {
-                                            Response::Err(err) => {
-                                                println!("{:#?}", err);
-                                                return;
-                                            
-                                            }
-                                            
-                                            Response::Ok(order) => {
-                                                match order.data.expect("expected order response data") {
-                                                    StatusType::Statuses(statuses) => {
-                                                        statuses.iter().for_each(|status| match status {
-                                                    Status::Filled(order) => {
-                                                        println!("Order {} was successfully filled.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Resting(order) => {
-                                                        println!("Order {} was successfully placed.\n", order.oid);
-                                                        
-                                                    }
-                                                    Status::Error(msg) => {
-                                                        println!("Order failed with error: {:#?}\n", msg)
-                                                    }
-                                                    _ =>  unreachable!(),
-                                            });
-                                            },
-                                            _ =>  unreachable!(),
-                                        }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        println!("{:#?}", err);
-                                        return;
-                                    }
-                                }
-                            }
+                let feed = feed.clone();
+
+                if action == "status" {
+                    let prices = match (
+                        feed.mark_px(&position.job.base).await,
+                        feed.mark_px(&position.job.quote).await,
+                    ) {
+                        (Ok(base_price), Ok(quote_price)) => Some((base_price, quote_price)),
+                        (Err(err), _) | (_, Err(err)) => {
+                            println!("Failed to fetch market prices: {:#?}", err);
+                            None
                         }
+                    };
 
-                        if tp.is_none() && sl.is_none() {
-                            return;
-                        };
+                    position.print(prices);
+                    return true;
+                }
 
-                        println!("Monitoring positions for tp or sl\n---");
+                if position.status == PairStatus::Closed {
+                    println!("Pair {} is already closed", id);
+                    return false;
+                }
 
-                        let (exit_short_order, exit_long_order, current_ratio) = loop {
-                            let asset_ctxs = info.contexts().await.expect("Failed to fetch asset ctxs");
+                if matches!(action, "set-tp" | "set-sl") {
+                    let value = matches
+                        .get_one::<String>("value")
+                        .expect("Value is required")
+                        .parse::<f64>()
+                        .expect("Failed to parse value");
 
-                            let base_market_price = {
-                                let base_asset_ctx =
-                                    asset_ctx(&asset_ctxs,&pair.base)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!(
-                                        "Failed to find quote asset:  {}",
-                                        pair.quote
-                                    ));
-                                base_asset_ctx.mark_px.parse::<f64>().unwrap()
-                            };
+                    if action == "set-tp" {
+                        position.job.tp = Some(value);
+                    } else {
+                        position.job.sl = Some(value);
+                    }
 
-                            let quote_market_price = {
-                                let quote_asset_ctx =
-                                    asset_ctx(&asset_ctxs,&pair.quote)
-                                    .expect("Failed to fetch asset ctxs")
-                                    .expect(&format!(
-                                        "Failed to find quote asset:  {}",
-                                        pair.quote
-                                    ));
+                    match position.save() {
+                        Ok(()) => println!(
+                            "Pair {} {} set to {} ✔️\n---",
+                            id,
+                            if action == "set-tp" { "tp" } else { "sl" },
+                            value
+                        ),
+                        Err(err) => {
+                            println!("Failed to update pair: {:#?}", err);
+                            return false;
+                        }
+                    }
+                    return true;
+                }
 
-                                quote_asset_ctx.mark_px.parse::<f64>().unwrap()
-                            };
+                if action == "resume" && matches.get_flag("daemon") {
+                    match daemon::submit(command_line, Job::PairMonitor { id }).await {
+                        Ok(job_id) => println!("Pair {} submitted to the daemon as job {} ✔️\n---", id, job_id),
+                        Err(err) => {
+                            println!("Failed to submit pair {}: {:#?}", id, err);
+                            return false;
+                        }
+                    }
+                    return true;
+                }
 
-                            let current_ratio =
-                                format!("{:.2}", base_market_price / quote_market_price)
-                                    .parse::<f64>()
-                                    .unwrap();
-
-                            // check if tp or sl has been reached
-                            if let Some(tp) = tp {
-                                if current_ratio <= tp {
-                                    println!("Take profit reached: {} <= {}", current_ratio, tp);
-
-                                    let exit_short_order = OrderRequest {
-                                        cloid: None,
-                                        asset: base_asset,
-                                        is_buy: true,
-                                        limit_px: parse_price(
-                                            base_market_price * (1.0 + slippage),
-                                        ),
-                                        sz: parse_size(base_sz, base_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    let exit_long_order = OrderRequest {
-                                        cloid: None,
-                                        asset: quote_asset,
-                                        is_buy: false,
-                                        limit_px: parse_price(
-                                            quote_market_price * (1.0 - slippage),
-                                        ),
-                                        sz: parse_size(quote_sz, quote_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    break (exit_short_order, exit_long_order, current_ratio);
-                                }
+                let wallet = Arc::new(
+                    match config
+                        .private_key
+                        .parse::<LocalWallet>() {
+                            Ok(wallet) => wallet,
+                            Err(_) => {
+                                println!("Error: Invalid private key");
+                                return false;
                             }
+                        }
+                );
 
-                            if let Some(sl) = sl {
-                                if current_ratio >= sl {
-                                    println!("Stop loss reached: {} >= {}", current_ratio, sl);
-
-                                    let exit_short_order = OrderRequest {
-                                        cloid: None,
-                                        asset: base_asset,
-                                        is_buy: true,
-                                        limit_px: parse_price(
-                                            base_market_price * (1.0 + slippage),
-                                        ),
-                                        sz: parse_size(base_sz, base_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    let exit_long_order = OrderRequest {
-                                        cloid: None,
-                                        asset: quote_asset,
-                                        is_buy: false,
-                                        limit_px: parse_price(
-                                            quote_market_price * (1.0 - slippage),
-                                        ),
-                                        sz: parse_size(quote_sz, quote_sz_decimals),
-                                        reduce_only: true,
-                                        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
-                                    };
-
-                                    break (exit_short_order, exit_long_order, current_ratio);
-                                }
-                            }
+                if action == "resume" {
+                    pair::monitor(position, &feed, &exchange, wallet, &assets, &journal).await;
+                    return true;
+                }
 
-                            println!(
-                                "Current Ratio: {}, Target Ratio Tp: {}, Target Ratio Sl: {}, Tp Diff: {}, Sl Diff: {}. Checking again in 5 seconds\n---",
-                                format!("{:.2}", current_ratio).parse::<f64>().unwrap(),
-                                format!("{:.2}", tp.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                                format!("{:.2}", sl.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                                format!("{:.2}", current_ratio - tp.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                                format!("{:.2}", current_ratio - sl.unwrap_or(0.0)).parse::<f64>().unwrap(),
-                            );
+                if !pair::close(&mut position, &feed, &exchange, wallet, &assets, &journal).await {
+                    println!("Pair {} isn't fully closed, run `hl pair close {}` again to close what is left", id, id);
+                    return false;
+                }
+            }
 
-                            tokio::time::sleep(Duration::from_secs(5)).await;
-                        };
+            _ => {
+                println!("Invalid command: expected commands: (buy, sell, list, status, close, set-tp, set-sl, resume)");
+                return false;
+            }
+        },
 
-                        // place exit orders
-                        println!("{}", "---".repeat(20));
-                        println!("Order 1 of 2");
-                        println!("Side: Buy");
-                        println!(
-                            "Size in {}: {}",
-                            pair.base,
-                            parse_size(base_sz, base_sz_decimals)
-                        );
-                        println!("Ratio: {}\n", current_ratio);
-
-                        match exchange.place_order(wallet.clone(), vec![exit_short_order], None).await {
-                            Ok(order) => match order {
-                                Response::Err(err) => {
-                                    println!("{:#?}", err);
-                                    return;
-                                
-                                }
-                                
-                                Response::Ok(order) => {
-                                    match order.data.expect("expected order response data") {
-                                        StatusType::Statuses(statuses) => {
-                                            statuses.iter().for_each(|status| match status {
-                                        Status::Filled(order) => {
-                                            println!(
-                                                "Order {} was successfully filled.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Resting(order) => {
-                                            println!(
-                                                "Order {} was successfully placed.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Error(msg) => {
-                                            println!("Order failed with error: {:#?}\n", msg)
-                                        }
-                                        _ =>  unreachable!(),
-                                    });
-                                },
-                                _ => unreachable!(),
-                            }
-                            }},
-                            Err(err) => {
-                                println!("{:#?}", err);
-                                return;
+        Some(("daemon", matches)) => match matches.subcommand() {
+            Some(("start", _)) => {
+                let wallet = Arc::new(
+                    match config
+                        .private_key
+                        .parse::<LocalWallet>() {
+                            Ok(wallet) => wallet,
+                            Err(_) => {
+                                println!("Error: Invalid private key");
+                                return false;
                             }
                         }
+                );
 
-                        println!("{}", "---".repeat(20));
-                        println!("Order 2 of 2");
-                        println!("Side: Sell");
-                        println!(
-                            "Size in {}: {}",
-                            pair.quote,
-                            parse_size(quote_sz, quote_sz_decimals)
-                        );
-                        println!("Ratio: {}\n", current_ratio);
-
-                        match exchange.place_order(wallet.clone(), vec![exit_long_order], None).await {
-                            Ok(order) => match order {
-                                Response::Err(err) => {
-                                    println!("{:#?}", err);
-                                    return;
-                                
-                                }
-                                
-                                Response::Ok(order) => {
-                                    match order.data.expect("expected order response data") {
-                                        StatusType::Statuses(statuses) => {
-                                            statuses.iter().for_each(|status| match status {
-                                        Status::Filled(order) => {
-                                            println!(
-                                                "Order {} was successfully filled.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Resting(order) => {
-                                            println!(
-                                                "Order {} was successfully placed.\n",
-                                                order.oid
-                                            );
-                                        }
-                                        Status::Error(msg) => {
-                                            println!("Order failed with error: {:#?}\n", msg)
-                                        }
-                                        _ =>  unreachable!(),
-                                    });
-                                },
-                                _ =>  unreachable!(),
-                            }
-                            }},
-                            Err(err) => {
-                                println!("{:#?}", err);
-                                return;
-                            }
-                        }
+                let feed = MarketFeed::new(info.clone(), Duration::from_secs(2));
+
+                if let Err(err) = daemon::serve(feed, exchange.clone(), wallet, assets.clone(), profile).await {
+                    println!("Daemon failed: {:#?}", err);
+                    return false;
+                }
+            }
+            Some(("jobs", _)) => match daemon::request(&DaemonRequest::Jobs).await {
+                Ok(DaemonResponse::Jobs { jobs }) => {
+                    let repeat = 35;
+                    for job in jobs.iter() {
+                        println!("{}", "_".repeat(repeat));
+                        println!();
+                        println!("Job Id: {}", job.id);
+                        println!("Job: {}", job.description);
+                        println!("Status: {}", if job.running { "Running" } else { "Finished" });
+                        println!("Submitted: {} seconds ago", twap::now().saturating_sub(job.submitted_at));
+                    }
+
+                    println!("{}", "_".repeat(repeat));
+                    println!("\nTotal Jobs: {}", jobs.len());
+                }
+                Ok(DaemonResponse::Error { message }) => {
+                    println!("{}", message);
+                    return false;
+                }
+                Ok(_) => {
+                    println!("Unexpected daemon response");
+                    return false;
+                }
+                Err(err) => {
+                    println!("{:#?}", err);
+                    return false;
+                }
+            },
+            Some(("cancel", matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Job id is required")
+                    .parse::<u64>()
+                    .expect("Failed to parse job id");
+
+                match daemon::request(&DaemonRequest::Cancel { id }).await {
+                    Ok(DaemonResponse::Cancelled { id }) => println!("Job {} successfully cancelled ✔️\n---", id),
+                    Ok(DaemonResponse::Error { message }) => {
+                        println!("{}", message);
+                        return false;
+                    }
+                    Ok(_) => {
+                        println!("Unexpected daemon response");
+                        return false;
+                    }
+                    Err(err) => {
+                        println!("{:#?}", err);
+                        return false;
                     }
                 }
             }
+            _ => {
+                println!("Invalid command: expected commands: (daemon start, daemon jobs, daemon cancel)");
+                return false;
+            }
+        },
+
+        Some(("journal", matches)) => match matches.subcommand() {
+            Some(("list", matches)) => {
+                let limit = matches
+                    .get_one::<String>("limit")
+                    .map(|limit| limit.parse::<u32>().expect("Failed to parse limit"))
+                    .unwrap_or(20);
+
+                let entries = journal.list(limit).expect("Failed to read journal");
+
+                let symbols = assets
+                    .iter()
+                    .map(|(symbol, (_, asset))| (*asset, symbol.as_str()))
+                    .collect::<HashMap<u32, &str>>();
+
+                let repeat = 35;
+                for entry in entries.iter() {
+                    println!("{}", "_".repeat(repeat));
+                    println!();
+                    println!("Id: {}", entry.id);
+                    println!("Time: {}", entry.created_at);
+                    println!("Strategy: {}", entry.strategy);
+                    println!("Asset: {}", symbols.get(&entry.asset).unwrap_or(&"?"));
+                    println!("Side: {}", if entry.is_buy { "Buy" } else { "Sell" });
+                    println!("Size: {}", entry.sz);
+                    println!("Limit Price: {}", entry.limit_px);
+                    println!("Status: {}", entry.status.as_deref().unwrap_or("pending"));
+                }
+
+                println!("{}", "_".repeat(repeat));
+                println!("\nTotal Entries: {}", entries.len());
+            }
+            Some(("show", matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Journal id is required")
+                    .parse::<i64>()
+                    .expect("Failed to parse journal id");
+
+                let entry = match journal.show(id).expect("Failed to read journal") {
+                    Some(entry) => entry,
+                    None => {
+                        println!("No journal entry with id {}", id);
+                        return false;
+                    }
+                };
+
+                let symbol = assets
+                    .iter()
+                    .find(|(_, (_, asset))| *asset == entry.asset)
+                    .map(|(symbol, _)| symbol.as_str())
+                    .unwrap_or("?");
+
+                println!("{}", "---".repeat(20));
+                println!("Id: {}", entry.id);
+                println!("Time: {}", entry.created_at);
+                println!("Command: {}", entry.command_line);
+                println!("Profile: {}", entry.profile);
+                println!("Strategy: {}", entry.strategy);
+                println!("Asset: {}", symbol);
+                println!("Side: {}", if entry.is_buy { "Buy" } else { "Sell" });
+                println!("Size: {}", entry.sz);
+                println!("Limit Price: {}", entry.limit_px);
+                println!("Reduce Only: {}", entry.reduce_only);
+                println!("Status: {}", entry.status.as_deref().unwrap_or("pending"));
+                if let Some(oid) = entry.oid {
+                    println!("Order Id: {}", oid);
+                }
+                if let Some(detail) = &entry.detail {
+                    println!("Detail: {}", detail);
+                }
+                if let Some(note) = &entry.note {
+                    println!("Note: {}", note);
+                }
+                if let Some(tags) = &entry.tags {
+                    println!("Tags: {}", tags);
+                }
+                println!("Request: {}", entry.request);
+            }
+            Some(("annotate", matches)) => {
+                let id = matches
+                    .get_one::<String>("id")
+                    .expect("Journal id is required")
+                    .parse::<i64>()
+                    .expect("Failed to parse journal id");
+
+                let note = matches.get_one::<String>("note").map(|note| note.as_str());
+
+                let tags = matches
+                    .get_many::<String>("tag")
+                    .map(|tags| tags.cloned().collect::<Vec<_>>())
+                    .unwrap_or_default();
+
+                if note.is_none() && tags.is_empty() {
+                    println!("Nothing to annotate, expected --note and/or --tag");
+                    return false;
+                }
 
+                match journal.annotate(id, note, &tags) {
+                    Ok(true) => println!("Journal entry {} successfully annotated ✔️\n---", id),
+                    Ok(false) => {
+                        println!("No journal entry with id {}", id);
+                        return false;
+                    }
+                    Err(err) => {
+                        println!("Failed to annotate journal entry: {:#?}", err);
+                        return false;
+                    }
+                }
+            }
             _ => {
-                println!("Invalid command: expected commands: (buy, sell)");
+                println!("Invalid command: expected commands: (journal list, journal show, journal annotate)");
             }
         },
 
         _ => {
-            println!("Invalid command: expected commands: (buy, sell, twap, view, pair)");
+            println!("Invalid command: expected commands: (buy, sell, twap, pov, iceberg, grid, dca, rebalance, view, pair, basket, when, alert, serve, webhook, shell, run, strategy, journal, daemon)");
+            return false;
         }
     };
+
+    true
 }
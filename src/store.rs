@@ -0,0 +1,106 @@
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+use home::home_dir;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Directory the saved jobs of a kind are kept in, one `<id>.json` file each
+fn dir(kind: &str) -> Result<PathBuf, anyhow::Error> {
+    let home_dir = match home_dir() {
+        Some(path) => path,
+        None => return Err(anyhow::anyhow!("Impossible to get your home dir!")),
+    };
+
+    // create .hyperliquid/<kind> if it doesn't exist
+    let path = home_dir.join(".hyperliquid").join(kind);
+    std::fs::create_dir_all(&path)?;
+
+    Ok(path)
+}
+
+/// Ids of the saved jobs of a kind, in ascending order
+fn ids(kind: &str) -> Result<Vec<u64>, anyhow::Error> {
+    let mut ids = std::fs::read_dir(dir(kind)?)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_stem()?.to_str()?.parse::<u64>().ok())
+        .collect::<Vec<_>>();
+
+    ids.sort();
+
+    Ok(ids)
+}
+
+/// Saves a new job of a kind under the next free id, set on it with `set_id`. Each file is created
+/// only if it doesn't exist yet, so the daemon and the cli creating a job at once don't share an id
+pub fn create<T: Serialize>(kind: &str, mut job: T, set_id: impl Fn(&mut T, u64)) -> Result<T, anyhow::Error> {
+    let dir = dir(kind)?;
+    let mut id = ids(kind)?.last().copied().unwrap_or(0) + 1;
+
+    loop {
+        set_id(&mut job, id);
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(format!("{}.json", id)))
+        {
+            Ok(mut file) => {
+                file.write_all(serde_json::to_string_pretty(&job)?.as_bytes())?;
+                return Ok(job);
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => id += 1,
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+pub fn load<T: DeserializeOwned>(kind: &str, id: u64) -> Result<Option<T>, anyhow::Error> {
+    let path = dir(kind)?.join(format!("{}.json", id));
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let job = std::fs::read_to_string(&path)?;
+
+    Ok(Some(serde_json::from_str(&job)?))
+}
+
+pub fn save<T: Serialize>(kind: &str, id: u64, job: &T) -> Result<(), anyhow::Error> {
+    let path = dir(kind)?.join(format!("{}.json", id));
+
+    std::fs::write(path, serde_json::to_string_pretty(job)?)?;
+
+    Ok(())
+}
+
+/// Deletes a saved job, returns false if it doesn't exist
+pub fn remove(kind: &str, id: u64) -> Result<bool, anyhow::Error> {
+    let path = dir(kind)?.join(format!("{}.json", id));
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    std::fs::remove_file(path)?;
+
+    Ok(true)
+}
+
+/// Every saved job of a kind, ordered by id. A file that can't be read e.g a corrupt one is
+/// reported and skipped, so it doesn't hide the other jobs
+pub fn list<T: DeserializeOwned>(kind: &str) -> Result<Vec<T>, anyhow::Error> {
+    let jobs = ids(kind)?
+        .into_iter()
+        .filter_map(|id| match load(kind, id) {
+            Ok(job) => job,
+            Err(err) => {
+                println!("Skipping {} {}, failed to read it: {:#?}", kind, id, err);
+                None
+            }
+        })
+        .collect();
+
+    Ok(jobs)
+}
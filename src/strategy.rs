@@ -0,0 +1,599 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+
+use crate::{
+    grid::{SimulatedExchange, Venue},
+    market::MarketFeed,
+    twap::now,
+};
+
+/// Most rounds of orders a single step may place, in case handlers keep reacting to their own fills
+const MAX_ROUNDS: usize = 10;
+
+/// What the script asked for during a handler, carried out once the handler returns
+enum Action {
+    Place {
+        is_buy: bool,
+        usd: f64,
+        /// At market when not given
+        price: Option<f64>,
+    },
+    /// Cancels one order, or all of them when not given
+    Cancel(Option<u64>),
+    Close,
+}
+
+/// The strategy's own position and resting orders, built from the fills of its orders
+#[derive(Default)]
+struct Book {
+    mark: f64,
+    /// Signed size in the base asset, negative when short
+    size: f64,
+    entry: f64,
+    realized: f64,
+    fills: u32,
+    /// Resting order ids and whether they buy
+    orders: HashMap<u64, bool>,
+}
+
+impl Book {
+    fn apply(&mut self, is_buy: bool, sz: f64, px: f64) {
+        let signed = if is_buy { sz } else { -sz };
+        self.fills += 1;
+
+        if self.size == 0.0 || self.size.signum() == signed.signum() {
+            self.entry = (self.entry * self.size.abs() + px * sz) / (self.size.abs() + sz);
+            self.size += signed;
+            return;
+        }
+
+        let closed = sz.min(self.size.abs());
+        self.realized += closed * (px - self.entry) * self.size.signum();
+        self.size += signed;
+
+        if self.size.abs() < f64::EPSILON {
+            self.size = 0.0;
+            self.entry = 0.0;
+        } else if self.size.signum() == signed.signum() {
+            // flipped, what's left was opened at this fill
+            self.entry = px;
+        }
+    }
+
+    fn unrealized(&self) -> f64 {
+        self.size * (self.mark - self.entry)
+    }
+
+    fn position(&self) -> Map {
+        let mut position = Map::new();
+        position.insert("size".into(), self.size.into());
+        position.insert("entry".into(), self.entry.into());
+        position.insert("upnl".into(), self.unrealized().into());
+        position.insert("realized".into(), self.realized.into());
+        position
+    }
+
+    fn orders(&self) -> Array {
+        self.orders.keys().map(|oid| (*oid as INT).into()).collect()
+    }
+}
+
+fn number(value: &Dynamic) -> Result<f64, Box<EvalAltResult>> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|value| value as f64))
+        .map_err(|_| format!("Expected a number, got {}", value.type_name()).into())
+}
+
+fn side(side: &str) -> Result<bool, Box<EvalAltResult>> {
+    match side.to_lowercase().as_str() {
+        "buy" => Ok(true),
+        "sell" => Ok(false),
+        _ => Err(format!("Invalid side {}, expected buy or sell", side).into()),
+    }
+}
+
+/// A Rhai script reacting to market data and its own fills. The script defines any of
+/// `on_start(tick)`, `on_tick(tick)`, `on_fill(fill)`, `on_position(position)` and
+/// `on_stop(position)`, keeps its state on `this` and trades with `buy`, `sell`, `place`,
+/// `cancel`, `cancel_all` and `close`. Sizes are in USD as on the command line.
+pub struct Strategy {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    this: Dynamic,
+    symbol: String,
+    book: Rc<RefCell<Book>>,
+    actions: Rc<RefCell<Vec<Action>>>,
+}
+
+impl Strategy {
+    pub fn load(path: &Path, symbol: &str) -> Result<Self, anyhow::Error> {
+        let book = Rc::new(RefCell::new(Book::default()));
+        let actions = Rc::new(RefCell::new(Vec::new()));
+
+        let mut engine = Engine::new();
+
+        for (name, is_buy) in [("buy", true), ("sell", false)] {
+            let queue = actions.clone();
+            engine.register_fn(name, move |usd: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                let usd = number(&usd)?;
+                queue.borrow_mut().push(Action::Place { is_buy, usd, price: None });
+                Ok(())
+            });
+
+            let queue = actions.clone();
+            engine.register_fn(
+                name,
+                move |usd: Dynamic, price: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                    let (usd, price) = (number(&usd)?, number(&price)?);
+                    queue.borrow_mut().push(Action::Place { is_buy, usd, price: Some(price) });
+                    Ok(())
+                },
+            );
+        }
+
+        let queue = actions.clone();
+        engine.register_fn("place", move |side_name: &str, usd: Dynamic| -> Result<(), Box<EvalAltResult>> {
+            let (is_buy, usd) = (side(side_name)?, number(&usd)?);
+            queue.borrow_mut().push(Action::Place { is_buy, usd, price: None });
+            Ok(())
+        });
+
+        let queue = actions.clone();
+        engine.register_fn(
+            "place",
+            move |side_name: &str, usd: Dynamic, price: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                let (is_buy, usd, price) = (side(side_name)?, number(&usd)?, number(&price)?);
+                queue.borrow_mut().push(Action::Place { is_buy, usd, price: Some(price) });
+                Ok(())
+            },
+        );
+
+        let queue = actions.clone();
+        engine.register_fn("cancel", move |oid: INT| {
+            queue.borrow_mut().push(Action::Cancel(Some(oid as u64)));
+        });
+
+        let queue = actions.clone();
+        engine.register_fn("cancel_all", move || {
+            queue.borrow_mut().push(Action::Cancel(None));
+        });
+
+        let queue = actions.clone();
+        engine.register_fn("close", move || {
+            queue.borrow_mut().push(Action::Close);
+        });
+
+        let state = book.clone();
+        engine.register_fn("position", move || state.borrow().position());
+
+        let state = book.clone();
+        engine.register_fn("mark", move || state.borrow().mark);
+
+        let state = book.clone();
+        engine.register_fn("orders", move || state.borrow().orders());
+
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|err| anyhow::anyhow!("{}", err))?;
+
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|err| anyhow::anyhow!("{}", err))?;
+
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            this: Map::new().into(),
+            symbol: symbol.to_uppercase(),
+            book,
+            actions,
+        })
+    }
+
+    /// Calls a handler with `this` bound to the strategy state, a handler the script doesn't
+    /// define is skipped
+    fn call(&mut self, name: &str, arg: Dynamic) -> Result<(), anyhow::Error> {
+        if !self.ast.iter_functions().any(|function| function.name == name) {
+            return Ok(());
+        }
+
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.this);
+
+        // handlers return nothing the runner uses
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut self.scope, &self.ast, name, (arg,))
+            .map(|_| ())
+            .map_err(|err| anyhow::anyhow!("{} failed: {}", name, err))
+    }
+
+    fn tick(&self, time: u64) -> Dynamic {
+        let book = self.book.borrow();
+
+        let mut tick = Map::new();
+        tick.insert("asset".into(), self.symbol.clone().into());
+        tick.insert("time".into(), (time as INT).into());
+        tick.insert("mark".into(), book.mark.into());
+        tick.insert("position".into(), book.position().into());
+        tick.insert("orders".into(), book.orders().into());
+        tick.into()
+    }
+
+    fn fill(&mut self, oid: Option<u64>, is_buy: bool, (sz, usd): (f64, f64)) -> Result<(), anyhow::Error> {
+        if sz <= 0.0 {
+            return Ok(());
+        }
+
+        let px = usd / sz;
+        self.book.borrow_mut().apply(is_buy, sz, px);
+
+        println!(
+            "Filled: {} {} {} at {}",
+            if is_buy { "buy" } else { "sell" },
+            sz,
+            self.symbol,
+            px
+        );
+
+        let mut fill = Map::new();
+        fill.insert("oid".into(), oid.map_or(Dynamic::UNIT, |oid| (oid as INT).into()));
+        fill.insert("side".into(), (if is_buy { "buy" } else { "sell" }).into());
+        fill.insert("size".into(), sz.into());
+        fill.insert("price".into(), px.into());
+
+        self.call("on_fill", fill.into())?;
+
+        let position = self.book.borrow().position();
+        self.call("on_position", position.into())
+    }
+
+    /// Carries out what the handlers asked for, then what the handlers of the resulting fills
+    /// asked for, and so on
+    async fn execute(&mut self, venue: &mut Venue<'_>, asset: (u32, u32)) -> Result<(), anyhow::Error> {
+        for _ in 0..MAX_ROUNDS {
+            let actions = std::mem::take(&mut *self.actions.borrow_mut());
+            if actions.is_empty() {
+                return Ok(());
+            }
+
+            let mark = self.book.borrow().mark;
+
+            for action in actions {
+                match action {
+                    Action::Place { is_buy, usd, price: Some(price) } => {
+                        let oids = venue.place(asset, &[(is_buy, price, usd / price)]).await?;
+
+                        if let Some(Some(oid)) = oids.first() {
+                            println!(
+                                "Placed: {} ${} of {} at {}, order {}",
+                                if is_buy { "buy" } else { "sell" },
+                                usd,
+                                self.symbol,
+                                price,
+                                oid
+                            );
+                            self.book.borrow_mut().orders.insert(*oid, is_buy);
+                        }
+                    }
+                    Action::Place { is_buy, usd, price: None } => {
+                        let filled = venue.market(asset, is_buy, usd / mark, mark, false).await?;
+                        self.fill(None, is_buy, filled)?;
+                    }
+                    Action::Cancel(oid) => {
+                        let cancelled = {
+                            let mut book = self.book.borrow_mut();
+                            let oids = match oid {
+                                Some(oid) => vec![oid],
+                                None => book.orders.keys().copied().collect(),
+                            };
+
+                            oids.into_iter()
+                                .filter_map(|oid| book.orders.remove(&oid).map(|is_buy| (oid, is_buy)))
+                                .collect::<Vec<_>>()
+                        };
+
+                        venue
+                            .cancel(asset.1, cancelled.iter().map(|(oid, _)| *oid).collect())
+                            .await?;
+
+                        // a live order may have partly filled before it was cancelled
+                        for (oid, is_buy) in cancelled {
+                            let filled = venue.fills(oid).await?;
+                            self.fill(Some(oid), is_buy, filled)?;
+                        }
+                    }
+                    Action::Close => {
+                        let size = self.book.borrow().size;
+                        if size != 0.0 {
+                            let filled = venue.market(asset, size < 0.0, size.abs(), mark, true).await?;
+                            self.fill(None, size < 0.0, filled)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Handlers kept placing orders for {} rounds in a single step",
+            MAX_ROUNDS
+        ))
+    }
+
+    pub async fn start(&mut self, venue: &mut Venue<'_>, asset: (u32, u32), time: u64, mark: f64) -> Result<(), anyhow::Error> {
+        self.book.borrow_mut().mark = mark;
+
+        let tick = self.tick(time);
+        self.call("on_start", tick)?;
+        self.execute(venue, asset).await
+    }
+
+    /// Picks up the fills of resting orders at the new mark price, then runs `on_tick`
+    pub async fn step(&mut self, venue: &mut Venue<'_>, asset: (u32, u32), time: u64, mark: f64) -> Result<(), anyhow::Error> {
+        self.book.borrow_mut().mark = mark;
+
+        let open = venue.open_orders(mark).await?;
+        let done = self
+            .book
+            .borrow()
+            .orders
+            .iter()
+            .filter(|(oid, _)| !open.contains(oid))
+            .map(|(oid, is_buy)| (*oid, *is_buy))
+            .collect::<Vec<_>>();
+
+        for (oid, is_buy) in done {
+            self.book.borrow_mut().orders.remove(&oid);
+            let filled = venue.fills(oid).await?;
+            self.fill(Some(oid), is_buy, filled)?;
+        }
+
+        self.execute(venue, asset).await?;
+
+        let tick = self.tick(time);
+        self.call("on_tick", tick)?;
+        self.execute(venue, asset).await
+    }
+
+    /// Runs `on_stop`, then cancels the orders the strategy left resting. The position is left
+    /// open unless `on_stop` closes it
+    pub async fn stop(&mut self, venue: &mut Venue<'_>, asset: (u32, u32)) -> Result<(), anyhow::Error> {
+        let position = self.book.borrow().position();
+        self.call("on_stop", position.into())?;
+
+        self.actions.borrow_mut().push(Action::Cancel(None));
+        self.execute(venue, asset).await
+    }
+
+    pub fn print(&self) {
+        let book = self.book.borrow();
+
+        println!("{}", "---".repeat(20));
+        println!("Asset: {}", self.symbol);
+        println!("Fills: {}", book.fills);
+        println!("Position: {} at {}", book.size, book.entry);
+        println!("Realized PnL: {:.2}", book.realized);
+        println!("Unrealized PnL: {:.2} at {}", book.unrealized(), book.mark);
+        println!("Resting orders: {}", book.orders.len());
+    }
+}
+
+/// Runs a strategy on the live mark price every interval until ctrl-c, against the exchange or a
+/// simulation of it
+pub async fn run(
+    mut strategy: Strategy,
+    feed: &MarketFeed,
+    mut venue: Venue<'_>,
+    asset: (u32, u32),
+    interval: Duration,
+) -> Result<(), anyhow::Error> {
+    let mark = feed.mark_px(&strategy.symbol).await?;
+    let result = strategy.start(&mut venue, asset, now(), mark).await;
+
+    let result = match result {
+        Ok(()) => loop {
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = tokio::signal::ctrl_c() => break Ok(()),
+            }
+
+            let mark = match feed.mark_px(&strategy.symbol).await {
+                Ok(mark) => mark,
+                Err(err) => {
+                    println!("Failed to fetch the mark price: {:#?}", err);
+                    continue;
+                }
+            };
+
+            if let Err(err) = strategy.step(&mut venue, asset, now(), mark).await {
+                break Err(err);
+            }
+        },
+        Err(err) => Err(err),
+    };
+
+    let stopped = strategy.stop(&mut venue, asset).await;
+    strategy.print();
+
+    result.and(stopped)
+}
+
+/// Reads recorded `time,mark` rows, a header or lines starting with `#` are skipped
+pub fn load_records(path: &Path) -> Result<Vec<(u64, f64)>, anyhow::Error> {
+    let records = std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (time, mark) = line.split_once(',')?;
+            Some((time.trim().parse::<u64>().ok()?, mark.trim().parse::<f64>().ok()?))
+        })
+        .collect::<Vec<_>>();
+
+    if records.is_empty() {
+        return Err(anyhow::anyhow!("No time,mark rows found in {}", path.display()));
+    }
+
+    Ok(records)
+}
+
+/// Starts a strategy on the first record and steps it through the rest
+async fn replay(
+    strategy: &mut Strategy,
+    venue: &mut Venue<'_>,
+    records: &[(u64, f64)],
+    asset: (u32, u32),
+) -> Result<(), anyhow::Error> {
+    let (time, mark) = records[0];
+    strategy.start(venue, asset, time, mark).await?;
+
+    for (time, mark) in records[1..].iter() {
+        strategy.step(venue, asset, *time, *mark).await?;
+    }
+
+    Ok(())
+}
+
+/// Replays recorded mark prices through a strategy against a simulated exchange
+pub async fn test(mut strategy: Strategy, records: &[(u64, f64)], asset: (u32, u32)) -> Result<(), anyhow::Error> {
+    let mut venue = Venue::Simulated(SimulatedExchange::default());
+
+    let result = replay(&mut strategy, &mut venue, records, asset).await;
+
+    let stopped = strategy.stop(&mut venue, asset).await;
+    strategy.print();
+
+    result.and(stopped)
+}
+
+/// Appends the mark price of an asset to a `time,mark` file every interval until ctrl-c
+pub async fn record(feed: &MarketFeed, symbol: &str, path: &Path, interval: Duration) -> Result<(), anyhow::Error> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    let mut count = 0;
+
+    loop {
+        let mark = feed.mark_px(symbol).await?;
+        writeln!(file, "{},{}", now(), mark)?;
+        count += 1;
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    println!("Recorded {} {} mark prices to {}", count, symbol, path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn book_averages_into_a_position() {
+        let mut book = Book::default();
+
+        book.apply(true, 1.0, 100.0);
+        book.apply(true, 3.0, 200.0);
+
+        assert_eq!(book.size, 4.0);
+        assert!(close(book.entry, 175.0));
+        assert_eq!(book.realized, 0.0);
+        assert_eq!(book.fills, 2);
+    }
+
+    #[test]
+    fn book_realizes_a_partial_close() {
+        let mut book = Book::default();
+
+        book.apply(false, 2.0, 100.0);
+        book.apply(true, 0.5, 90.0);
+
+        assert_eq!(book.size, -1.5);
+        // the rest of the short keeps its entry
+        assert!(close(book.entry, 100.0));
+        assert!(close(book.realized, 5.0));
+
+        book.apply(true, 1.5, 110.0);
+
+        assert_eq!(book.size, 0.0);
+        assert_eq!(book.entry, 0.0);
+        assert!(close(book.realized, -10.0));
+    }
+
+    #[test]
+    fn book_flips_a_position() {
+        let mut book = Book::default();
+
+        book.apply(true, 1.0, 100.0);
+        book.apply(false, 3.0, 120.0);
+
+        assert_eq!(book.size, -2.0);
+        assert!(close(book.entry, 120.0));
+        assert!(close(book.realized, 20.0));
+
+        book.mark = 110.0;
+        assert!(close(book.unrealized(), 20.0));
+    }
+
+    #[tokio::test]
+    async fn steps_fill_resting_orders_at_the_new_mark() {
+        let mut strategy = Strategy::load(&fixture("dip.rhai"), "eth").unwrap();
+        let mut venue = Venue::Simulated(SimulatedExchange::default());
+
+        strategy.start(&mut venue, (4, 1), 0, 2000.0).await.unwrap();
+        assert_eq!(strategy.book.borrow().orders.len(), 1);
+
+        strategy.step(&mut venue, (4, 1), 10, 1970.0).await.unwrap();
+        assert_eq!(strategy.book.borrow().fills, 0);
+
+        // the buy at 1960 fills and on_fill rests the take profit
+        strategy.step(&mut venue, (4, 1), 20, 1950.0).await.unwrap();
+        let book = strategy.book.borrow();
+        assert_eq!(book.fills, 1);
+        assert!(close(book.size, 100.0 / 1960.0));
+        assert!(close(book.entry, 1960.0));
+        assert_eq!(book.orders.values().collect::<Vec<_>>(), vec![&false]);
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_marks() {
+        let mut strategy = Strategy::load(&fixture("dip.rhai"), "eth").unwrap();
+        let records = load_records(&fixture("eth.csv")).unwrap();
+        let mut venue = Venue::Simulated(SimulatedExchange::default());
+
+        replay(&mut strategy, &mut venue, &records, (4, 1)).await.unwrap();
+
+        let book = strategy.book.borrow();
+        assert_eq!(book.fills, 2);
+        assert!(book.size.abs() < 1e-12);
+        assert!(close(book.realized, 2.0));
+        assert!(book.orders.is_empty());
+
+        // the first record starts the strategy, every later one is a single tick
+        let this = strategy.this.clone().cast::<Map>();
+        assert_eq!(this["ticks"].as_int().unwrap(), records.len() as INT - 1);
+    }
+}
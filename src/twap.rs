@@ -0,0 +1,532 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ethers::signers::LocalWallet;
+use hyperliquid::{
+    types::exchange::{
+        request::{Limit, OrderRequest, OrderType, Tif},
+        response::{Response, Status, StatusType},
+    },
+    utils::{parse_price, parse_size},
+    Exchange,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{helpers::place_order, journal::Journal, market::MarketFeed, store};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TwapStatus {
+    Running,
+    Paused,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapJob {
+    pub id: u64,
+    pub symbol: String,
+    pub is_buy: bool,
+    /// Total order size in USD
+    pub size: f64,
+    pub interval_secs: u64,
+    pub num_of_orders: u32,
+    pub slices_done: u32,
+    /// Filled size in the base asset
+    pub filled_sz: f64,
+    /// Filled size in USD
+    pub filled_usd: f64,
+    /// Unix timestamp (secs) the next slice is due at
+    pub next_due: u64,
+    pub status: TwapStatus,
+    pub created_at: u64,
+    /// Size in USD of slices skipped for trading beyond the limit price
+    #[serde(default)]
+    pub skipped_usd: f64,
+    /// Randomizes each slice size by up to +/- this fraction
+    #[serde(default)]
+    pub size_jitter: f64,
+    /// Randomizes each interval by up to +/- this fraction
+    #[serde(default)]
+    pub interval_jitter: f64,
+    /// Slices are not placed while the market trades beyond this price
+    #[serde(default)]
+    pub limit_px: Option<f64>,
+    /// Retries a slice beyond the limit price next interval instead of skipping it
+    #[serde(default)]
+    pub defer_on_limit: bool,
+    /// Caps each slice to this fraction of the volume traded over the last interval
+    #[serde(default)]
+    pub max_participation: Option<f64>,
+}
+
+/// Randomizes value by up to +/- the jitter fraction
+fn jitter(value: f64, jitter: f64) -> f64 {
+    if jitter <= 0.0 {
+        return value;
+    }
+
+    value * (1.0 + (rand::random::<f64>() * 2.0 - 1.0) * jitter.min(1.0))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before unix epoch")
+        .as_secs()
+}
+
+impl TwapJob {
+    pub fn new(
+        symbol: &str,
+        is_buy: bool,
+        size: f64,
+        interval: Duration,
+        num_of_orders: u32,
+    ) -> Result<Self, anyhow::Error> {
+        store::create(
+            "twap",
+            Self {
+                id: 0,
+                symbol: symbol.to_uppercase(),
+                is_buy,
+                size,
+                interval_secs: interval.as_secs(),
+                num_of_orders,
+                slices_done: 0,
+                filled_sz: 0.0,
+                filled_usd: 0.0,
+                next_due: now(),
+                status: TwapStatus::Running,
+                created_at: now(),
+                skipped_usd: 0.0,
+                size_jitter: 0.0,
+                interval_jitter: 0.0,
+                limit_px: None,
+                defer_on_limit: false,
+                max_participation: None,
+            },
+            |job, id| job.id = id,
+        )
+    }
+
+    pub fn load(id: u64) -> Result<Option<Self>, anyhow::Error> {
+        store::load("twap", id)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        store::save("twap", self.id, self)
+    }
+
+    pub fn list() -> Result<Vec<Self>, anyhow::Error> {
+        store::list("twap")
+    }
+
+    /// A running job whose next slice is more than a minute overdue has lost the process driving it
+    pub fn is_stale(&self) -> bool {
+        self.status == TwapStatus::Running && now() > self.next_due + 60
+    }
+
+    pub fn remaining_slices(&self) -> u32 {
+        self.num_of_orders - self.slices_done
+    }
+
+    /// USD left to execute, excluding what was filled or skipped
+    pub fn remaining_usd(&self) -> f64 {
+        (self.size - self.filled_usd - self.skipped_usd).max(0.0)
+    }
+
+    pub fn side(&self) -> &'static str {
+        if self.is_buy {
+            "Buy"
+        } else {
+            "Sell"
+        }
+    }
+
+    pub fn print(&self) {
+        println!("{}", "---".repeat(20));
+        println!("Twap Id: {}", self.id);
+        println!("Side: {}", self.side());
+        println!("Asset: {}", self.symbol);
+        println!("Status: {}", self.status_name());
+        println!("Slices: {} of {}", self.slices_done, self.num_of_orders);
+        println!("Target Size in USD: {}", self.size);
+        println!("Filled Size in {}: {}", self.symbol, self.filled_sz);
+        println!("Filled Size in USD: {:.2}", self.filled_usd);
+        if self.skipped_usd > 0.0 {
+            println!("Skipped Size in USD: {:.2}", self.skipped_usd);
+        }
+        println!("Interval: {} seconds", self.interval_secs);
+        if let Some(limit_px) = self.limit_px {
+            println!(
+                "Limit Price: {} ({} beyond it)",
+                limit_px,
+                if self.defer_on_limit { "defer" } else { "skip" }
+            );
+        }
+        if let Some(max_participation) = self.max_participation {
+            println!("Max Participation: {}%", max_participation * 100.0);
+        }
+        if self.status == TwapStatus::Running {
+            println!(
+                "Next Slice: {}",
+                if self.is_stale() {
+                    "overdue, run `hl twap resume` to continue".to_string()
+                } else {
+                    format!("in {} seconds", self.next_due.saturating_sub(now()))
+                }
+            );
+        }
+    }
+
+    /// Re-reads the saved status before saving so a pause/cancel issued meanwhile is kept
+    fn checkpoint(&mut self) {
+        if let Ok(Some(latest)) = TwapJob::load(self.id) {
+            self.status = latest.status;
+        }
+
+        if self.slices_done == self.num_of_orders {
+            self.status = TwapStatus::Completed;
+        }
+
+        if let Err(err) = self.save() {
+            println!("Failed to save twap {}: {:#?}", self.id, err);
+        }
+    }
+
+    /// Schedules the next slice one (jittered) interval from now
+    fn schedule_next(&mut self) -> u64 {
+        let interval = jitter(self.interval_secs as f64, self.interval_jitter).round() as u64;
+
+        self.next_due = now() + interval.max(1);
+
+        interval
+    }
+
+    pub fn status_name(&self) -> &'static str {
+        match self.status {
+            TwapStatus::Running => "Running",
+            TwapStatus::Paused => "Paused",
+            TwapStatus::Cancelled => "Cancelled",
+            TwapStatus::Completed => "Completed",
+        }
+    }
+}
+
+/// Drives a twap job from its last completed slice until it completes, is paused or cancelled.
+/// Progress is saved after every slice so an interrupted job can be resumed.
+pub async fn run(
+    mut job: TwapJob,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    (sz_decimals, asset): (u32, u32),
+    journal: &Journal,
+) {
+    while job.slices_done < job.num_of_orders {
+        // wait for the next slice, checking for pause/cancel every few seconds
+        loop {
+            match TwapJob::load(job.id) {
+                Ok(Some(latest)) => job.status = latest.status,
+                Ok(None) => {
+                    println!("Twap {} no longer exists, stopping", job.id);
+                    return;
+                }
+                Err(err) => println!("Failed to read twap {}: {:#?}", job.id, err),
+            }
+
+            match job.status {
+                TwapStatus::Running => {}
+                TwapStatus::Paused => {
+                    println!("Twap {} paused, run `hl twap resume {}` to continue", job.id, job.id);
+                    return;
+                }
+                _ => {
+                    println!("Twap {} {}", job.id, job.status_name().to_lowercase());
+                    return;
+                }
+            }
+
+            let remaining = job.next_due.saturating_sub(now());
+            if remaining == 0 {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_secs(remaining.min(5))).await;
+        }
+
+        let market_price = match feed.mark_px(&job.symbol).await {
+            Ok(market_price) => market_price,
+            Err(err) => {
+                println!("Failed to fetch market price: {:#?}", err);
+                println!("Twap {} interrupted, run `hl twap resume {}` to continue", job.id, job.id);
+                return;
+            }
+        };
+
+        if let Some(limit_px) = job.limit_px {
+            let beyond_limit = if job.is_buy {
+                market_price > limit_px
+            } else {
+                market_price < limit_px
+            };
+
+            if beyond_limit {
+                if job.defer_on_limit {
+                    let interval = job.schedule_next();
+                    println!(
+                        "Market price {} is beyond the limit price {}, deferring slice {} by {} seconds\n---",
+                        market_price,
+                        limit_px,
+                        job.slices_done + 1,
+                        interval
+                    );
+                } else {
+                    println!(
+                        "Market price {} is beyond the limit price {}, skipping slice {}\n---",
+                        market_price,
+                        limit_px,
+                        job.slices_done + 1
+                    );
+                    job.skipped_usd += job.remaining_usd() / job.remaining_slices() as f64;
+                    job.slices_done += 1;
+                    job.schedule_next();
+                }
+
+                job.checkpoint();
+                continue;
+            }
+        }
+
+        // spread whatever is left over the remaining slices so skipped fills and jitter even out
+        let mut slice_usd = job.remaining_usd() / job.remaining_slices() as f64;
+        if job.remaining_slices() > 1 {
+            slice_usd = jitter(slice_usd, job.size_jitter);
+        }
+
+        if let Some(max_participation) = job.max_participation {
+            let since = (now() - job.interval_secs.max(60)) * 1000;
+            let traded_usd = match traded_volume(feed, &job.symbol, since).await {
+                Ok(traded_usd) => traded_usd,
+                Err(err) => {
+                    println!("Failed to fetch traded volume: {:#?}", err);
+                    0.0
+                }
+            };
+
+            let max_slice_usd = traded_usd * max_participation;
+            if slice_usd > max_slice_usd {
+                println!(
+                    "Capping slice at {}% of ${:.2} traded over the last interval",
+                    max_participation * 100.0,
+                    traded_usd
+                );
+                slice_usd = max_slice_usd;
+            }
+
+            if slice_usd <= 0.0 {
+                let interval = job.schedule_next();
+                println!(
+                    "No volume to participate in, deferring slice {} by {} seconds\n---",
+                    job.slices_done + 1,
+                    interval
+                );
+                job.checkpoint();
+                continue;
+            }
+        }
+
+        let sz = slice_usd / market_price;
+
+        println!("{}", "---".repeat(20));
+        println!("Order {} of {}", job.slices_done + 1, job.num_of_orders);
+        println!("Side: {}", job.side());
+        println!("Size in {}: {}", job.symbol, parse_size(sz, sz_decimals));
+        println!(
+            "Size in USD: {}",
+            parse_size(sz * market_price, sz_decimals)
+        );
+        println!("Market price: {}\n", market_price);
+
+        let slice = Slice {
+            asset,
+            sz_decimals,
+            is_buy: job.is_buy,
+            sz,
+            market_price,
+            limit_px: job.limit_px,
+            reduce_only: false,
+        };
+
+        let (filled_sz, filled_usd) =
+            match place_slice(exchange, wallet.clone(), slice, journal).await {
+                Ok(filled) => filled,
+                Err(err) => {
+                    println!("{:#?}", err);
+                    println!("Twap {} interrupted, run `hl twap resume {}` to continue", job.id, job.id);
+                    return;
+                }
+            };
+
+        job.slices_done += 1;
+        job.filled_sz += filled_sz;
+        job.filled_usd += filled_usd;
+        let interval = job.schedule_next();
+
+        job.checkpoint();
+
+        println!(
+            "Filled {} {} (${:.2}) of ${} target",
+            job.filled_sz, job.symbol, job.filled_usd, job.size
+        );
+
+        if job.slices_done != job.num_of_orders {
+            println!("Waiting for {} seconds", interval);
+            println!("{}", "-".repeat(5));
+        }
+    }
+
+    println!("Twap {} completed ✔️\n---", job.id);
+}
+
+/// A single IOC child order of an execution algo
+pub struct Slice {
+    pub asset: u32,
+    pub sz_decimals: u32,
+    pub is_buy: bool,
+    /// Size in the base asset
+    pub sz: f64,
+    pub market_price: f64,
+    /// The order price never crosses this price
+    pub limit_px: Option<f64>,
+    /// Only reduces the position, an exit never opens one the other way
+    pub reduce_only: bool,
+}
+
+/// Places a slice at market with 3% slippage, capped at its limit price. Returns the filled size
+/// in the base asset and in USD, or an error if the request itself failed
+pub async fn place_slice(
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    slice: Slice,
+    journal: &Journal,
+) -> Result<(f64, f64), anyhow::Error> {
+    let slippage = 3.0 / 100.0;
+    let side = if slice.is_buy { "Buy" } else { "Sell" };
+
+    let limit_price = match (slice.is_buy, slice.limit_px) {
+        (true, Some(limit_px)) => (slice.market_price * (1.0 + slippage)).min(limit_px),
+        (true, None) => slice.market_price * (1.0 + slippage),
+        (false, Some(limit_px)) => (slice.market_price * (1.0 - slippage)).max(limit_px),
+        (false, None) => slice.market_price * (1.0 - slippage),
+    };
+
+    let order = OrderRequest {
+        cloid: None,
+        asset: slice.asset,
+        is_buy: slice.is_buy,
+        limit_px: parse_price(limit_price),
+        sz: parse_size(slice.sz, slice.sz_decimals),
+        reduce_only: slice.reduce_only,
+        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+    };
+
+    match place_order(exchange, wallet, vec![order], journal).await? {
+        Response::Ok(order) => match order.data {
+            Some(StatusType::Statuses(statuses)) => Ok(statuses.iter().fold(
+                (0.0, 0.0),
+                |(sz, usd), status| match status {
+                    Status::Filled(order) => {
+                        println!("{} order {} was successfully filled.\n", side, order.oid);
+
+                        let total_sz = order.total_sz.parse::<f64>().unwrap_or(0.0);
+                        let avg_px = order.avg_px.parse::<f64>().unwrap_or(0.0);
+
+                        (sz + total_sz, usd + total_sz * avg_px)
+                    }
+                    Status::Error(msg) => {
+                        println!("{} order failed with error: {:#?}\n", side, msg);
+                        (sz, usd)
+                    }
+                    _ => (sz, usd),
+                },
+            )),
+            _ => Ok((0.0, 0.0)),
+        },
+        Response::Err(err) => Err(anyhow::anyhow!(err)),
+    }
+}
+
+/// The asset's latest trades from the trades feed, oldest first as (unix timestamp in ms, USD value)
+async fn recent_trades(feed: &MarketFeed, symbol: &str) -> Result<Vec<(u64, f64)>, anyhow::Error> {
+    let mut trades = feed
+        .info()
+        .recent_trades(symbol.to_uppercase())
+        .await?
+        .iter()
+        .map(|trade| Ok((trade.time, trade.px.parse::<f64>()? * trade.sz.parse::<f64>()?)))
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+    trades.sort_by_key(|(time, _)| *time);
+
+    Ok(trades)
+}
+
+/// USD volume traded in the asset since the unix timestamp in ms, from the trades feed. The feed
+/// only holds the latest trades, so a busy market is undercounted over a long window
+pub async fn traded_volume(
+    feed: &MarketFeed,
+    symbol: &str,
+    since: u64,
+) -> Result<f64, anyhow::Error> {
+    let volume = recent_trades(feed, symbol)
+        .await?
+        .iter()
+        .filter(|(time, _)| *time >= since)
+        .map(|(_, usd)| usd)
+        .sum();
+
+    Ok(volume)
+}
+
+/// Reads the USD volume traded in an asset from one read to the next, each trade of the feed is
+/// counted once
+pub struct VolumeWindow {
+    symbol: String,
+    /// Time of the newest trade counted, unix timestamp in ms
+    last: u64,
+}
+
+impl VolumeWindow {
+    /// Starts counting from now, the volume traded before isn't counted
+    pub async fn start(feed: &MarketFeed, symbol: &str) -> Result<Self, anyhow::Error> {
+        let mut window = Self {
+            symbol: symbol.to_string(),
+            last: now() * 1000,
+        };
+        window.read(feed).await?;
+
+        Ok(window)
+    }
+
+    /// USD volume traded since the last read
+    pub async fn read(&mut self, feed: &MarketFeed) -> Result<f64, anyhow::Error> {
+        let mut traded_usd = 0.0;
+
+        // the trades of a block share its time and are published together, none is left behind
+        // at a time already counted
+        for (time, usd) in recent_trades(feed, &self.symbol).await? {
+            if time <= self.last {
+                continue;
+            }
+
+            traded_usd += usd;
+            self.last = time;
+        }
+
+        Ok(traded_usd)
+    }
+}
@@ -38,7 +38,7 @@ impl TryFrom<&str> for OrderSize {
 
 pub struct TwapInterval {
     pub interval: Duration,
-    pub num_of_orders: u8,
+    pub num_of_orders: u32,
 }
 
 impl TryFrom<&str> for TwapInterval {
@@ -49,21 +49,74 @@ impl TryFrom<&str> for TwapInterval {
 
         let values = value.split(",").collect::<Vec<&str>>();
 
-        let interval = values.get(0).ok_or("Invalid interval")?;
+        let interval = values.first().ok_or("Invalid interval")?.trim();
         let num_of_orders = values.get(1).ok_or("Invalid num of orders")?;
 
-        let interval = interval.parse::<u64>().map_err(|_| "Invalid interval")?;
-        let interval = Duration::from_secs(interval * 60);
+        let TimeSpan(interval) = interval.try_into()?;
+
+        let num_of_orders = num_of_orders
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| "Invalid num of orders")?;
+
+        if interval.is_zero() || num_of_orders == 0 {
+            return Err("Interval and num of orders must be greater than 0".into());
+        }
 
         Ok(TwapInterval {
             interval,
-            num_of_orders: num_of_orders
-                .parse::<u8>()
-                .map_err(|_| "Invalid num of orders")?,
+            num_of_orders,
         })
     }
 }
 
+/// A length of time e.g 30s, 5m, 1h, 1d, a bare number is in minutes
+pub struct TimeSpan(pub Duration);
+
+impl TryFrom<&str> for TimeSpan {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+
+        let (value, unit) = match value.find(|c: char| c.is_ascii_alphabetic()) {
+            Some(index) => value.split_at(index),
+            None => (value, "m"),
+        };
+
+        let value = value.parse::<u64>().map_err(|_| "Invalid time")?;
+        let value = match unit {
+            "s" => Duration::from_secs(value),
+            "m" => Duration::from_secs(value * 60),
+            "h" => Duration::from_secs(value * 60 * 60),
+            "d" => Duration::from_secs(value * 60 * 60 * 24),
+            _ => return Err("Invalid time unit, expected s, m, h or d".into()),
+        };
+
+        Ok(TimeSpan(value))
+    }
+}
+
+/// A percentage stored as a fraction e.g 20% => 0.2
+pub struct Percent(pub f64);
+
+impl TryFrom<&str> for Percent {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        let value = value.strip_suffix("%").unwrap_or(value);
+
+        let value = value.parse::<f64>().map_err(|_| "Invalid percentage")?;
+
+        if value < 0.0 {
+            return Err("Percentage must not be negative".into());
+        }
+
+        Ok(Percent(value / 100.0))
+    }
+}
+
 pub struct SzPerInterval {
     pub size: f64,
     pub interval: u32,
@@ -115,6 +168,293 @@ impl TryFrom<&str> for LimitPrice {
     }
 }
 
+/// How scale rungs are spaced between the lower and upper price
+#[derive(Clone, Copy)]
+pub enum Distribution {
+    /// Equal steps between rungs
+    Linear,
+    /// Equal percentage steps between rungs
+    Geometric,
+    /// Each step is twice the previous one, moving away from the market
+    Exponential,
+}
+
+impl TryFrom<&str> for Distribution {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "linear" => Ok(Distribution::Linear),
+            "geometric" => Ok(Distribution::Geometric),
+            "exponential" => Ok(Distribution::Exponential),
+            _ => Err("Invalid distribution, expected linear, geometric or exponential".into()),
+        }
+    }
+}
+
+/// How the total size of a scale is split between rungs, from the rung nearest the market
+pub enum Weight {
+    Flat,
+    /// More size deeper in the range
+    Increasing,
+    /// More size nearer the market
+    Decreasing,
+    /// One weight per rung e.g 1,2,4
+    Custom(Vec<f64>),
+}
+
+impl TryFrom<&str> for Weight {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "flat" => Ok(Weight::Flat),
+            "increasing" => Ok(Weight::Increasing),
+            "decreasing" => Ok(Weight::Decreasing),
+            value => {
+                let weights = value
+                    .split(",")
+                    .map(|weight| weight.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| {
+                        "Invalid weight, expected flat, increasing, decreasing or custom weights e.g 1,2,4"
+                    })?;
+
+                if weights.iter().any(|weight| *weight < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+                    return Err("Custom weights must not be negative and can't all be 0".into());
+                }
+
+                Ok(Weight::Custom(weights))
+            }
+        }
+    }
+}
+
+/// A time of day in UTC e.g 14:00, stored as seconds since midnight
+pub struct TimeOfDay(pub u64);
+
+impl TryFrom<&str> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (hour, minute) = value
+            .trim()
+            .split_once(":")
+            .ok_or("Invalid time of day, expected HH:MM e.g 14:00")?;
+
+        let hour = hour.parse::<u64>().map_err(|_| "Invalid hour")?;
+        let minute = minute.parse::<u64>().map_err(|_| "Invalid minute")?;
+
+        if hour > 23 || minute > 59 {
+            return Err("Invalid time of day, expected HH:MM between 00:00 and 23:59".into());
+        }
+
+        Ok(TimeOfDay(hour * 60 * 60 + minute * 60))
+    }
+}
+
+/// What a scheduled job does about runs it missed while nothing was executing it
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MissedRuns {
+    /// Skips the missed runs and waits for the next scheduled one
+    Skip,
+    /// Catches up with a single run
+    Once,
+    /// Catches up with a single run sized for every missed run
+    All,
+}
+
+impl TryFrom<&str> for MissedRuns {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "skip" => Ok(MissedRuns::Skip),
+            "once" => Ok(MissedRuns::Once),
+            "all" => Ok(MissedRuns::All),
+            _ => Err("Invalid missed-run policy, expected skip, once or all".into()),
+        }
+    }
+}
+
+/// Target notional weights per asset e.g btc=40%,eth=30%,sol=-30%, negative weights are shorts
+pub struct Targets(pub Vec<(String, f64)>);
+
+impl TryFrom<&str> for Targets {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let targets = value
+            .split(",")
+            .map(|target| {
+                let (asset, weight) = target
+                    .trim()
+                    .split_once("=")
+                    .ok_or("Invalid target, expected asset=weight e.g btc=40%")?;
+
+                let weight = weight.trim();
+                let weight = weight.strip_suffix("%").unwrap_or(weight);
+                let weight = weight.parse::<f64>().map_err(|_| "Invalid target weight")?;
+
+                Ok((asset.trim().to_uppercase(), weight / 100.0))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        if targets.iter().any(|(asset, _)| asset.is_empty()) {
+            return Err("Invalid target, expected asset=weight e.g btc=40%".into());
+        }
+
+        Ok(Targets(targets))
+    }
+}
+
+/// How a pair trade's notional is split between its legs
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Hedge {
+    /// 50/50 split
+    #[default]
+    Equal,
+    /// Quote leg sized by the beta of the base returns to the quote returns
+    Beta,
+    /// Legs sized so each carries the same volatility
+    Vol,
+}
+
+impl TryFrom<&str> for Hedge {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "equal" => Ok(Hedge::Equal),
+            "beta" => Ok(Hedge::Beta),
+            "vol" => Ok(Hedge::Vol),
+            _ => Err("Invalid hedge, expected equal, beta or vol".into()),
+        }
+    }
+}
+
+/// What a pair trade watches: the base/quote price ratio or its log
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Spread {
+    #[default]
+    Ratio,
+    Log,
+}
+
+impl TryFrom<&str> for Spread {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "ratio" => Ok(Spread::Ratio),
+            "log" => Ok(Spread::Log),
+            _ => Err("Invalid spread, expected ratio or log".into()),
+        }
+    }
+}
+
+/// What a pair trade does when a leg doesn't fully fill on entry
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Recovery {
+    /// Retries the missing size within the slippage budget, then unwinds if it still didn't fill
+    #[default]
+    Retry,
+    /// Unwinds the filled legs right away
+    Unwind,
+}
+
+impl TryFrom<&str> for Recovery {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "retry" => Ok(Recovery::Retry),
+            "unwind" => Ok(Recovery::Unwind),
+            _ => Err("Invalid leg recovery, expected retry or unwind".into()),
+        }
+    }
+}
+
+/// Where an alert is delivered e.g stdout, desktop, command:./page.sh or webhook:https://...
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Sink {
+    Stdout,
+    /// A desktop notification through notify-send or osascript
+    Desktop,
+    /// A shell command run with the alert in its environment
+    Command(String),
+    /// An HTTP endpoint the alert is posted to as JSON
+    Webhook(String),
+}
+
+impl TryFrom<&str> for Sink {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+
+        let (kind, target) = match value.split_once(":") {
+            Some((kind, target)) => (kind.to_lowercase(), Some(target.trim())),
+            None => (value.to_lowercase(), None),
+        };
+
+        match (kind.as_str(), target) {
+            ("stdout", None) => Ok(Sink::Stdout),
+            ("desktop", None) => Ok(Sink::Desktop),
+            ("command", Some(command)) if !command.is_empty() => Ok(Sink::Command(command.to_string())),
+            ("webhook", Some(url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(Sink::Webhook(url.to_string()))
+            }
+            _ => Err("Invalid sink, expected stdout, desktop, command:<command> or webhook:<url>".into()),
+        }
+    }
+}
+
+/// Signed weight per asset of a basket e.g btc:+2,eth:+1,sol:-3, negative weights are shorts
+pub struct BasketLegs(pub Vec<(String, f64)>);
+
+impl TryFrom<&str> for BasketLegs {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let legs = value
+            .split(",")
+            .map(|leg| {
+                let (asset, weight) = leg
+                    .trim()
+                    .split_once(":")
+                    .ok_or("Invalid basket leg, expected asset:weight e.g btc:+2")?;
+
+                let weight = weight.trim();
+                let weight = weight
+                    .strip_prefix("+")
+                    .unwrap_or(weight)
+                    .parse::<f64>()
+                    .map_err(|_| "Invalid basket leg weight")?;
+
+                Ok((asset.trim().to_uppercase(), weight))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        if legs.iter().any(|(asset, weight)| asset.is_empty() || *weight == 0.0) {
+            return Err("Every basket leg needs an asset and a weight other than 0".into());
+        }
+
+        for (i, (asset, _)) in legs.iter().enumerate() {
+            if legs[..i].iter().any(|(other, _)| other == asset) {
+                return Err(format!("{} is in the basket more than once", asset));
+            }
+        }
+
+        Ok(BasketLegs(legs))
+    }
+}
+
 pub struct Pair {
     pub base: String,
     pub quote: String,
@@ -167,14 +507,14 @@ impl TryFrom<&str> for TpSl {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum MarginType {
     Cross,
     Isolated,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     pub private_key: String,
     pub default_margin: MarginType,
@@ -0,0 +1,434 @@
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ethers::signers::{LocalWallet, Signer};
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use hyperliquid::{
+    types::exchange::{
+        request::{Limit, OrderRequest, OrderType, Tif, TpSl, Trigger},
+        response::{Response as ExchangeResponse, Status, StatusType},
+    },
+    utils::{parse_price, parse_size},
+    Exchange, Info,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{
+    helpers::{asset_ctx, close_order, market_px, place_order, position_szi, read_body, status, token_eq},
+    journal::Journal,
+    types::{LimitPrice, OrderSize, TpSl as TPSL},
+};
+
+/// Header the secret may be sent in when the sender can set headers
+const SECRET_HEADER: &str = "x-webhook-secret";
+
+/// An alert payload e.g `{"action":"buy","asset":"eth","size":"$200","tp":"5%","sl":"2%"}`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Signal {
+    /// buy, sell or close
+    action: String,
+    asset: String,
+    /// Order size as `hl buy` takes it, for close a percentage of the position, all of it otherwise
+    size: Option<String>,
+    /// Limit price e.g 3000 or @3000, at market with 3% slippage otherwise
+    price: Option<String>,
+    tp: Option<String>,
+    sl: Option<String>,
+    /// Shared secret, for senders that can't set the secret header
+    secret: Option<String>,
+    /// Unique id of the alert, signals with an id already seen are dropped
+    id: Option<String>,
+}
+
+/// Signals seen and orders placed recently, so replays are dropped and bursts are limited
+struct Recent {
+    /// Dedup key of every signal seen within the dedup window and when it was seen
+    seen: HashMap<String, Instant>,
+    /// When each signal accepted in the last minute was received
+    accepted: VecDeque<Instant>,
+}
+
+/// Receives alert signals and maps them onto buy, sell, close, tp and sl orders
+pub struct Receiver {
+    info: Arc<Info>,
+    exchange: Arc<Exchange>,
+    wallet: Arc<LocalWallet>,
+    assets: HashMap<String, (u32, u32)>,
+    profile: String,
+    secret: String,
+    /// Percentage sizes are of the cross margin account value when true
+    cross: bool,
+    dedup_window: Duration,
+    max_per_minute: usize,
+    recent: Mutex<Recent>,
+}
+
+impl Receiver {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        info: Arc<Info>,
+        exchange: Arc<Exchange>,
+        wallet: Arc<LocalWallet>,
+        assets: HashMap<String, (u32, u32)>,
+        profile: String,
+        secret: String,
+        cross: bool,
+        dedup_window: Duration,
+        max_per_minute: usize,
+    ) -> Self {
+        Self {
+            info,
+            exchange,
+            wallet,
+            assets,
+            profile,
+            secret,
+            cross,
+            dedup_window,
+            max_per_minute,
+            recent: Mutex::new(Recent {
+                seen: HashMap::new(),
+                accepted: VecDeque::new(),
+            }),
+        }
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let (status, body) = match self.receive(request).await {
+            Ok(body) => (StatusCode::OK, body),
+            Err((status, message)) => (status, json!({ "error": message })),
+        };
+
+        println!("Signal answered {}: {}\n---", status.as_u16(), body);
+
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap_or_default()
+    }
+
+    async fn receive(&self, request: Request<Body>) -> Result<Value, (StatusCode, String)> {
+        if request.method() != Method::POST {
+            return Err((StatusCode::METHOD_NOT_ALLOWED, "Signals must be POSTed".into()));
+        }
+
+        let header_secret = request
+            .headers()
+            .get(SECRET_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let bytes = read_body(request.into_body()).await?;
+
+        let signal = serde_json::from_slice::<Signal>(&bytes)
+            .map_err(|err| (StatusCode::BAD_REQUEST, format!("Invalid signal: {}", err)))?;
+
+        if !header_secret
+            .as_ref()
+            .or(signal.secret.as_ref())
+            .is_some_and(|secret| token_eq(secret, &self.secret))
+        {
+            return Err((StatusCode::UNAUTHORIZED, "Missing or invalid secret".into()));
+        }
+
+        // a signal without an id is identified by its payload
+        let key = match &signal.id {
+            Some(id) => format!("id:{}", id),
+            None => String::from_utf8_lossy(&bytes).to_string(),
+        };
+
+        {
+            let mut recent = self.recent.lock().await;
+            let now = Instant::now();
+
+            let dedup_window = self.dedup_window;
+            recent.seen.retain(|_, seen_at| now.duration_since(*seen_at) < dedup_window);
+            while recent
+                .accepted
+                .front()
+                .is_some_and(|accepted_at| now.duration_since(*accepted_at) >= Duration::from_secs(60))
+            {
+                recent.accepted.pop_front();
+            }
+
+            if recent.seen.contains_key(&key) {
+                return Err((StatusCode::CONFLICT, "Duplicate signal dropped".into()));
+            }
+
+            if recent.accepted.len() >= self.max_per_minute {
+                return Err((StatusCode::TOO_MANY_REQUESTS, "Too many signals, try again later".into()));
+            }
+
+            recent.seen.insert(key.clone(), now);
+            recent.accepted.push_back(now);
+        }
+
+        println!("{}", "---".repeat(20));
+        println!(
+            "Signal: {} {} {}",
+            signal.action,
+            signal.asset,
+            signal.size.as_deref().unwrap_or("")
+        );
+
+        let result = self.execute(signal).await;
+
+        // a signal that failed can be sent again, only executed ones are duplicates
+        if result.is_err() {
+            self.recent.lock().await.seen.remove(&key);
+        }
+
+        result.map_err(|err| (StatusCode::BAD_REQUEST, err))
+    }
+
+    fn journal(&self, strategy: &str) -> Result<Journal, String> {
+        let mut journal = Journal::open().map_err(|err| err.to_string())?;
+
+        if let Err(err) = journal.record_command("webhook", &self.profile, strategy) {
+            println!("Failed to journal command: {:#?}", err);
+        }
+
+        Ok(journal)
+    }
+
+    async fn place(&self, order: OrderRequest, journal: &Journal) -> Result<Vec<Status>, String> {
+        match place_order(&self.exchange, self.wallet.clone(), vec![order], journal)
+            .await
+            .map_err(|err| err.to_string())?
+        {
+            ExchangeResponse::Ok(order) => match order.data {
+                Some(StatusType::Statuses(statuses)) => Ok(statuses),
+                _ => Err("Unexpected response".into()),
+            },
+            ExchangeResponse::Err(err) => Err(err),
+        }
+    }
+
+    async fn execute(&self, signal: Signal) -> Result<Value, String> {
+        let symbol = signal.asset.to_uppercase();
+        let (sz_decimals, asset) = *self
+            .assets
+            .get(&symbol)
+            .ok_or_else(|| format!("Failed to find asset: {}", signal.asset))?;
+
+        let asset_ctxs = self.info.contexts().await.map_err(|err| err.to_string())?;
+        let market_price = asset_ctx(&asset_ctxs, &symbol)
+            .map_err(|err| err.to_string())?
+            .ok_or_else(|| format!("Failed to find asset ctx: {}", symbol))?
+            .mark_px
+            .parse::<f64>()
+            .map_err(|err| err.to_string())?;
+
+        let is_buy = match signal.action.to_lowercase().as_str() {
+            "buy" => true,
+            "sell" => false,
+            "close" => return self.close(&signal, asset, sz_decimals, market_price).await,
+            _ => return Err(format!("Invalid action {}, expected buy, sell or close", signal.action)),
+        };
+
+        let order_size: OrderSize = signal
+            .size
+            .as_deref()
+            .ok_or("Size is required")?
+            .try_into()?;
+
+        let LimitPrice::Absolute(price) = signal.price.as_deref().unwrap_or("@0").try_into()?;
+
+        let tp: Option<TPSL> = signal.tp.as_deref().map(TryInto::try_into).transpose()?;
+        let sl: Option<TPSL> = signal.sl.as_deref().map(TryInto::try_into).transpose()?;
+
+        let usd = match order_size {
+            OrderSize::Absolute(usd) => usd,
+            OrderSize::Percent(percent) => {
+                let state = self
+                    .info
+                    .user_state(self.wallet.address())
+                    .await
+                    .map_err(|err| err.to_string())?;
+
+                let balance = if self.cross {
+                    state.cross_margin_summary.account_value
+                } else {
+                    state.margin_summary.account_value
+                };
+
+                balance.parse::<f64>().map_err(|err| err.to_string())? * (percent as f64 / 100.0)
+            }
+        };
+
+        let (order_type, limit_price) = if price == 0.0 {
+            (OrderType::Limit(Limit { tif: Tif::Ioc }), market_px(market_price, is_buy))
+        } else {
+            (OrderType::Limit(Limit { tif: Tif::Gtc }), price)
+        };
+
+        let sz = usd / market_price;
+        let entry_price = if price == 0.0 { market_price } else { price };
+
+        let journal = self.journal(if is_buy { "webhook buy" } else { "webhook sell" })?;
+
+        let entry = self
+            .place(
+                OrderRequest {
+                    cloid: None,
+                    asset,
+                    is_buy,
+                    limit_px: parse_price(limit_price),
+                    sz: parse_size(sz, sz_decimals),
+                    reduce_only: false,
+                    order_type,
+                },
+                &journal,
+            )
+            .await?;
+
+        // a rejected entry fails the signal, so a corrected one can be sent again
+        if let Some(Status::Error(msg)) = entry.iter().find(|status| matches!(status, Status::Error(_))) {
+            return Err(format!("Entry rejected: {}", msg));
+        }
+
+        // tp and sl cover what a market entry filled, all of a limit entry as it may still fill
+        let sz = if price == 0.0 {
+            entry
+                .iter()
+                .map(|status| match status {
+                    Status::Filled(order) => order.total_sz.parse::<f64>().unwrap_or(0.0),
+                    _ => 0.0,
+                })
+                .sum::<f64>()
+        } else {
+            sz
+        };
+
+        let entry = entry.iter().map(status).collect::<Vec<_>>();
+
+        println!("Entry: {}", Value::from(entry.clone()));
+
+        let mut response = json!({ "entry": entry });
+
+        if sz <= 0.0 {
+            return Ok(response);
+        }
+
+        // tp and sl sit above and below the entry of a buy, the other way round for a sell
+        for (name, target, tpsl) in [("tp", tp, TpSl::Tp), ("sl", sl, TpSl::Sl)] {
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let above = (name == "tp") == is_buy;
+
+            let trigger_price = match target {
+                TPSL::Absolute(value) => entry_price + if above { value.abs() } else { -value.abs() },
+                TPSL::Percent(value) => {
+                    entry_price * if above { (100.0 + value) / 100.0 } else { (100.0 - value) / 100.0 }
+                }
+                TPSL::Fixed(value) => value,
+            };
+
+            let statuses = self
+                .place(
+                    OrderRequest {
+                        cloid: None,
+                        asset,
+                        is_buy: !is_buy,
+                        limit_px: parse_price(trigger_price),
+                        sz: parse_size(sz, sz_decimals),
+                        reduce_only: true,
+                        order_type: OrderType::Trigger(Trigger {
+                            trigger_px: parse_price(trigger_price).parse().unwrap_or(trigger_price),
+                            is_market: true,
+                            tpsl,
+                        }),
+                    },
+                    &journal,
+                )
+                .await;
+
+            println!("{} at {}: {:?}", name, parse_price(trigger_price), statuses);
+
+            response[name] = match statuses {
+                Ok(statuses) => statuses.iter().map(status).collect::<Vec<_>>().into(),
+                Err(err) => json!({ "error": err }),
+            };
+        }
+
+        Ok(response)
+    }
+
+    /// Closes the position in an asset at market, all of it or a percentage given as the size
+    async fn close(
+        &self,
+        signal: &Signal,
+        asset: u32,
+        sz_decimals: u32,
+        market_price: f64,
+    ) -> Result<Value, String> {
+        let percent = match signal.size.as_deref().map(OrderSize::try_from).transpose()? {
+            Some(OrderSize::Percent(percent)) => percent as f64 / 100.0,
+            Some(OrderSize::Absolute(_)) => return Err("Close size must be a percentage of the position".into()),
+            None => 1.0,
+        };
+
+        let state = self
+            .info
+            .user_state(self.wallet.address())
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let szi = position_szi(&state, &signal.asset).map_err(|err| err.to_string())?;
+
+        if szi == 0.0 {
+            return Err(format!("No open position in {}", signal.asset));
+        }
+
+        let journal = self.journal("webhook close")?;
+
+        let statuses = self
+            .place(close_order(asset, sz_decimals, szi, percent, market_price), &journal)
+            .await?;
+
+        Ok(json!({ "close": statuses.iter().map(status).collect::<Vec<_>>() }))
+    }
+}
+
+/// Receives signals on `bind` until ctrl-c
+pub async fn listen(receiver: Receiver, bind: SocketAddr) -> Result<(), anyhow::Error> {
+    let receiver = Arc::new(receiver);
+
+    let make_service = make_service_fn(move |_| {
+        let receiver = receiver.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let receiver = receiver.clone();
+
+                async move { Ok::<_, Infallible>(receiver.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&bind)?.serve(make_service);
+
+    println!("Receiving signals on http://{}\n---", bind);
+
+    server
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,543 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid::{types::info::response::{AssetContext, Ctx}, utils::parse_size, Exchange};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::asset_ctx,
+    journal::Journal,
+    market::MarketFeed,
+    twap::{now, place_slice, Slice},
+};
+
+/// A value a condition watches
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Operand {
+    /// Mark price of an asset
+    Mark(String),
+    /// Mark price of one asset over another's
+    Ratio(String, String),
+    /// Current hourly funding rate of an asset
+    Funding(String),
+    /// Account value in USD
+    Equity,
+    /// Unrealized PnL in USD of the open position in an asset
+    PositionPnl(String),
+    /// How far the mark price is from the liquidation price, as a fraction of the mark price, of
+    /// the position in an asset or of the position closest to liquidation
+    LiqDistance(Option<String>),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Mark(symbol) => write!(f, "{}", symbol.to_lowercase()),
+            Operand::Ratio(base, quote) => write!(f, "{}/{}", base.to_lowercase(), quote.to_lowercase()),
+            Operand::Funding(symbol) => write!(f, "funding({})", symbol.to_lowercase()),
+            Operand::Equity => write!(f, "equity"),
+            Operand::PositionPnl(symbol) => write!(f, "position {} pnl", symbol.to_lowercase()),
+            Operand::LiqDistance(Some(symbol)) => write!(f, "position {} liq-distance", symbol.to_lowercase()),
+            Operand::LiqDistance(None) => write!(f, "liq-distance"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Above,
+    AboveOrEqual,
+    Below,
+    BelowOrEqual,
+}
+
+impl Comparison {
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AboveOrEqual => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::BelowOrEqual => value <= threshold,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Comparison::Above => write!(f, ">"),
+            Comparison::AboveOrEqual => write!(f, ">="),
+            Comparison::Below => write!(f, "<"),
+            Comparison::BelowOrEqual => write!(f, "<="),
+        }
+    }
+}
+
+/// Comparisons of market or account values combined with and/or, and binds tighter than or
+/// e.g `btc > 70000 and (funding(eth) < 0 or eth/btc < 0.05)`
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Condition {
+    Compare(Operand, Comparison, f64),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Compare(operand, comparison, threshold) => {
+                write!(f, "{} {} {}", operand, comparison, threshold)
+            }
+            Condition::And(left, right) => {
+                // an or nested in an and needs its parentheses back
+                for (i, side) in [left, right].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " and ")?;
+                    }
+                    match side.as_ref() {
+                        Condition::Or(..) => write!(f, "({})", side)?,
+                        _ => write!(f, "{}", side)?,
+                    }
+                }
+                Ok(())
+            }
+            Condition::Or(left, right) => write!(f, "{} or {}", left, right),
+        }
+    }
+}
+
+impl TryFrom<&str> for Condition {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let tokens = tokenize(value)?;
+        let mut parser = Parser { tokens, pos: 0 };
+
+        let condition = parser.or()?;
+
+        match parser.tokens.get(parser.pos) {
+            Some(token) => Err(format!("Unexpected '{}' in condition", token)),
+            None => Ok(condition),
+        }
+    }
+}
+
+impl Condition {
+    /// Every operand the condition reads
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Condition::Compare(operand, _, _) => vec![operand],
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                let mut operands = left.operands();
+                operands.extend(right.operands());
+                operands
+            }
+        }
+    }
+
+    /// Assets the condition reads, upper cased
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols = vec![];
+
+        for operand in self.operands() {
+            let operand_symbols = match operand {
+                Operand::Mark(symbol)
+                | Operand::Funding(symbol)
+                | Operand::PositionPnl(symbol)
+                | Operand::LiqDistance(Some(symbol)) => vec![symbol],
+                Operand::Ratio(base, quote) => vec![base, quote],
+                Operand::Equity | Operand::LiqDistance(None) => vec![],
+            };
+
+            for symbol in operand_symbols {
+                if !symbols.contains(symbol) {
+                    symbols.push(symbol.clone());
+                }
+            }
+        }
+
+        symbols
+    }
+
+    /// Whether the condition reads the account, which costs a request per check
+    fn reads_account(&self) -> bool {
+        self.operands().iter().any(|operand| {
+            matches!(operand, Operand::Equity | Operand::PositionPnl(_) | Operand::LiqDistance(_))
+        })
+    }
+
+    /// Whether the condition holds for the given operand values, a comparison whose value is
+    /// missing e.g the pnl of a position that isn't open doesn't hold
+    pub fn evaluate(&self, values: &[(&Operand, f64)]) -> bool {
+        match self {
+            Condition::Compare(operand, comparison, threshold) => values
+                .iter()
+                .find(|(other, _)| *other == operand)
+                .is_some_and(|(_, value)| comparison.holds(*value, *threshold)),
+            Condition::And(left, right) => left.evaluate(values) && right.evaluate(values),
+            Condition::Or(left, right) => left.evaluate(values) || right.evaluate(values),
+        }
+    }
+}
+
+fn tokenize(value: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = value.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '/' => tokens.push(c.to_string()),
+            '>' | '<' => {
+                if chars.next_if_eq(&'=').is_some() {
+                    tokens.push(format!("{}=", c));
+                } else {
+                    tokens.push(c.to_string());
+                }
+            }
+            '&' if chars.next_if_eq(&'&').is_some() => tokens.push("and".to_string()),
+            '|' if chars.next_if_eq(&'|').is_some() => tokens.push("or".to_string()),
+            c if c.is_alphanumeric() || matches!(c, '.' | '-' | '$' | '%' | '_') => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | '%' | '_')) {
+                    word.push(c);
+                }
+                tokens.push(word);
+            }
+            c => return Err(format!("Unexpected '{}' in condition", c)),
+        }
+    }
+
+    if tokens.is_empty() {
+        return Err("Condition is empty".into());
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_is(&self, expected: &str) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|token| token.eq_ignore_ascii_case(expected))
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token.eq_ignore_ascii_case(expected) => Ok(()),
+            Some(token) => Err(format!("Expected '{}', found '{}'", expected, token)),
+            None => Err(format!("Expected '{}' at the end of the condition", expected)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Condition, String> {
+        let mut condition = self.and()?;
+
+        while self.peek_is("or") {
+            self.pos += 1;
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, String> {
+        let mut condition = self.comparison()?;
+
+        while self.peek_is("and") {
+            self.pos += 1;
+            condition = Condition::And(Box::new(condition), Box::new(self.comparison()?));
+        }
+
+        Ok(condition)
+    }
+
+    fn comparison(&mut self) -> Result<Condition, String> {
+        if self.peek_is("(") {
+            self.pos += 1;
+            let condition = self.or()?;
+            self.expect(")")?;
+            return Ok(condition);
+        }
+
+        let operand = self.operand()?;
+
+        let comparison = match self.next().as_deref() {
+            Some(">") => Comparison::Above,
+            Some(">=") => Comparison::AboveOrEqual,
+            Some("<") => Comparison::Below,
+            Some("<=") => Comparison::BelowOrEqual,
+            Some(token) => return Err(format!("Expected a comparison after {}, found '{}'", operand, token)),
+            None => return Err(format!("Expected a comparison after {}", operand)),
+        };
+
+        let threshold = self.next().ok_or("Expected a value at the end of the condition")?;
+
+        Ok(Condition::Compare(operand, comparison, number(&threshold)?))
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        let word = self.next().ok_or("Expected an asset, funding(asset), equity, position or liq-distance")?;
+
+        if word.eq_ignore_ascii_case("equity") {
+            return Ok(Operand::Equity);
+        }
+
+        if word.eq_ignore_ascii_case("liq-distance") {
+            return Ok(Operand::LiqDistance(None));
+        }
+
+        if word.eq_ignore_ascii_case("position") {
+            let symbol = self.symbol()?;
+
+            return match self.next() {
+                Some(word) if word.eq_ignore_ascii_case("pnl") => Ok(Operand::PositionPnl(symbol)),
+                Some(word) if word.eq_ignore_ascii_case("liq-distance") => Ok(Operand::LiqDistance(Some(symbol))),
+                Some(word) => Err(format!("Expected pnl or liq-distance after position, found '{}'", word)),
+                None => Err("Expected pnl or liq-distance at the end of the condition".into()),
+            };
+        }
+
+        if word.eq_ignore_ascii_case("funding") {
+            self.expect("(")?;
+            let symbol = self.symbol()?;
+            self.expect(")")?;
+            return Ok(Operand::Funding(symbol));
+        }
+
+        self.pos -= 1;
+        let symbol = self.symbol()?;
+
+        if self.peek_is("/") {
+            self.pos += 1;
+            return Ok(Operand::Ratio(symbol, self.symbol()?));
+        }
+
+        Ok(Operand::Mark(symbol))
+    }
+
+    fn symbol(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(word)
+                if word.chars().all(|c| c.is_alphanumeric())
+                    && !["and", "or"].iter().any(|keyword| word.eq_ignore_ascii_case(keyword)) =>
+            {
+                Ok(word.to_uppercase())
+            }
+            Some(word) => Err(format!("Invalid asset '{}'", word)),
+            None => Err("Expected an asset at the end of the condition".into()),
+        }
+    }
+}
+
+/// A number e.g 70000, $1000 or 0.01%
+fn number(value: &str) -> Result<f64, String> {
+    let (value, scale) = match value.strip_suffix('%') {
+        Some(value) => (value, 0.01),
+        None => (value, 1.0),
+    };
+
+    value
+        .trim_start_matches('$')
+        .parse::<f64>()
+        .map(|value| value * scale)
+        .map_err(|_| format!("Invalid value '{}'", value))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WhenJob {
+    pub condition: Condition,
+    pub symbol: String,
+    pub is_buy: bool,
+    /// Order size in USD
+    pub size: f64,
+    /// Order limit price, the order is placed at market with 3% slippage otherwise
+    pub limit_px: Option<f64>,
+    /// Unix time after which the condition is no longer watched
+    pub expires_at: Option<u64>,
+}
+
+impl WhenJob {
+    pub fn describe(&self) -> String {
+        format!(
+            "when \"{}\" {} ${} {}",
+            self.condition,
+            if self.is_buy { "buy" } else { "sell" },
+            self.size,
+            self.symbol.to_lowercase()
+        )
+    }
+}
+
+fn ctx<'a>(asset_ctxs: &'a Vec<AssetContext>, symbol: &str) -> Result<&'a Ctx, anyhow::Error> {
+    asset_ctx(asset_ctxs, symbol)?.ok_or_else(|| anyhow::anyhow!("Failed to find asset ctx: {}", symbol))
+}
+
+/// Reads the current value of every operand of the conditions, fetching the market and the
+/// account once. Operands without a value e.g the pnl of a position that isn't open are left out.
+pub async fn values<'a>(
+    conditions: &[&'a Condition],
+    feed: &MarketFeed,
+    wallet: &LocalWallet,
+) -> Result<Vec<(&'a Operand, f64)>, anyhow::Error> {
+    let asset_ctxs = feed.contexts().await?;
+
+    let state = if conditions.iter().any(|condition| condition.reads_account()) {
+        Some(feed.info().user_state(wallet.address()).await?)
+    } else {
+        None
+    };
+
+    // coin, unrealized pnl and liquidation distance of every open position
+    let mut positions = vec![];
+    for ap in state.iter().flat_map(|state| state.asset_positions.iter()) {
+        let coin = ap.position.coin.to_uppercase();
+        let mark_px = ctx(&asset_ctxs, &coin)?.mark_px.parse::<f64>()?;
+
+        let liq_distance = match &ap.position.liquidation_px {
+            Some(liquidation_px) => Some((mark_px - liquidation_px.parse::<f64>()?).abs() / mark_px),
+            None => None,
+        };
+
+        positions.push((coin, ap.position.unrealized_pnl.parse::<f64>()?, liq_distance));
+    }
+
+    let mut values = vec![];
+
+    for operand in conditions.iter().flat_map(|condition| condition.operands()) {
+        if values.iter().any(|(other, _)| *other == operand) {
+            continue;
+        }
+
+        let value = match operand {
+            Operand::Mark(symbol) => Some(ctx(&asset_ctxs, symbol)?.mark_px.parse::<f64>()?),
+            Operand::Ratio(base, quote) => Some(
+                ctx(&asset_ctxs, base)?.mark_px.parse::<f64>()?
+                    / ctx(&asset_ctxs, quote)?.mark_px.parse::<f64>()?,
+            ),
+            Operand::Funding(symbol) => Some(ctx(&asset_ctxs, symbol)?.funding.parse::<f64>()?),
+            Operand::Equity => match &state {
+                Some(state) => Some(state.margin_summary.account_value.parse::<f64>()?),
+                None => None,
+            },
+            Operand::PositionPnl(symbol) => positions
+                .iter()
+                .find(|(coin, _, _)| coin == symbol)
+                .map(|(_, pnl, _)| *pnl),
+            Operand::LiqDistance(Some(symbol)) => positions
+                .iter()
+                .find(|(coin, _, _)| coin == symbol)
+                .and_then(|(_, _, liq_distance)| *liq_distance),
+            Operand::LiqDistance(None) => positions
+                .iter()
+                .filter_map(|(_, _, liq_distance)| *liq_distance)
+                .reduce(f64::min),
+        };
+
+        if let Some(value) = value {
+            values.push((operand, value));
+        }
+    }
+
+    Ok(values)
+}
+
+/// Watches the condition every 5 seconds and places the order once it holds, until it expires
+pub async fn run(
+    job: WhenJob,
+    feed: &MarketFeed,
+    exchange: &Exchange,
+    wallet: Arc<LocalWallet>,
+    assets: &HashMap<String, (u32, u32)>,
+    journal: &Journal,
+) {
+    let (sz_decimals, asset) = match assets.get(&job.symbol) {
+        Some(asset) => *asset,
+        None => {
+            println!("Failed to find asset: {}", job.symbol);
+            return;
+        }
+    };
+
+    println!("Watching: {}\n---", job.condition);
+
+    loop {
+        if job.expires_at.is_some_and(|expires_at| now() >= expires_at) {
+            println!("Condition {} expired without being met", job.condition);
+            return;
+        }
+
+        let values = match values(&[&job.condition], feed, &wallet).await {
+            Ok(values) => values,
+            Err(err) => {
+                println!("Failed to read condition values: {:#?}. Checking again in 5 seconds\n---", err);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        let current = values
+            .iter()
+            .map(|(operand, value)| format!("{}: {}", operand, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if job.condition.evaluate(&values) {
+            println!("Condition met: {} ({})", job.condition, current);
+            break;
+        }
+
+        println!("{}. Checking again in 5 seconds\n---", current);
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+
+    let market_price = match feed.mark_px(&job.symbol).await {
+        Ok(price) => price,
+        Err(err) => {
+            println!("Failed to fetch market price: {:#?}", err);
+            return;
+        }
+    };
+
+    let sz = job.size / market_price;
+
+    println!("{}", "---".repeat(20));
+    println!("Side: {}", if job.is_buy { "Buy" } else { "Sell" });
+    println!("Size in {}: {}", job.symbol, parse_size(sz, sz_decimals));
+    println!("Size in USD: {}", job.size);
+    println!("Market price: {}\n", market_price);
+
+    let slice = Slice {
+        asset,
+        sz_decimals,
+        is_buy: job.is_buy,
+        sz,
+        market_price,
+        limit_px: job.limit_px,
+        reduce_only: false,
+    };
+
+    match place_slice(exchange, wallet, slice, journal).await {
+        Ok((filled_sz, _)) if filled_sz > 0.0 => {}
+        Ok(_) => println!("Order didn't fill"),
+        Err(err) => println!("Order failed with error: {:#?}", err),
+    }
+}
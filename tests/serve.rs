@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Once};
+
+use futures::{future::BoxFuture, FutureExt};
+use hl::{
+    journal::Journal,
+    serve::{Api, Backend},
+};
+use hyper::{body::to_bytes, Body, Request, StatusCode};
+use hyperliquid::types::{
+    exchange::{
+        request::{CancelRequest, OrderRequest},
+        response::Status,
+    },
+    info::response::{OpenOrder, UserState},
+};
+use serde_json::{json, Value};
+
+const TOKEN: &str = "test-token";
+
+/// A short 0.1 ETH position, as the info endpoint returns it
+const USER_STATE: &str = r#"{
+    "assetPositions": [{
+        "position": {
+            "coin": "ETH",
+            "cumFunding": {"allTime": "0.0", "sinceChange": "0.0", "sinceOpen": "0.0"},
+            "entryPx": "2000.0",
+            "leverage": {"type": "cross", "value": 20},
+            "liquidationPx": null,
+            "marginUsed": "10.0",
+            "maxLeverage": 50,
+            "positionValue": "200.0",
+            "returnOnEquity": "0.0",
+            "szi": "-0.1",
+            "unrealizedPnl": "0.0"
+        },
+        "type": "oneWay"
+    }],
+    "crossMaintenanceMarginUsed": "2.0",
+    "crossMarginSummary": {"accountValue": "1000.0", "totalMarginUsed": "10.0", "totalNtlPos": "200.0", "totalRawUsd": "1200.0"},
+    "marginSummary": {"accountValue": "1000.0", "totalMarginUsed": "10.0", "totalNtlPos": "200.0", "totalRawUsd": "1200.0"},
+    "time": 1700000000000,
+    "withdrawable": "990.0"
+}"#;
+
+/// Answers every call with a 2000 mark price and rests every order, keeping what it was sent
+#[derive(Clone, Default)]
+struct Mock {
+    placed: Arc<Mutex<Vec<OrderRequest>>>,
+    cancelled: Arc<Mutex<Vec<CancelRequest>>>,
+}
+
+impl Backend for Mock {
+    fn mark_px<'a>(&'a self, _: &'a str) -> BoxFuture<'a, Result<f64, anyhow::Error>> {
+        async { Ok(2000.0) }.boxed()
+    }
+
+    fn user_state(&self) -> BoxFuture<'_, Result<UserState, anyhow::Error>> {
+        async { Ok(serde_json::from_str(USER_STATE)?) }.boxed()
+    }
+
+    fn open_orders(&self) -> BoxFuture<'_, Result<Vec<OpenOrder>, anyhow::Error>> {
+        async { Ok(vec![]) }.boxed()
+    }
+
+    fn place_orders<'a>(
+        &'a self,
+        orders: Vec<OrderRequest>,
+        _: &'a Journal,
+    ) -> BoxFuture<'a, Result<Vec<Status>, anyhow::Error>> {
+        async move {
+            let mut placed = self.placed.lock().unwrap();
+            let statuses = (0..orders.len())
+                .map(|i| serde_json::from_value(json!({ "resting": { "oid": placed.len() + i + 1 } })))
+                .collect::<Result<Vec<_>, _>>()?;
+            placed.extend(orders);
+
+            Ok(statuses)
+        }
+        .boxed()
+    }
+
+    fn place_batch<'a>(
+        &'a self,
+        orders: Vec<OrderRequest>,
+        journal: &'a Journal,
+    ) -> BoxFuture<'a, Result<Vec<Status>, anyhow::Error>> {
+        self.place_orders(orders, journal)
+    }
+
+    fn cancel_orders(&self, cancels: Vec<CancelRequest>) -> BoxFuture<'_, Result<(), anyhow::Error>> {
+        async move {
+            self.cancelled.lock().unwrap().extend(cancels);
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+fn api() -> (Api, Mock) {
+    // the journal lives under the home dir, keep the tests out of the real one
+    static HOME: Once = Once::new();
+    HOME.call_once(|| std::env::set_var("HOME", std::env::temp_dir().join("hl-serve-tests")));
+
+    let mock = Mock::default();
+    let assets = HashMap::from([("BTC".to_string(), (5, 0)), ("ETH".to_string(), (4, 1))]);
+
+    (Api::new(mock.clone(), assets, "dev".into(), TOKEN.into()), mock)
+}
+
+async fn call(api: &Api, method: &str, path: &str, token: Option<&str>, body: Value) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(path);
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = api
+        .handle(request.body(Body::from(body.to_string())).unwrap())
+        .await;
+    let status = response.status();
+    let body = to_bytes(response.into_body()).await.unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn rejects_a_missing_or_wrong_token() {
+    let (api, mock) = api();
+
+    let (status, body) = call(&api, "GET", "/orders", None, Value::Null).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body["error"].is_string());
+
+    let (status, _) = call(&api, "POST", "/orders", Some("wrong"), json!({"asset": "eth", "side": "buy", "size": 100})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(mock.placed.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn refuses_oversized_bodies() {
+    let (api, mock) = api();
+
+    let (status, body) = call(&api, "POST", "/orders", Some(TOKEN), json!({"asset": "eth", "side": "buy", "size": 100, "pad": "x".repeat(70_000)})).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(body["error"].is_string());
+    assert!(mock.placed.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn answers_unknown_routes_with_not_found() {
+    let (api, _) = api();
+
+    for (method, path) in [("GET", "/nope"), ("PUT", "/orders"), ("DELETE", "/orders/eth"), ("GET", "/positions/eth/close")] {
+        let (status, body) = call(&api, method, path, Some(TOKEN), Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, path);
+        assert!(body["error"].as_str().unwrap().starts_with("No route"));
+    }
+
+    let (status, _) = call(&api, "GET", "/orders", Some(TOKEN), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn places_orders() {
+    let (api, mock) = api();
+
+    let (status, body) = call(&api, "POST", "/orders", Some(TOKEN), json!({"asset": "eth", "side": "buy", "size": 200})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([{ "status": "resting", "oid": 1 }]));
+
+    let (status, _) = call(&api, "POST", "/orders", Some(TOKEN), json!({"asset": "eth", "side": "sell", "size": 100, "price": 2500, "reduceOnly": true})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let placed = mock.placed.lock().unwrap();
+    // a market order is an ioc limit 3% through the mark price
+    assert_eq!(placed[0].asset, 1);
+    assert!(placed[0].is_buy);
+    assert!((placed[0].limit_px.parse::<f64>().unwrap() - 2060.0).abs() < 1e-6);
+    assert_eq!(placed[0].sz.parse::<f64>().unwrap(), 0.1);
+    assert!(!placed[1].is_buy);
+    assert!(placed[1].reduce_only);
+    assert_eq!(placed[1].limit_px.parse::<f64>().unwrap(), 2500.0);
+    assert_eq!(placed[1].sz.parse::<f64>().unwrap(), 0.04);
+}
+
+#[tokio::test]
+async fn rejects_invalid_orders() {
+    let (api, mock) = api();
+
+    for body in [
+        json!({"asset": "eth", "side": "long", "size": 100}),
+        json!({"asset": "eth", "side": "buy", "size": 0}),
+        json!({"asset": "eth", "side": "buy"}),
+    ] {
+        let (status, _) = call(&api, "POST", "/orders", Some(TOKEN), body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, _) = call(&api, "POST", "/orders", Some(TOKEN), json!({"asset": "doge", "side": "buy", "size": 100})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(mock.placed.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn cancels_orders() {
+    let (api, mock) = api();
+
+    let (status, body) = call(&api, "DELETE", "/orders/eth/42", Some(TOKEN), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "cancelled": 42 }));
+
+    let (status, _) = call(&api, "DELETE", "/orders/eth/abc", Some(TOKEN), Value::Null).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let cancelled = mock.cancelled.lock().unwrap();
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!((cancelled[0].asset, cancelled[0].oid), (1, 42));
+}
+
+#[tokio::test]
+async fn closes_positions() {
+    let (api, mock) = api();
+
+    let (status, body) = call(&api, "POST", "/positions/eth/close", Some(TOKEN), Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([{ "status": "resting", "oid": 1 }]));
+
+    let (status, _) = call(&api, "POST", "/positions/btc/close", Some(TOKEN), Value::Null).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let placed = mock.placed.lock().unwrap();
+    assert_eq!(placed.len(), 1);
+    // the short is bought back reduce-only, 3% above the mark price
+    assert!(placed[0].is_buy);
+    assert!(placed[0].reduce_only);
+    assert_eq!(placed[0].sz.parse::<f64>().unwrap(), 0.1);
+    assert!((placed[0].limit_px.parse::<f64>().unwrap() - 2060.0).abs() < 1e-6);
+}